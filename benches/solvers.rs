@@ -0,0 +1,40 @@
+//! Compares solver backends on the 17-clue corpus, run with `cargo bench`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use gui_experiment::{Backtracking, Dlx, Pack, Solver};
+
+const CORPUS: &str = include_str!("../puzzles/seventeen.txt");
+
+fn corpus() -> Vec<gui_experiment::SudokuModel> {
+    let pack = Pack::parse(CORPUS).expect("corpus should parse");
+    (0..pack.len()).map(|index| pack.model(index)).collect()
+}
+
+fn bench_backend(criterion: &mut Criterion, name: &str, solver: &mut impl Solver) {
+    let corpus = corpus();
+    let mut group = criterion.benchmark_group(name);
+    group.sample_size(10);
+    group.bench_function("solve", |bencher| {
+        bencher.iter(|| {
+            for model in &corpus {
+                assert!(solver.solve(model).is_some());
+            }
+        })
+    });
+    group.bench_function("unique", |bencher| {
+        bencher.iter(|| {
+            for model in &corpus {
+                assert_eq!(solver.count_solutions(model, 2), 1);
+            }
+        })
+    });
+    group.finish();
+}
+
+fn backends(criterion: &mut Criterion) {
+    bench_backend(criterion, "backtracking", &mut Backtracking);
+    bench_backend(criterion, "dlx", &mut Dlx::new());
+}
+
+criterion_group!(benches, backends);
+criterion_main!(benches);
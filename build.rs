@@ -1,32 +1,44 @@
 fn main() {
-    const _: () = {
-        let enabled_features = {
-            cfg!(feature = "slint") as u8
-                + cfg!(feature = "floem") as u8
-                + cfg!(feature = "iced") as u8
-                + cfg!(feature = "egui") as u8
-                + cfg!(feature = "gpui") as u8
-                + cfg!(feature = "wasm") as u8
-                + cfg!(feature = "xilem") as u8
-                + cfg!(feature = "leptos") as u8
-                + cfg!(feature = "rui") as u8
-                + cfg!(feature = "ratatui") as u8
-                + cfg!(feature = "kas") as u8
-        };
-
-        match enabled_features {
-            0 => panic!("None of the features were enabled, exactly one must be."),
-            1 => {}
-            2.. => panic!("You can't compile to run multiple GUIs at the same time"),
-        }
-    };
-
     #[cfg(feature = "slint")]
     slint_build::compile("ui/main.slint").unwrap();
 
     // Auto-patch gpui shaders on macOS to fix Metal compilation
     #[cfg(all(target_os = "macos", feature = "gpui"))]
     patch_gpui_shaders();
+
+    #[cfg(feature = "capi")]
+    c_api();
+}
+
+/// Generates the C header into `OUT_DIR`, `tests/c_api.rs` checks that `include/sudoku.h` matches
+/// it. The C test program is compiled against it, and only linked into tests, statically, so that
+/// neither the library has it, nor a shared library found at runtime takes part.
+#[cfg(feature = "capi")]
+fn c_api() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=tests/c/c_api_test.c");
+
+    // Only the C ABI module is parsed, so that other constants of the crate don't leak into the
+    // header, the board type is declared in `cbindgen.toml` instead
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets the manifest dir");
+    let out_dir = std::env::var("OUT_DIR").expect("cargo sets the output dir");
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .expect("Failed to read cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{crate_dir}/src/capi.rs"))
+        .generate()
+        .expect("Failed to generate the C header")
+        .write_to_file(format!("{out_dir}/sudoku.h"));
+
+    cc::Build::new()
+        .file("tests/c/c_api_test.c")
+        .include(&out_dir)
+        .cargo_metadata(false)
+        .compile("c_api_test");
+    println!("cargo:rustc-link-arg-tests={out_dir}/libc_api_test.a");
 }
 
 #[cfg(all(target_os = "macos", feature = "gpui"))]
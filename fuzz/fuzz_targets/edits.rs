@@ -0,0 +1,44 @@
+#![no_main]
+
+//! Every 4 bytes are an edit: what to do, the column, the row and the value. Values cover the whole
+//! `u8` and `i8` range, so the wrapping of `set` and `add` is exercised, `u8::MAX` included.
+
+use gui_experiment::{Origin, SudokuModel};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut model = SudokuModel::example();
+    let givens = model.givens_line();
+    for edit in data.chunks_exact(4) {
+        let (x, y, value) = (edit[1] as usize % 9, edit[2] as usize % 9, edit[3]);
+        let before = model.get(x, y).value();
+        let enabled = model.get(x, y).enabled();
+        let changed = match edit[0] % 4 {
+            0 => {
+                model.set(x, y, value);
+                Some(value)
+            }
+            1 => {
+                model.add(x, y, value as i8);
+                Some(before.wrapping_add_signed(value as i8))
+            }
+            2 => {
+                model.place(x, y, value, Origin::Hint);
+                Some(value)
+            }
+            _ => {
+                model.undo();
+                None
+            }
+        };
+        if let Some(value) = changed {
+            let expected = match value {
+                _ if !enabled => before,
+                u8::MAX => 9,
+                value => value % 10,
+            };
+            assert_eq!(model.get(x, y).value(), expected);
+        }
+        assert_eq!(model.givens_line(), givens);
+    }
+});
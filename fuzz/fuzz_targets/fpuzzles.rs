@@ -0,0 +1,16 @@
+#![no_main]
+
+use gui_experiment::fpuzzles;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    if let Ok(import) = fpuzzles::decode(text) {
+        let model = import.puzzle.model;
+        let again = fpuzzles::decode(&fpuzzles::sudokupad_link(&import.puzzle))
+            .expect("exported puzzle can be imported");
+        assert!(again.warnings.is_empty());
+        assert_eq!(again.puzzle.model.line(), model.line());
+        assert_eq!(again.puzzle.model.givens_line(), model.givens_line());
+        assert_eq!(again.puzzle.model.regions(), model.regions());
+    }
+});
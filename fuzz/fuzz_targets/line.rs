@@ -0,0 +1,12 @@
+#![no_main]
+
+use gui_experiment::SudokuModel;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    if let Ok(model) = SudokuModel::from_line(text) {
+        let line = model.line();
+        assert_eq!(SudokuModel::from_line(&line).unwrap().line(), line);
+        assert_eq!(model.givens_line(), line);
+    }
+});
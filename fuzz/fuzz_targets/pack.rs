@@ -0,0 +1,12 @@
+#![no_main]
+
+use gui_experiment::Pack;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    if let Ok(pack) = Pack::parse(text) {
+        let written = pack.to_string();
+        let reparsed = Pack::parse(&written).expect("written pack can be parsed");
+        assert_eq!(reparsed.to_string(), written);
+    }
+});
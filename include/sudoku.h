@@ -0,0 +1,153 @@
+#ifndef SUDOKU_H
+#define SUDOKU_H
+
+/* Generated by cbindgen from src/capi.rs, don't edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/* Opaque board, see sudoku_new, sudoku_load and sudoku_generate */
+typedef struct SudokuModel SudokuModel;
+
+/**
+ * Buffer size [`sudoku_save`] needs: 81 cells and the terminating `NUL`
+ */
+#define SUDOKU_LINE_SIZE 82
+
+/**
+ * Same as the colour of a cell in the games
+ */
+typedef enum SudokuStatus {
+  SUDOKU_STATUS_NORMAL,
+  /**
+   * Value clashes with a peer or breaks an extra rule
+   */
+  SUDOKU_STATUS_CONFLICT,
+  /**
+   * Cell completes its row, column or region
+   */
+  SUDOKU_STATUS_COMPLETE,
+} SudokuStatus;
+
+/**
+ * Empty board without givens
+ */
+SudokuModel *sudoku_new(void);
+
+/**
+ * Random puzzle with a single solution, the same seed always gives the same puzzle
+ */
+SudokuModel *sudoku_generate(uint64_t seed);
+
+/**
+ * Board from 81 cells row by row, `.` or `0` for empty cells, every digit becomes a given.
+ * Returns `NULL` if the line is not a puzzle.
+ *
+ * # Safety
+ *
+ * `line` must be `NULL` or a `NUL` terminated string.
+ */
+SudokuModel *sudoku_load(const char *line);
+
+/**
+ * Writes all values row by row, `.` for empty cells, in the format [`sudoku_load`] reads. Givens
+ * and entries are written alike, so [`sudoku_load`] reads them all back as givens, use
+ * [`sudoku_save_givens`] and [`sudoku_save_entries`] to keep them apart.
+ * Returns [`SUDOKU_LINE_SIZE`], nothing is written if `size` is smaller than that.
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board, `buffer` must have room for `size` bytes.
+ */
+size_t sudoku_save(const SudokuModel *board, char *buffer, size_t size);
+
+/**
+ * Like [`sudoku_save`], but only the givens, a puzzle for [`sudoku_load`]
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board, `buffer` must have room for `size` bytes.
+ */
+size_t sudoku_save_givens(const SudokuModel *board, char *buffer, size_t size);
+
+/**
+ * Like [`sudoku_save`], but only the entered values, for [`sudoku_load_entries`]
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board, `buffer` must have room for `size` bytes.
+ */
+size_t sudoku_save_entries(const SudokuModel *board, char *buffer, size_t size);
+
+/**
+ * Enters the values of a line like [`sudoku_save_entries`] writes, givens stay as they are.
+ * Returns `false` and leaves the board as it is if the line is not 81 cells.
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board, `line` must be `NULL` or a `NUL` terminated string.
+ */
+bool sudoku_load_entries(SudokuModel *board, const char *line);
+
+/**
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board, it can't be used afterwards.
+ */
+void sudoku_free(SudokuModel *board);
+
+/**
+ * Value of the cell, `0` for an empty one
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board.
+ */
+uint8_t sudoku_get(const SudokuModel *board, size_t x, size_t y);
+
+/**
+ * Enters a value, `0` clears the cell. Returns `false` for givens and values above 9.
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board.
+ */
+bool sudoku_set(SudokuModel *board, size_t x, size_t y, uint8_t value);
+
+/**
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board.
+ */
+bool sudoku_is_given(const SudokuModel *board, size_t x, size_t y);
+
+/**
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board.
+ */
+enum SudokuStatus sudoku_status(const SudokuModel *board, size_t x, size_t y);
+
+/**
+ * Fills the empty cells with the first solution found, entered values are kept as if they were
+ * given. Returns `false` and leaves the board as it is if there is no solution.
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board.
+ */
+bool sudoku_solve(SudokuModel *board);
+
+/**
+ * Counts solutions, but stops as soon as `limit` is reached. Use `2` to check uniqueness.
+ *
+ * # Safety
+ *
+ * `board` must be `NULL` or a live board.
+ */
+size_t sudoku_count_solutions(const SudokuModel *board, size_t limit);
+
+#endif  /* SUDOKU_H */
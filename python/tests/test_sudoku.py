@@ -0,0 +1,60 @@
+"""Runs against the extension built with `maturin develop --features python`."""
+
+import pytest
+
+from gui_experiment import SudokuModel
+
+GRID_01 = "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.."
+
+
+def test_grid_is_a_list_of_columns():
+    columns = [[0] * 9 for _ in range(9)]
+    columns[1][0] = 5
+    model = SudokuModel(columns)
+    assert model.get(1, 0) == 5
+    assert model.is_given(1, 0)
+    assert model.line().startswith(".5.")
+
+
+def test_parse_and_solve():
+    model = SudokuModel.from_line(GRID_01)
+    assert str(model) == GRID_01
+    assert model.count_solutions() == 1
+    solution = model.solve()
+    assert solution.is_solved()
+    assert solution.get(0, 0) == 4
+    assert not model.is_solved()
+
+
+def test_invalid_input():
+    with pytest.raises(ValueError, match="expected 81 cells"):
+        SudokuModel.from_line("123")
+    with pytest.raises(ValueError):
+        SudokuModel([[10] * 9] * 9)
+    with pytest.raises(IndexError):
+        SudokuModel().get(9, 0)
+
+
+def test_colour_follows_conflicts():
+    model = SudokuModel.from_line(GRID_01)
+    model.set(0, 0, 3)
+    assert model.colour(0, 0) == "red"
+    assert model.colour(2, 0) == "red"
+    assert model.has_conflicts()
+    assert model.solve() is None
+
+    model.set(0, 0, 0)
+    assert model.colour(0, 0) == "black"
+    assert 4 in model.candidates(0, 0)
+
+
+def test_givens_stay():
+    model = SudokuModel.from_line(GRID_01)
+    model.set(2, 0, 7)
+    assert model.get(2, 0) == 3
+
+
+def test_generate():
+    model = SudokuModel.generate(5)
+    assert model.count_solutions() == 1
+    assert repr(model) == f"SudokuModel.from_line({str(model)!r})"
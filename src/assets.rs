@@ -0,0 +1,180 @@
+//! Files the game needs at runtime, compiled into the binary, so that it runs from any directory.
+//! Any of them can be replaced with a file of the same name in the directory of overrides,
+//! `gui-experiment/assets` in the config directory, or the one of [`set_dir`]:
+//!
+//! - [`ICON`], the PNG icon of windows
+//! - [`PACK`], the pack that is played when no puzzle is given
+//! - [`FONT`], the TrueType or OpenType font of the board, Fira Mono by default, see [`font`]
+//!
+//! Overrides that can't be read or used are warned about, and the compiled in files are used.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::pack::{DEFAULT_PACK, Pack};
+
+pub const ICON: &str = "favicon.png";
+pub const PACK: &str = "default.txt";
+pub const FONT: &str = "font.ttf";
+
+static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+static FONT_FILE: OnceLock<(Cow<'static, [u8]>, String)> = OnceLock::new();
+
+/// Uses another directory of overrides, only before any asset is read
+pub fn set_dir(dir: PathBuf) {
+    if DIR.set(Some(dir)).is_err() {
+        tracing::warn!("assets were read already, the directory of overrides stays");
+    }
+}
+
+/// Directory of overrides, if there's a config directory for the default one
+pub fn dir() -> Option<&'static Path> {
+    DIR.get_or_init(default_dir).as_deref()
+}
+
+/// Directory of overrides without [`set_dir`], if there's a config directory
+pub fn default_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("gui-experiment").join("assets"))
+}
+
+/// Contents of the asset, from the directory of overrides if it's there
+pub fn get(name: &str) -> Option<Cow<'static, [u8]>> {
+    read(dir(), name)
+}
+
+/// Override of the asset, for frontends that only load files by path
+pub fn path(name: &str) -> Option<PathBuf> {
+    Some(dir()?.join(name)).filter(|path| path.is_file())
+}
+
+/// Icon as a PNG
+pub fn icon() -> Cow<'static, [u8]> {
+    get(ICON).expect("icon is compiled in")
+}
+
+/// Pack to play when no puzzle is given, it has no file to save progress to
+pub fn pack() -> Pack {
+    let text = get(PACK).expect("pack is compiled in");
+    Pack::parse(&String::from_utf8_lossy(&text)).unwrap_or_else(|err| {
+        tracing::warn!("{PACK} of the assets isn't a pack: {err}");
+        Pack::default_pack()
+    })
+}
+
+/// Font of the board as TrueType or OpenType
+pub fn font() -> &'static [u8] {
+    &font_file().0
+}
+
+/// Family of [`font`], that frontends select it by once it's loaded
+pub fn font_family() -> &'static str {
+    &font_file().1
+}
+
+fn font_file() -> &'static (Cow<'static, [u8]>, String) {
+    FONT_FILE.get_or_init(|| {
+        let font = get(FONT).expect("font is compiled in");
+        match family(&font) {
+            Some(family) => (font, family),
+            None => {
+                tracing::warn!("{FONT} of the assets isn't a TrueType or OpenType font");
+                let font = embedded(FONT).expect("font is compiled in");
+                let family = family(font).expect("compiled in font has a family");
+                (Cow::Borrowed(font), family)
+            }
+        }
+    })
+}
+
+/// Family name from the `name` table of the font, the typographic one if there is one, as that's
+/// the one font databases group weights by
+fn family(font: &[u8]) -> Option<String> {
+    let u16_at = |offset: usize| {
+        Some(u16::from_be_bytes(
+            font.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    let u32_at = |offset: usize| {
+        Some(u32::from_be_bytes(
+            font.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let table = (0..u16_at(4)? as usize)
+        .map(|index| 12 + 16 * index)
+        .find(|&record| font.get(record..record + 4) == Some(b"name"))?;
+    let table = u32_at(table + 8)? as usize;
+    let strings = table + u16_at(table + 4)? as usize;
+    let mut names = Vec::new();
+    for record in (0..u16_at(table + 2)? as usize).map(|index| table + 6 + 12 * index) {
+        let (platform, id) = (u16_at(record)?, u16_at(record + 6)?);
+        let start = strings + u16_at(record + 10)? as usize;
+        let bytes = font.get(start..start + u16_at(record + 8)? as usize)?;
+        let name = match platform {
+            // Unicode and Windows names are UTF-16, big endian
+            0 | 3 => String::from_utf16(
+                &bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>(),
+            )
+            .ok()?,
+            _ => bytes.iter().map(|&byte| char::from(byte)).collect(),
+        };
+        names.push((id, name));
+    }
+    [16, 1]
+        .into_iter()
+        .find_map(|id| names.iter().find(|(own, _)| *own == id))
+        .map(|(_, name)| name.clone())
+}
+
+fn read(dir: Option<&Path>, name: &str) -> Option<Cow<'static, [u8]>> {
+    if let Some(dir) = dir {
+        let path = dir.join(name);
+        match std::fs::read(&path) {
+            Ok(bytes) => return Some(Cow::Owned(bytes)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => tracing::warn!("Failed to read {}: {err}", path.display()),
+        }
+    }
+    embedded(name).map(Cow::Borrowed)
+}
+
+fn embedded(name: &str) -> Option<&'static [u8]> {
+    match name {
+        ICON => Some(include_bytes!("../www/favicon.png")),
+        PACK => Some(DEFAULT_PACK.as_bytes()),
+        FONT => Some(include_bytes!("../www/FiraMono-Medium.ttf")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_replace_compiled_in_assets() {
+        let dir =
+            std::env::temp_dir().join(format!("gui-experiment-assets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(PACK), "# Title\n").unwrap();
+
+        assert_eq!(read(Some(&dir), PACK).as_deref(), Some(&b"# Title\n"[..]));
+        assert_eq!(read(Some(&dir), ICON), read(None, ICON));
+        assert_eq!(read(None, PACK).as_deref(), Some(DEFAULT_PACK.as_bytes()));
+        assert_eq!(read(Some(&dir), FONT), read(None, FONT));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn font_family_is_read_from_the_font() {
+        assert_eq!(
+            family(embedded(FONT).unwrap()).as_deref(),
+            Some("Fira Mono")
+        );
+        assert_eq!(family(DEFAULT_PACK.as_bytes()), None);
+        assert_eq!(family(&[]), None);
+    }
+}
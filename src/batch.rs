@@ -0,0 +1,258 @@
+//! Solving and rating large puzzle files, one 81-cell line per puzzle, on all cores.
+//!
+//! The file is streamed in chunks of [`CHUNK`] lines, every chunk is split between worker threads,
+//! and results are reported in the order of the file. Blank lines and lines starting with `#` are
+//! skipped, so packs work too, although only their givens are used.
+
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, Write};
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use crate::pack::LineError;
+use crate::{Difficulty, Dlx, Grade, Solver, SudokuModel};
+
+/// Lines read at once, memory use only depends on it and not on the size of the file
+pub const CHUNK: usize = 4096;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Verdict {
+    /// Line is not a puzzle
+    Invalid(LineError),
+    /// Givens conflict, or the grid can't be completed
+    NoSolution,
+    /// Grid can be completed in more than one way, the first solution found is kept
+    Multiple(SudokuModel),
+    Unique(SudokuModel, Grade),
+}
+
+impl Verdict {
+    pub fn of(line: &str, solver: &mut impl Solver) -> Verdict {
+        match SudokuModel::from_line(line) {
+            Ok(model) => Verdict::of_model(&model, solver),
+            Err(error) => Verdict::Invalid(error),
+        }
+    }
+
+    /// Same for a puzzle that was parsed already, e.g. one with regions or constraints
+    pub fn of_model(model: &SudokuModel, solver: &mut impl Solver) -> Verdict {
+        let mut first = None;
+        let mut count = 0;
+        solver.search(model, &mut |solved| {
+            first.get_or_insert(*solved);
+            count += 1;
+            count < 2
+        });
+        match (first, count) {
+            (Some(solution), 1) => match model.grade() {
+                Some(grade) => Verdict::Unique(solution, grade),
+                None => Verdict::NoSolution,
+            },
+            (Some(solution), _) => Verdict::Multiple(solution),
+            (None, _) => Verdict::NoSolution,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Invalid(_) => "invalid",
+            Verdict::NoSolution => "unsolvable",
+            Verdict::Multiple(_) => "multiple",
+            Verdict::Unique(_, _) => "unique",
+        }
+    }
+}
+
+/// Result for a single line of the file
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// Starting from 1
+    pub line: usize,
+    pub puzzle: String,
+    pub verdict: Verdict,
+}
+
+/// Tab separated: line, puzzle, verdict, then the solution, difficulty and rating when there are
+/// any, or the reason a line is invalid
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\t{}\t{}", self.line, self.puzzle, self.verdict.name())?;
+        match &self.verdict {
+            Verdict::Invalid(error) => write!(f, "\t{error}"),
+            Verdict::NoSolution => Ok(()),
+            Verdict::Multiple(solution) => write!(f, "\t{}", solution.line()),
+            Verdict::Unique(solution, grade) => write!(
+                f,
+                "\t{}\t{}\t{:.2}",
+                solution.line(),
+                grade.difficulty().name(),
+                grade.rating()
+            ),
+        }
+    }
+}
+
+/// Totals over the whole file
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub puzzles: usize,
+    pub invalid: usize,
+    pub unsolvable: usize,
+    pub multiple: usize,
+    pub unique: usize,
+    /// Unique puzzles by difficulty, in the order of [`Difficulty::ALL`]
+    pub difficulties: [usize; 4],
+    pub elapsed: Duration,
+}
+
+impl Summary {
+    fn add(&mut self, record: &Record) {
+        self.puzzles += 1;
+        match record.verdict {
+            Verdict::Invalid(_) => self.invalid += 1,
+            Verdict::NoSolution => self.unsolvable += 1,
+            Verdict::Multiple(_) => self.multiple += 1,
+            Verdict::Unique(_, grade) => {
+                self.unique += 1;
+                let difficulty = grade.difficulty();
+                if let Some(index) = Difficulty::ALL.iter().position(|d| *d == difficulty) {
+                    self.difficulties[index] += 1;
+                }
+            }
+        }
+    }
+
+    /// Wall time per puzzle, so it gets lower with more cores
+    pub fn per_puzzle(&self) -> Duration {
+        self.elapsed.div_f64(self.puzzles.max(1) as f64)
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "puzzles:    {}", self.puzzles)?;
+        writeln!(f, "unique:     {}", self.unique)?;
+        for (difficulty, count) in Difficulty::ALL.iter().zip(self.difficulties) {
+            writeln!(f, "  {:<9} {count}", format!("{}:", difficulty.name()))?;
+        }
+        writeln!(f, "multiple:   {}", self.multiple)?;
+        writeln!(f, "unsolvable: {}", self.unsolvable)?;
+        writeln!(f, "invalid:    {}", self.invalid)?;
+        writeln!(f, "time:       {:.3?}", self.elapsed)?;
+        write!(f, "per puzzle: {:.3?}", self.per_puzzle())
+    }
+}
+
+/// Solves every puzzle of `input` on `threads` threads, or on all cores when it is `None`.
+/// `report` gets the results in the order of lines, and can stop the batch with an error.
+pub fn solve_lines(
+    input: impl BufRead,
+    threads: Option<NonZeroUsize>,
+    mut report: impl FnMut(&Record) -> io::Result<()>,
+) -> io::Result<Summary> {
+    let start = Instant::now();
+    let threads = threads
+        .or_else(|| std::thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
+    let mut solvers = vec![Dlx::new(); threads];
+    let mut summary = Summary::default();
+    let mut lines = input.lines().enumerate().filter(|(_, line)| {
+        line.as_ref().map_or(true, |line| {
+            !line.trim().is_empty() && !line.trim().starts_with('#')
+        })
+    });
+    let mut chunk = Vec::with_capacity(CHUNK);
+
+    loop {
+        chunk.clear();
+        for (number, line) in lines.by_ref().take(CHUNK) {
+            chunk.push((number + 1, line?.trim().to_string()));
+        }
+        if chunk.is_empty() {
+            break;
+        }
+
+        let per_thread = chunk.len().div_ceil(threads);
+        let results: Vec<Vec<Record>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = chunk
+                .chunks(per_thread)
+                .zip(solvers.iter_mut())
+                .map(|(lines, solver)| {
+                    scope.spawn(move || {
+                        lines
+                            .iter()
+                            .map(|(line, puzzle)| Record {
+                                line: *line,
+                                verdict: Verdict::of(puzzle, solver),
+                                puzzle: puzzle.clone(),
+                            })
+                            .collect()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("solver thread panicked"))
+                .collect()
+        });
+
+        for record in results.iter().flatten() {
+            summary.add(record);
+            report(record)?;
+        }
+    }
+
+    summary.elapsed = start.elapsed();
+    Ok(summary)
+}
+
+/// Writes a line for every puzzle, see [`Record`] for the format
+pub fn solve_to(
+    input: impl BufRead,
+    mut output: impl Write,
+    threads: Option<NonZeroUsize>,
+) -> io::Result<Summary> {
+    let summary = solve_lines(input, threads, |record| writeln!(output, "{record}"))?;
+    output.flush()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_the_order_of_lines() {
+        let corpus = include_str!("../puzzles/seventeen.txt");
+        let input = format!("{corpus}\nnot a puzzle\n{}\n", ".".repeat(81));
+        let mut output = Vec::new();
+        let summary = solve_to(input.as_bytes(), &mut output, NonZeroUsize::new(3)).unwrap();
+
+        assert_eq!(summary.unique, 20);
+        assert_eq!(summary.multiple, 1);
+        assert_eq!(summary.invalid, 1);
+        assert_eq!(summary.puzzles, 22);
+        assert_eq!(summary.difficulties.iter().sum::<usize>(), 20);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 22);
+        let numbers: Vec<usize> = lines
+            .iter()
+            .map(|line| line.split('\t').next().unwrap().parse().unwrap())
+            .collect();
+        assert!(numbers.is_sorted());
+        assert!(lines[20].contains("\tinvalid\t"));
+        assert!(lines[21].contains("\tmultiple\t"));
+    }
+
+    #[test]
+    fn time_per_puzzle_of_huge_batches() {
+        let summary = Summary {
+            puzzles: 1 << 32,
+            elapsed: Duration::from_secs(1 << 32),
+            ..Summary::default()
+        };
+        assert_eq!(summary.per_puzzle(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,530 @@
+//! Printable booklets: several puzzles per page, with their titles, difficulty and page numbers,
+//! and optionally their solutions at the back.
+//!
+//! Pages are laid out once, as [`render`](crate::render) shapes placed on the page plus some text,
+//! and then written either as one SVG file per page or as a single PDF. The PDF is written by hand
+//! and only uses the built-in Helvetica font, so nothing has to be embedded.
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::render::{self, RenderOptions, Shape};
+use crate::{Dlx, Palette, Puzzle, Rgb, Solver, Theme};
+
+/// A4 in points
+const A4: (f32, f32) = (595.0, 842.0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookletOptions {
+    /// Puzzles on a page, arranged in a grid of about the same number of rows and columns
+    pub per_page: usize,
+    /// Pages with the solutions after all the puzzles
+    pub solutions: bool,
+    /// Page size in points, 1/72 of an inch
+    pub width: f32,
+    pub height: f32,
+    /// Empty space around the page, page numbers go into the bottom one
+    pub margin: f32,
+    /// Colours of boards and text, see [`RenderOptions::palette`]
+    pub palette: &'static Palette,
+}
+
+impl Default for BookletOptions {
+    fn default() -> Self {
+        Self {
+            per_page: 4,
+            solutions: true,
+            width: A4.0,
+            height: A4.1,
+            margin: 40.0,
+            palette: Theme::Light.palette(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Middle,
+    End,
+}
+
+/// Something to print, coordinates are in points from the top left corner of the page
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Shape(Shape),
+    /// Single line of Helvetica, `y` is the baseline
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        text: String,
+        align: Align,
+        fill: Rgb,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// Starting from 1
+    pub number: usize,
+    pub elements: Vec<Element>,
+}
+
+/// Puzzle pages first, then solution pages if they are enabled. Puzzles are numbered, and their
+/// solutions have the same numbers. Puzzles without a known difficulty are graded, and ones without
+/// a solution get an empty grid with a note at the back.
+pub fn layout(puzzles: &[Puzzle], options: &BookletOptions) -> Vec<Page> {
+    let per_page = options.per_page.max(1);
+    let mut boards: Vec<_> = puzzles
+        .iter()
+        .enumerate()
+        .map(|(index, puzzle)| {
+            let difficulty = puzzle
+                .difficulty
+                .or_else(|| puzzle.model.grade().map(|grade| grade.difficulty()))
+                .map_or("", |difficulty| difficulty.name());
+            Board {
+                caption: format!("{}. {}", index + 1, puzzle.title),
+                note: difficulty.to_string(),
+                shapes: board(&puzzle.model, options.palette),
+            }
+        })
+        .collect();
+    let puzzle_count = boards.len();
+    if options.solutions {
+        let mut solver = Dlx::new();
+        for (index, puzzle) in puzzles.iter().enumerate() {
+            let (note, solution) = match solver.solve(&puzzle.model) {
+                Some(solution) => ("solution", solution),
+                None => ("no solution", empty(&puzzle.model)),
+            };
+            boards.push(Board {
+                caption: format!("{}. {}", index + 1, puzzle.title),
+                note: note.to_string(),
+                shapes: board(&solution, options.palette),
+            });
+        }
+    }
+
+    let (puzzles, solutions) = boards.split_at(puzzle_count);
+    puzzles
+        .chunks(per_page)
+        .chain(solutions.chunks(per_page))
+        .enumerate()
+        .map(|(index, boards)| page(index + 1, boards, options))
+        .collect()
+}
+
+struct Board {
+    caption: String,
+    note: String,
+    shapes: Vec<Shape>,
+}
+
+/// Grid of the puzzle without any values, with its regions and rules
+fn empty(model: &crate::SudokuModel) -> crate::SudokuModel {
+    model.constraints().fold(
+        crate::SudokuModel::new().with_regions(*model.regions()),
+        |empty, constraint| empty.with_constraint(constraint),
+    )
+}
+
+fn board(model: &crate::SudokuModel, palette: &'static Palette) -> Vec<Shape> {
+    let options = RenderOptions {
+        status: false,
+        palette,
+        ..RenderOptions::default()
+    };
+    render::shapes(model, &options)
+}
+
+fn page(number: usize, boards: &[Board], options: &BookletOptions) -> Page {
+    let per_page = options.per_page.max(1);
+    let columns = (per_page as f32).sqrt().floor().max(1.0) as usize;
+    let rows = per_page.div_ceil(columns);
+    let slot_width = (options.width - 2.0 * options.margin) / columns as f32;
+    let slot_height = (options.height - 2.0 * options.margin) / rows as f32;
+    let gap = options.margin / 2.0;
+    let font = (slot_width.min(slot_height) / 24.0).clamp(8.0, 14.0);
+    let caption = font * 1.6;
+    let side = (slot_width - gap).min(slot_height - gap - caption);
+    let scale = side / RenderOptions::default().size();
+
+    let mut elements = Vec::new();
+    for (index, board) in boards.iter().enumerate() {
+        let left =
+            options.margin + (index % columns) as f32 * slot_width + (slot_width - side) / 2.0;
+        let top = options.margin + (index / columns) as f32 * slot_height;
+        elements.push(Element::Text {
+            x: left,
+            y: top + font,
+            size: font,
+            text: board.caption.clone(),
+            align: Align::Start,
+            fill: options.palette.given,
+        });
+        elements.push(Element::Text {
+            x: left + side,
+            y: top + font,
+            size: font * 0.85,
+            text: board.note.clone(),
+            align: Align::End,
+            fill: options.palette.border,
+        });
+        elements.extend(
+            board
+                .shapes
+                .iter()
+                .map(|shape| Element::Shape(shape.transform(left, top + caption, scale))),
+        );
+    }
+    elements.push(Element::Text {
+        x: options.width / 2.0,
+        y: options.height - options.margin / 2.0,
+        size: 10.0,
+        text: number.to_string(),
+        align: Align::Middle,
+        fill: options.palette.border,
+    });
+    Page { number, elements }
+}
+
+/// One SVG document per page
+pub fn svg(pages: &[Page], options: &BookletOptions) -> Vec<String> {
+    pages
+        .iter()
+        .map(|page| {
+            let (width, height) = (options.width, options.height);
+            let mut svg = format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}pt\" height=\"{height:.0}pt\" viewBox=\"0 0 {width:.2} {height:.2}\">\n"
+            );
+            for element in &page.elements {
+                match element {
+                    Element::Shape(shape) => {
+                        svg.push_str(&render::svg_elements(std::slice::from_ref(shape)))
+                    }
+                    Element::Text {
+                        x,
+                        y,
+                        size,
+                        text,
+                        align,
+                        fill: Rgb(r, g, b),
+                    } => {
+                        let anchor = match align {
+                            Align::Start => "start",
+                            Align::Middle => "middle",
+                            Align::End => "end",
+                        };
+                        let _ = writeln!(
+                            svg,
+                            r#"<text x="{x:.2}" y="{y:.2}" font-family="Helvetica, Arial, sans-serif" font-size="{size:.2}" text-anchor="{anchor}" fill="rgb({r},{g},{b})">{}</text>"#,
+                            escape(text)
+                        );
+                    }
+                }
+            }
+            svg.push_str("</svg>\n");
+            svg
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Whole booklet as a PDF document
+pub fn pdf(pages: &[Page], options: &BookletOptions) -> Vec<u8> {
+    // Catalog, page tree and font come first, then every page and its content
+    let mut objects = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|index| format!("{} 0 R", 4 + 2 * index))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+    ];
+    for (index, page) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                options.width,
+                options.height,
+                5 + 2 * index
+            )
+            .into_bytes(),
+        );
+        let content = content(page, options.height);
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").into_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .into_bytes(),
+    );
+    pdf
+}
+
+/// Drawing operators of a page, PDF coordinates go up from the bottom left corner
+fn content(page: &Page, height: f32) -> Vec<u8> {
+    let colour = |rgb: Rgb| {
+        let [r, g, b] = rgb.to_f32();
+        format!("{r:.3} {g:.3} {b:.3}")
+    };
+    let mut content = Vec::new();
+    for element in &page.elements {
+        match element {
+            Element::Shape(Shape::Rect {
+                x,
+                y,
+                width,
+                height: h,
+                fill,
+            }) => content.extend(
+                format!(
+                    "{} rg {x:.2} {:.2} {width:.2} {h:.2} re f\n",
+                    colour(*fill),
+                    height - y - h
+                )
+                .into_bytes(),
+            ),
+            Element::Shape(Shape::Polyline {
+                points,
+                width,
+                stroke,
+            }) => {
+                let mut path = format!("{} RG {width:.2} w 1 J 1 j", colour(*stroke));
+                for (index, (x, y)) in points.iter().enumerate() {
+                    let operator = if index == 0 { "m" } else { "l" };
+                    let _ = write!(path, " {x:.2} {:.2} {operator}", height - y);
+                }
+                path.push_str(" S\n");
+                content.extend(path.into_bytes());
+            }
+            Element::Text {
+                x,
+                y,
+                size,
+                text,
+                align,
+                fill,
+            } => {
+                let width = text_width(text, *size);
+                let x = match align {
+                    Align::Start => *x,
+                    Align::Middle => x - width / 2.0,
+                    Align::End => x - width,
+                };
+                content.extend(
+                    format!(
+                        "BT {} rg /F1 {size:.2} Tf {x:.2} {:.2} Td (",
+                        colour(*fill),
+                        height - y
+                    )
+                    .into_bytes(),
+                );
+                for byte in win_ansi(text) {
+                    if matches!(byte, b'(' | b')' | b'\\') {
+                        content.push(b'\\');
+                    }
+                    content.push(byte);
+                }
+                content.extend(b") Tj ET\n");
+            }
+        }
+    }
+    content
+}
+
+/// Text in the encoding of the built-in fonts, which is Latin-1 for everything but a few symbols.
+/// Characters it doesn't have become `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|char| match char {
+            ' '..='~' | '\u{A0}'..='\u{FF}' => char as u8,
+            '–' => 0x96,
+            '—' => 0x97,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '…' => 0x85,
+            '€' => 0x80,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Helvetica advance widths of printable ASCII, in 1/1000 of the font size
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278,
+    278, // space to /
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // digits
+    278, 278, 584, 584, 584, 556, 1015, // : to @
+    667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667,
+    611, 722, 667, 944, 667, 667, 611, // capitals
+    278, 278, 278, 469, 556, 333, // [ to `
+    556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500,
+    278, 556, 500, 722, 500, 500, 500, // small letters
+    334, 260, 334, 584, // { to ~
+];
+
+/// Width in points, other characters are counted as wide as a digit
+fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = win_ansi(text)
+        .into_iter()
+        .map(|byte| match byte {
+            b' '..=b'~' => HELVETICA[(byte - b' ') as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Writes a PDF, or SVG pages next to each other when the path ends with `.svg`:
+/// `booklet.svg` becomes `booklet-1.svg`, `booklet-2.svg` and so on. Returns the written files.
+pub fn save(
+    puzzles: &[Puzzle],
+    options: &BookletOptions,
+    path: impl AsRef<Path>,
+) -> io::Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let pages = layout(puzzles, options);
+    if path.extension().is_some_and(|extension| extension == "svg") {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        svg(&pages, options)
+            .into_iter()
+            .zip(&pages)
+            .map(|(svg, page)| {
+                let file = path.with_file_name(format!("{stem}-{}.svg", page.number));
+                std::fs::write(&file, svg)?;
+                Ok(file)
+            })
+            .collect()
+    } else {
+        std::fs::write(path, pdf(&pages, options))?;
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pack;
+
+    fn puzzles() -> Vec<Puzzle> {
+        Pack::default_pack().puzzles()[..5].to_vec()
+    }
+
+    #[test]
+    fn solutions_follow_the_puzzles() {
+        let options = BookletOptions::default();
+        let pages = layout(&puzzles(), &options);
+        let numbers: Vec<_> = pages.iter().map(|page| page.number).collect();
+        assert_eq!(numbers, [1, 2, 3, 4]);
+
+        let captions = |page: &Page| -> Vec<String> {
+            page.elements
+                .iter()
+                .filter_map(|element| match element {
+                    Element::Text { text, .. } => Some(text.clone()),
+                    Element::Shape(_) => None,
+                })
+                .collect()
+        };
+        assert_eq!(captions(&pages[1]).len(), 3);
+        assert!(captions(&pages[2]).contains(&"solution".to_string()));
+        assert_eq!(captions(&pages[3]).last().unwrap(), "4");
+
+        let without = BookletOptions {
+            solutions: false,
+            per_page: 1,
+            ..options
+        };
+        assert_eq!(layout(&puzzles(), &without).len(), 5);
+    }
+
+    #[test]
+    fn unsolvable_puzzles_get_an_empty_grid() {
+        let mut puzzles = puzzles();
+        puzzles[0].model = crate::SudokuModel::from_line(&format!("11{}", ".".repeat(79))).unwrap();
+        let options = BookletOptions {
+            per_page: 1,
+            ..BookletOptions::default()
+        };
+        let pages = layout(&puzzles, &options);
+        let solution = &pages[5].elements;
+        assert!(solution.iter().any(|element| matches!(
+            element,
+            Element::Text { text, .. } if text == "no solution"
+        )));
+        let shapes = solution
+            .iter()
+            .filter(|element| matches!(element, Element::Shape(_)))
+            .count();
+        assert_eq!(
+            shapes,
+            board(&crate::SudokuModel::new(), options.palette).len()
+        );
+    }
+
+    #[test]
+    fn pdf_objects_are_where_the_table_says() {
+        let options = BookletOptions::default();
+        let pages = layout(&puzzles(), &options);
+        let pdf = pdf(&pages, &options);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // The binary comment after the header isn't UTF-8, everything after it is
+        let trailer = String::from_utf8_lossy(&pdf);
+        assert!(trailer.contains("/Count 4"));
+        let xref: usize = trailer
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+        assert!(table.starts_with("xref\n0 12\n"));
+        for (index, entry) in table.lines().skip(3).take(11).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", index + 1).as_bytes()));
+        }
+
+        assert_eq!(svg(&pages, &options).len(), 4);
+    }
+}
@@ -0,0 +1,266 @@
+//! C ABI, to embed the engine into C and C++ programs.
+//!
+//! The header `include/sudoku.h` is generated by cbindgen, see the README. Boards are opaque, they
+//! are created by [`sudoku_new`], [`sudoku_load`] or [`sudoku_generate`] and must be released with
+//! [`sudoku_free`]. Coordinates are the column `x`, then the row `y`, both in `0..9`. Functions
+//! accept `NULL` boards and out of range coordinates, and do nothing for them.
+
+use core::ffi::{CStr, c_char};
+
+use crate::{Colour, Dlx, Solver, SudokuModel};
+
+/// Buffer size [`sudoku_save`] needs: 81 cells and the terminating `NUL`
+pub const SUDOKU_LINE_SIZE: usize = 82;
+
+/// Same as the colour of a cell in the games
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SudokuStatus {
+    Normal,
+    /// Value clashes with a peer or breaks an extra rule
+    Conflict,
+    /// Cell completes its row, column or region
+    Complete,
+}
+
+impl From<Colour> for SudokuStatus {
+    fn from(value: Colour) -> Self {
+        match value {
+            Colour::Black => SudokuStatus::Normal,
+            Colour::Red => SudokuStatus::Conflict,
+            Colour::Green => SudokuStatus::Complete,
+        }
+    }
+}
+
+fn boxed(model: SudokuModel) -> *mut SudokuModel {
+    Box::into_raw(Box::new(model))
+}
+
+fn in_grid(x: usize, y: usize) -> bool {
+    x < 9 && y < 9
+}
+
+/// Empty board without givens
+#[unsafe(no_mangle)]
+pub extern "C" fn sudoku_new() -> *mut SudokuModel {
+    boxed(SudokuModel::new())
+}
+
+/// Random puzzle with a single solution, the same seed always gives the same puzzle
+#[unsafe(no_mangle)]
+pub extern "C" fn sudoku_generate(seed: u64) -> *mut SudokuModel {
+    boxed(SudokuModel::generate(seed))
+}
+
+/// Board from 81 cells row by row, `.` or `0` for empty cells, every digit becomes a given.
+/// Returns `NULL` if the line is not a puzzle.
+///
+/// # Safety
+///
+/// `line` must be `NULL` or a `NUL` terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_load(line: *const c_char) -> *mut SudokuModel {
+    if line.is_null() {
+        return core::ptr::null_mut();
+    }
+    let line = unsafe { CStr::from_ptr(line) };
+    match line.to_str().map(SudokuModel::from_line) {
+        Ok(Ok(model)) => boxed(model),
+        _ => core::ptr::null_mut(),
+    }
+}
+
+/// Writes all values row by row, `.` for empty cells, in the format [`sudoku_load`] reads. Givens
+/// and entries are written alike, so [`sudoku_load`] reads them all back as givens, use
+/// [`sudoku_save_givens`] and [`sudoku_save_entries`] to keep them apart.
+/// Returns [`SUDOKU_LINE_SIZE`], nothing is written if `size` is smaller than that.
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board, `buffer` must have room for `size` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_save(
+    board: *const SudokuModel,
+    buffer: *mut c_char,
+    size: usize,
+) -> usize {
+    unsafe { save(board, buffer, size, SudokuModel::line) }
+}
+
+/// Like [`sudoku_save`], but only the givens, a puzzle for [`sudoku_load`]
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board, `buffer` must have room for `size` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_save_givens(
+    board: *const SudokuModel,
+    buffer: *mut c_char,
+    size: usize,
+) -> usize {
+    unsafe { save(board, buffer, size, SudokuModel::givens_line) }
+}
+
+/// Like [`sudoku_save`], but only the entered values, for [`sudoku_load_entries`]
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board, `buffer` must have room for `size` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_save_entries(
+    board: *const SudokuModel,
+    buffer: *mut c_char,
+    size: usize,
+) -> usize {
+    unsafe { save(board, buffer, size, SudokuModel::entries_line) }
+}
+
+/// Enters the values of a line like [`sudoku_save_entries`] writes, givens stay as they are.
+/// Returns `false` and leaves the board as it is if the line is not 81 cells.
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board, `line` must be `NULL` or a `NUL` terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_load_entries(board: *mut SudokuModel, line: *const c_char) -> bool {
+    let Some(board) = (unsafe { board.as_mut() }) else {
+        return false;
+    };
+    if line.is_null() {
+        return false;
+    }
+    let line = unsafe { CStr::from_ptr(line) };
+    let Ok(Ok(entries)) = line.to_str().map(SudokuModel::from_line) else {
+        return false;
+    };
+    for x in 0..9 {
+        for y in 0..9 {
+            board.set(x, y, entries.get(x, y).value);
+        }
+    }
+    true
+}
+
+unsafe fn save(
+    board: *const SudokuModel,
+    buffer: *mut c_char,
+    size: usize,
+    line: fn(&SudokuModel) -> String,
+) -> usize {
+    let Some(board) = (unsafe { board.as_ref() }) else {
+        return SUDOKU_LINE_SIZE;
+    };
+    if buffer.is_null() || size < SUDOKU_LINE_SIZE {
+        return SUDOKU_LINE_SIZE;
+    }
+    let buffer = unsafe { core::slice::from_raw_parts_mut(buffer.cast::<u8>(), SUDOKU_LINE_SIZE) };
+    for (cell, char) in line(board).bytes().zip(buffer.iter_mut()) {
+        *char = cell;
+    }
+    buffer[SUDOKU_LINE_SIZE - 1] = 0;
+    SUDOKU_LINE_SIZE
+}
+
+/// # Safety
+///
+/// `board` must be `NULL` or a live board, it can't be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_free(board: *mut SudokuModel) {
+    if !board.is_null() {
+        drop(unsafe { Box::from_raw(board) });
+    }
+}
+
+/// Value of the cell, `0` for an empty one
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_get(board: *const SudokuModel, x: usize, y: usize) -> u8 {
+    match unsafe { board.as_ref() } {
+        Some(board) if in_grid(x, y) => board.get(x, y).value,
+        _ => 0,
+    }
+}
+
+/// Enters a value, `0` clears the cell. Returns `false` for givens and values above 9.
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_set(
+    board: *mut SudokuModel,
+    x: usize,
+    y: usize,
+    value: u8,
+) -> bool {
+    match unsafe { board.as_mut() } {
+        Some(board) if in_grid(x, y) && value <= 9 && board.get(x, y).enabled() => {
+            board.set(x, y, value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// # Safety
+///
+/// `board` must be `NULL` or a live board.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_is_given(board: *const SudokuModel, x: usize, y: usize) -> bool {
+    match unsafe { board.as_ref() } {
+        Some(board) if in_grid(x, y) => !board.get(x, y).enabled(),
+        _ => false,
+    }
+}
+
+/// # Safety
+///
+/// `board` must be `NULL` or a live board.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_status(
+    board: *const SudokuModel,
+    x: usize,
+    y: usize,
+) -> SudokuStatus {
+    match unsafe { board.as_ref() } {
+        Some(board) if in_grid(x, y) => board.colour(x, y).into(),
+        _ => SudokuStatus::Normal,
+    }
+}
+
+/// Fills the empty cells with the first solution found, entered values are kept as if they were
+/// given. Returns `false` and leaves the board as it is if there is no solution.
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_solve(board: *mut SudokuModel) -> bool {
+    let Some(board) = (unsafe { board.as_mut() }) else {
+        return false;
+    };
+    match Dlx::new().solve(board) {
+        Some(solution) => {
+            *board = solution;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Counts solutions, but stops as soon as `limit` is reached. Use `2` to check uniqueness.
+///
+/// # Safety
+///
+/// `board` must be `NULL` or a live board.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sudoku_count_solutions(board: *const SudokuModel, limit: usize) -> usize {
+    match unsafe { board.as_ref() } {
+        Some(board) => Dlx::new().count_solutions(board, limit),
+        None => 0,
+    }
+}
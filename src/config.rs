@@ -0,0 +1,248 @@
+//! Preferences of the player, read from `gui-experiment/config.toml` in the config directory, e.g.
+//! `~/.config` on Linux, when it's there. Every setting is optional, and the command line overrides
+//! them:
+//!
+//! ```toml
+//! # Or "light", "high-contrast" and "colour-blind"
+//! theme = "dark"
+//! # "en", "de" or "ru", the one of `LANG` and the like without it
+//! locale = "de"
+//! # Logical pixels, frontends zoom the rest of the board along with the cells
+//! cell-size = 60
+//! # Of generated puzzles
+//! difficulty = "hard"
+//! assist = "conflicts"
+//!
+//! # Keys of the terminal, arrows, Esc, digits and `+`/`-` always work
+//! [keys]
+//! up = "w"
+//! left = "a"
+//! down = "s"
+//! right = "d"
+//! debug = "D"
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::{Action, Assist, Difficulty, Keys, Locale, Options, Theme};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    pub theme: Option<Theme>,
+    /// Of texts, the one of the environment is used without it
+    pub locale: Option<Locale>,
+    pub cell_size: Option<f32>,
+    pub keys: Keys,
+    /// Of puzzles generated without asking for one
+    pub difficulty: Difficulty,
+    pub assist: Assist,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            locale: None,
+            cell_size: None,
+            keys: Keys::default(),
+            difficulty: Difficulty::Medium,
+            assist: Assist::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Where the config is looked for, if there's a config directory at all
+    pub fn path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join("gui-experiment")
+                .join("config.toml"),
+        )
+    }
+
+    /// Config of the file, or the default one when there's no such file
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (key, value) in text.parse::<Table>()? {
+            match key.as_str() {
+                "theme" => config.theme = Some(named(&key, &value, Theme::by_name)?),
+                "locale" => config.locale = Some(named(&key, &value, Locale::by_name)?),
+                "cell-size" => config.cell_size = Some(size(&key, &value)?),
+                "difficulty" => config.difficulty = named(&key, &value, Difficulty::by_name)?,
+                "assist" => config.assist = named(&key, &value, Assist::by_name)?,
+                "keys" => config.keys = keys(&value)?,
+                _ => return Err(ConfigError::Unknown(key)),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Options to play with, before the command line changes them
+    pub fn options(&self) -> Options {
+        Options {
+            cell_size: self.cell_size,
+            theme: self.theme.unwrap_or_default(),
+            locale: self.locale.unwrap_or_else(Locale::from_env),
+            keys: self.keys,
+            assist: self.assist,
+            ..Options::default()
+        }
+    }
+}
+
+fn named<T>(key: &str, value: &Value, by_name: fn(&str) -> Option<T>) -> Result<T, ConfigError> {
+    value
+        .as_str()
+        .and_then(by_name)
+        .ok_or_else(|| ConfigError::value(key, value))
+}
+
+fn size(key: &str, value: &Value) -> Result<f32, ConfigError> {
+    let size = match value {
+        Value::Integer(size) => *size as f64,
+        Value::Float(size) => *size,
+        _ => return Err(ConfigError::value(key, value)),
+    };
+    if size.is_finite() && size > 0.0 {
+        Ok(size as f32)
+    } else {
+        Err(ConfigError::value(key, value))
+    }
+}
+
+fn keys(value: &Value) -> Result<Keys, ConfigError> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| ConfigError::value("keys", value))?;
+    let mut keys = Keys::default();
+    let mut bound = Vec::new();
+    for (name, value) in table {
+        let key = format!("keys.{name}");
+        let action = Action::by_name(name).ok_or_else(|| ConfigError::Unknown(key.clone()))?;
+        let mut chars = value.as_str().unwrap_or_default().chars();
+        let (Some(char), None) = (chars.next(), chars.next()) else {
+            return Err(ConfigError::value(&key, value));
+        };
+        bound.push((action, char));
+    }
+    // Defaults of other actions are freed first, so that keys can be swapped
+    for (action, _) in &bound {
+        keys.unbind(*action);
+    }
+    for (action, char) in bound {
+        keys.bind(action, char)
+            .map_err(|other| ConfigError::Key { char, other })?;
+    }
+    Ok(keys)
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    /// Setting that isn't known, like a misspelled one
+    Unknown(String),
+    /// Value of the setting has the wrong type, or isn't one of the allowed ones
+    Value {
+        key: String,
+        value: String,
+    },
+    /// Key is bound to another action too, or sets values, without one
+    Key {
+        char: char,
+        other: Option<Action>,
+    },
+}
+
+impl ConfigError {
+    fn value(key: &str, value: &Value) -> Self {
+        ConfigError::Value {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "{error}"),
+            ConfigError::Toml(error) => write!(f, "{error}"),
+            ConfigError::Unknown(key) => write!(f, "unknown setting `{key}`"),
+            ConfigError::Value { key, value } => write!(f, "{value} isn't a valid `{key}`"),
+            ConfigError::Key {
+                char,
+                other: Some(other),
+            } => write!(f, "`{char}` is bound to {} too", other.name()),
+            ConfigError::Key { char, other: None } => {
+                write!(f, "`{char}` sets values, so it can't be bound")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        ConfigError::Toml(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_read() {
+        let config = Config::parse(
+            "theme = \"light\"\nlocale = \"ru_RU.UTF-8\"\ncell-size = 60\nassist = \"off\"\n\
+             [keys]\nup = \"j\"\ndown = \"k\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.theme, Some(Theme::Light));
+        assert_eq!(config.locale, Some(Locale::Russian));
+        assert_eq!(config.cell_size, Some(60.0));
+        assert_eq!(config.difficulty, Difficulty::Medium);
+        assert_eq!(config.assist, Assist::Off);
+        assert_eq!(config.keys.action('j'), Some(Action::Up));
+        assert_eq!(config.keys.action('k'), Some(Action::Down));
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        let error = |text| Config::parse(text).unwrap_err().to_string();
+        assert_eq!(error("colour = 1"), "unknown setting `colour`");
+        assert_eq!(error("cell-size = -5"), "-5 isn't a valid `cell-size`");
+        assert_eq!(error("[keys]\njump = \"x\""), "unknown setting `keys.jump`");
+        assert_eq!(error("[keys]\nhint = \"q\""), "`q` is bound to quit too");
+        assert_eq!(
+            error("[keys]\nhint = \"5\""),
+            "`5` sets values, so it can't be bound"
+        );
+        assert!(matches!(
+            Config::parse("theme = "),
+            Err(ConfigError::Toml(_))
+        ));
+    }
+}
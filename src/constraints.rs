@@ -0,0 +1,263 @@
+//! Extra rules on top of rows, columns and regions.
+//!
+//! Rules are referenced as `&'static dyn Constraint`, so that the model stays `Copy`. Built-in rules
+//! are unit types, and a reference to them is promoted to `'static`, e.g.
+//! `SudokuModel::example().with_constraint(&AntiKnight)`.
+
+use core::fmt::Debug;
+
+use crate::SudokuModel;
+
+pub trait Constraint: Debug + Sync {
+    /// Short name that identifies the rule in puzzle files
+    fn name(&self) -> &'static str;
+
+    /// Whether `value` can be in the cell without breaking the rule, judging by the other cells.
+    /// Current value of the cell itself is ignored.
+    fn allows(&self, model: &SudokuModel, x: usize, y: usize, value: u8) -> bool;
+
+    /// Whether the cell should be highlighted as a part of the rule, like a diagonal or a window
+    fn marks(&self, _x: usize, _y: usize) -> bool {
+        false
+    }
+}
+
+/// All rules that come with the crate, in the order they are listed in puzzle files
+pub const BUILT_IN: [&dyn Constraint; 6] = [
+    &Diagonal::Negative,
+    &Diagonal::Positive,
+    &AntiKnight,
+    &AntiKing,
+    &NonConsecutive,
+    &Windoku,
+];
+
+pub fn by_name(name: &str) -> Option<&'static dyn Constraint> {
+    BUILT_IN
+        .into_iter()
+        .find(|constraint| constraint.name() == name)
+}
+
+/// No digit repeats on a main diagonal, both of them together make Sudoku-X
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagonal {
+    /// From top left to bottom right
+    Negative,
+    /// From bottom left to top right
+    Positive,
+}
+
+impl Constraint for Diagonal {
+    fn name(&self) -> &'static str {
+        match self {
+            Diagonal::Negative => "diagonal-",
+            Diagonal::Positive => "diagonal+",
+        }
+    }
+
+    fn allows(&self, model: &SudokuModel, x: usize, y: usize, value: u8) -> bool {
+        !self.marks(x, y) || unique_among(model, (0..9).map(|i| self.cell(i)), x, y, value)
+    }
+
+    fn marks(&self, x: usize, y: usize) -> bool {
+        self.cell(x) == (x, y)
+    }
+}
+
+impl Diagonal {
+    fn cell(&self, x: usize) -> (usize, usize) {
+        match self {
+            Diagonal::Negative => (x, x),
+            Diagonal::Positive => (x, 8 - x),
+        }
+    }
+}
+
+/// Cells a chess knight's move apart can't contain the same digit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AntiKnight;
+
+impl Constraint for AntiKnight {
+    fn name(&self) -> &'static str {
+        "antiknight"
+    }
+
+    fn allows(&self, model: &SudokuModel, x: usize, y: usize, value: u8) -> bool {
+        const MOVES: [(isize, isize); 8] = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+        unique_among(model, offsets(x, y, &MOVES), x, y, value)
+    }
+}
+
+/// Cells a chess king's move apart, including diagonally, can't contain the same digit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AntiKing;
+
+impl Constraint for AntiKing {
+    fn name(&self) -> &'static str {
+        "antiking"
+    }
+
+    fn allows(&self, model: &SudokuModel, x: usize, y: usize, value: u8) -> bool {
+        unique_among(model, offsets(x, y, &KING_MOVES), x, y, value)
+    }
+}
+
+/// Orthogonally adjacent cells can't contain consecutive digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonConsecutive;
+
+impl Constraint for NonConsecutive {
+    fn name(&self) -> &'static str {
+        "nonconsecutive"
+    }
+
+    fn allows(&self, model: &SudokuModel, x: usize, y: usize, value: u8) -> bool {
+        offsets(x, y, &KING_MOVES[..4]).all(|(other_x, other_y)| {
+            let other = model.get(other_x, other_y).value;
+            other == 0 || other.abs_diff(value) != 1
+        })
+    }
+}
+
+/// Four extra 3x3 windows, offset by one cell from the grid edges, each containing all digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Windoku;
+
+impl Constraint for Windoku {
+    fn name(&self) -> &'static str {
+        "windoku"
+    }
+
+    fn allows(&self, model: &SudokuModel, x: usize, y: usize, value: u8) -> bool {
+        match Self::window(x, y) {
+            None => true,
+            Some((left, top)) => unique_among(
+                model,
+                (left..left + 3).flat_map(|x| (top..top + 3).map(move |y| (x, y))),
+                x,
+                y,
+                value,
+            ),
+        }
+    }
+
+    fn marks(&self, x: usize, y: usize) -> bool {
+        Self::window(x, y).is_some()
+    }
+}
+
+impl Windoku {
+    /// Top left corner of the window that contains the cell
+    fn window(x: usize, y: usize) -> Option<(usize, usize)> {
+        let start = |i: usize| match i {
+            1..=3 => Some(1),
+            5..=7 => Some(5),
+            _ => None,
+        };
+        Some((start(x)?, start(y)?))
+    }
+}
+
+/// Orthogonal moves go first, so that they can be used on their own
+const KING_MOVES: [(isize, isize); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+
+fn offsets(
+    x: usize,
+    y: usize,
+    moves: &[(isize, isize)],
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    moves.iter().filter_map(move |&(dx, dy)| {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        (x < 9 && y < 9).then_some((x, y))
+    })
+}
+
+fn unique_among(
+    model: &SudokuModel,
+    mut cells: impl Iterator<Item = (usize, usize)>,
+    x: usize,
+    y: usize,
+    value: u8,
+) -> bool {
+    !cells.any(|(other_x, other_y)| {
+        (other_x, other_y) != (x, y) && model.get(other_x, other_y).value == value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Colour;
+
+    #[test]
+    fn anti_knight_conflict() {
+        let mut model = SudokuModel::new().with_constraint(&AntiKnight);
+        model.set(0, 0, 4);
+        model.set(1, 2, 4);
+        assert!(matches!(model.colour(0, 0), Colour::Red));
+        assert!(matches!(model.colour(1, 2), Colour::Red));
+        assert!(!model.candidates(2, 1).contains(4));
+    }
+
+    #[test]
+    fn non_consecutive_neighbours() {
+        let mut model = SudokuModel::new().with_constraint(&NonConsecutive);
+        model.set(4, 4, 5);
+        assert!(!model.candidates(4, 5).contains(6));
+        assert!(model.candidates(5, 5).contains(6));
+    }
+
+    #[test]
+    fn sudoku_x_solution() {
+        let model = SudokuModel::new()
+            .with_constraint(&Diagonal::Negative)
+            .with_constraint(&Diagonal::Positive)
+            .with_constraint(&Windoku);
+        let solution = model.solve().unwrap();
+        for diagonal in [Diagonal::Negative, Diagonal::Positive] {
+            let mut seen = crate::Candidates::ALL;
+            for i in 0..9 {
+                let (x, y) = diagonal.cell(i);
+                assert!(seen.contains(solution.get(x, y).value));
+                seen.remove(solution.get(x, y).value);
+            }
+        }
+        for (left, top) in [(1, 1), (5, 1), (1, 5), (5, 5)] {
+            let mut seen = crate::Candidates::ALL;
+            for (x, y) in (left..left + 3).flat_map(|x| (top..top + 3).map(move |y| (x, y))) {
+                assert_eq!(Windoku::window(x, y), Some((left, top)));
+                assert!(seen.contains(solution.get(x, y).value));
+                seen.remove(solution.get(x, y).value);
+            }
+        }
+    }
+
+    #[test]
+    fn names_are_unique() {
+        for constraint in BUILT_IN {
+            assert_eq!(
+                by_name(constraint.name()).unwrap().name(),
+                constraint.name()
+            );
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Reading and writing puzzles in the formats other tools use.
+//!
+//! Every format is read into [`Puzzle`]s and written from them, so any of them can be converted
+//! into any other. Pictures and booklets can only be written.
+//!
+//! SadMan Software's `.sdk` files have a grid of 9 rows of 9 cells, with `.` for empty cells,
+//! after lines like `#A author` that describe it:
+//!
+//! ```text
+//! #A Somebody
+//! #D Title of the puzzle
+//! #L medium
+//! ..3.2.6..
+//! 9..3.5..1
+//! ...
+//! ```
+//!
+//! Several puzzles are written one after another, separated by a blank line.
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use crate::booklet::{self, BookletOptions};
+use crate::fpuzzles::{self, FPuzzlesError};
+use crate::pack::{LineError, PackError};
+use crate::render::{self, RenderOptions};
+use crate::{Difficulty, Pack, Puzzle, SudokuModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// 81 cells row by row, a puzzle per line
+    Line,
+    /// Lines with metadata comments, see [`crate::pack`]
+    Pack,
+    /// SadMan Software's grid of 9 rows
+    Sdk,
+    /// f-puzzles JSON, a puzzle per line
+    FPuzzles,
+    /// SudokuPad link, a puzzle per line
+    SudokuPad,
+    /// Picture of a single puzzle
+    Svg,
+    /// Booklet to print, with solutions
+    Pdf,
+}
+
+impl Format {
+    pub const ALL: [Format; 7] = [
+        Format::Line,
+        Format::Pack,
+        Format::Sdk,
+        Format::FPuzzles,
+        Format::SudokuPad,
+        Format::Svg,
+        Format::Pdf,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Line => "line",
+            Format::Pack => "pack",
+            Format::Sdk => "sdk",
+            Format::FPuzzles => "fpuzzles",
+            Format::SudokuPad => "sudokupad",
+            Format::Svg => "svg",
+            Format::Pdf => "pdf",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Format> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Format of a file by its extension, then by its contents. Packs are the fallback, plain
+    /// lines are a pack too.
+    pub fn guess(path: Option<&Path>, text: &str) -> Format {
+        let extension = path
+            .and_then(Path::extension)
+            .and_then(|extension| extension.to_str());
+        match extension {
+            Some(extension) if extension.eq_ignore_ascii_case("sdk") => Format::Sdk,
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Format::FPuzzles,
+            _ => {
+                let text = text.trim_start();
+                if text.starts_with('{') {
+                    Format::FPuzzles
+                } else if text.starts_with("http") {
+                    Format::SudokuPad
+                } else if text
+                    .lines()
+                    .find(|line| !line.trim_start().starts_with('#'))
+                    .is_some_and(|line| line.trim().len() == 9)
+                {
+                    Format::Sdk
+                } else {
+                    Format::Pack
+                }
+            }
+        }
+    }
+
+    pub fn is_readable(&self) -> bool {
+        !matches!(self, Format::Svg | Format::Pdf)
+    }
+
+    /// Puzzles of the text, f-puzzles rules this crate doesn't have are left out silently
+    pub fn read(&self, text: &str) -> Result<Vec<Puzzle>, ConvertError> {
+        match self {
+            Format::Line | Format::Pack => Ok(Pack::parse(text)?.puzzles().to_vec()),
+            Format::Sdk => read_sdk(text),
+            Format::FPuzzles | Format::SudokuPad => {
+                // Pretty JSON spans lines, but a file of links or minified JSON has one per line
+                if let Ok(import) = fpuzzles::decode(text) {
+                    return Ok(vec![import.puzzle]);
+                }
+                text.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| Ok(fpuzzles::decode(line)?.puzzle))
+                    .collect()
+            }
+            Format::Svg | Format::Pdf => Err(ConvertError::Unreadable(*self)),
+        }
+    }
+
+    pub fn write(&self, puzzles: &[Puzzle]) -> Result<Vec<u8>, ConvertError> {
+        let lines = |line: fn(&Puzzle) -> String| {
+            puzzles
+                .iter()
+                .map(|puzzle| line(puzzle) + "\n")
+                .collect::<String>()
+        };
+        Ok(match self {
+            Format::Line => lines(|puzzle| puzzle.model.givens_line()),
+            Format::Pack => Pack::from(puzzles.to_vec()).to_string(),
+            Format::Sdk => puzzles.iter().map(sdk).collect::<Vec<_>>().join("\n"),
+            Format::FPuzzles => lines(fpuzzles::to_json),
+            Format::SudokuPad => lines(fpuzzles::sudokupad_link),
+            Format::Svg => match puzzles {
+                [puzzle] => render::svg(&puzzle.model, &RenderOptions::default()),
+                _ => return Err(ConvertError::Single(*self, puzzles.len())),
+            },
+            Format::Pdf => {
+                let options = BookletOptions::default();
+                return Ok(booklet::pdf(&booklet::layout(puzzles, &options), &options));
+            }
+        }
+        .into_bytes())
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConvertError {
+    Pack(PackError),
+    FPuzzles(FPuzzlesError),
+    /// Grid of an `.sdk` file is malformed, lines are counted from 1, from its first row
+    Sdk {
+        line: usize,
+        error: LineError,
+    },
+    /// Format can only be written
+    Unreadable(Format),
+    /// Format holds a single puzzle, but there are this many
+    Single(Format, usize),
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvertError::Pack(error) => write!(f, "{error}"),
+            ConvertError::FPuzzles(error) => write!(f, "{error}"),
+            ConvertError::Sdk { line, error } => write!(f, "line {line}: {error}"),
+            ConvertError::Unreadable(format) => write!(f, "{format} can't be read, only written"),
+            ConvertError::Single(format, count) => {
+                write!(f, "{format} holds a single puzzle, not {count}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<PackError> for ConvertError {
+    fn from(error: PackError) -> Self {
+        ConvertError::Pack(error)
+    }
+}
+
+impl From<FPuzzlesError> for ConvertError {
+    fn from(error: FPuzzlesError) -> Self {
+        ConvertError::FPuzzles(error)
+    }
+}
+
+fn read_sdk(text: &str) -> Result<Vec<Puzzle>, ConvertError> {
+    let mut puzzles = Vec::new();
+    let mut next = Puzzle::new("", SudokuModel::new());
+    let mut rows = String::new();
+    let mut first = 0;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let key = chars.next();
+            let value = chars.as_str().trim().to_string();
+            match key {
+                Some('A') => next.author = Some(value),
+                Some('D') => next.title = value,
+                Some('S') => next.source = Some(value),
+                Some('L') => next.difficulty = Difficulty::by_name(&value),
+                _ => {}
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        if rows.is_empty() {
+            first = number + 1;
+        }
+        rows.push_str(line);
+        if rows.chars().count() >= 81 {
+            next.model = SudokuModel::from_line(&rows)
+                .map_err(|error| ConvertError::Sdk { line: first, error })?;
+            if next.title.is_empty() {
+                next.title = format!("Puzzle {}", puzzles.len() + 1);
+            }
+            puzzles.push(next);
+            next = Puzzle::new("", SudokuModel::new());
+            rows.clear();
+        }
+    }
+    if !rows.is_empty() {
+        let error = LineError::Length(rows.chars().count());
+        return Err(ConvertError::Sdk { line: first, error });
+    }
+    Ok(puzzles)
+}
+
+fn sdk(puzzle: &Puzzle) -> String {
+    let mut text = format!("#D {}\n", puzzle.title);
+    if let Some(author) = &puzzle.author {
+        text += &format!("#A {author}\n");
+    }
+    if let Some(source) = &puzzle.source {
+        text += &format!("#S {source}\n");
+    }
+    if let Some(difficulty) = puzzle.difficulty {
+        text += &format!("#L {}\n", difficulty.name());
+    }
+    let line = puzzle.model.givens_line();
+    for row in line.as_bytes().chunks(9) {
+        text += std::str::from_utf8(row).expect("lines are ASCII");
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_formats_round_trip() {
+        let pack = Pack::default_pack();
+        for format in Format::ALL.into_iter().filter(Format::is_readable) {
+            let text = String::from_utf8(format.write(pack.puzzles()).unwrap()).unwrap();
+            let guessed = match format {
+                Format::Line => Format::Pack,
+                _ => format,
+            };
+            assert_eq!(Format::guess(None, &text), guessed);
+            let puzzles = format.read(&text).unwrap();
+            assert_eq!(puzzles.len(), pack.len(), "{format}");
+            for (read, written) in puzzles.iter().zip(pack.puzzles()) {
+                assert_eq!(read.model.line(), written.model.givens_line(), "{format}");
+            }
+        }
+    }
+
+    #[test]
+    fn sdk_files_are_read() {
+        let text = "#A Somebody\n#D First\n#L hard\n..3.2.6..\n9..3.5..1\n..18.64..\n\
+                    ..81.29..\n7.......8\n..67.82..\n..26.95..\n8..2.3..9\n..5.1.3..\n";
+        assert_eq!(Format::guess(Some(Path::new("a.txt")), text), Format::Sdk);
+        let puzzles = Format::Sdk.read(text).unwrap();
+        assert_eq!(puzzles[0].title, "First");
+        assert_eq!(puzzles[0].author.as_deref(), Some("Somebody"));
+        assert_eq!(puzzles[0].difficulty, Some(Difficulty::Hard));
+        assert!(matches!(
+            Format::Sdk.read(&text[..text.len() - 10]),
+            Err(ConvertError::Sdk { line: 4, .. })
+        ));
+    }
+}
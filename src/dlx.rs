@@ -0,0 +1,248 @@
+//! Knuth's Algorithm X with dancing links.
+//!
+//! Every possible placement of a digit is a row of an exact cover matrix, and every rule that a
+//! digit appears exactly once is a column: one per cell, and one per digit in every row, column
+//! and region. Regions are taken from the model, so jigsaw puzzles are covered too. Extra
+//! constraints don't fit into exact cover, they are checked whenever a placement is chosen.
+
+use crate::{Solver, SudokuModel};
+
+/// Cells, then digits in rows, digits in columns and digits in regions
+const COLUMNS: usize = 4 * 81;
+const ROOT: usize = 0;
+
+/// Matrix is kept between searches, so solving many puzzles doesn't allocate after the first one
+#[derive(Debug, Default, Clone)]
+pub struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of every node, headers point to themselves
+    column: Vec<usize>,
+    /// Cell and digit of every node, unused for headers
+    placement: Vec<(u8, u8, u8)>,
+    /// Nodes left in every column, indexed by header
+    size: Vec<usize>,
+}
+
+impl Dlx {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds the matrix for the model, filled cells only get a row for their own value
+    fn load(&mut self, model: &SudokuModel) {
+        self.left.clear();
+        self.right.clear();
+        self.up.clear();
+        self.down.clear();
+        self.column.clear();
+        self.placement.clear();
+        self.size.clear();
+
+        for node in 0..=COLUMNS {
+            self.left
+                .push(if node == ROOT { COLUMNS } else { node - 1 });
+            self.right
+                .push(if node == COLUMNS { ROOT } else { node + 1 });
+            self.up.push(node);
+            self.down.push(node);
+            self.column.push(node);
+            self.placement.push((0, 0, 0));
+            self.size.push(0);
+        }
+
+        for y in 0..9 {
+            for x in 0..9 {
+                let region = model.regions().region(x, y);
+                let filled = model.get(x, y).value;
+                for value in 1..=9 {
+                    if filled != 0 && filled != value {
+                        continue;
+                    }
+                    let digit = value as usize - 1;
+                    self.add_row(
+                        (x as u8, y as u8, value),
+                        [
+                            1 + y * 9 + x,
+                            1 + 81 + y * 9 + digit,
+                            1 + 2 * 81 + x * 9 + digit,
+                            1 + 3 * 81 + region * 9 + digit,
+                        ],
+                    );
+                }
+            }
+        }
+    }
+
+    fn add_row(&mut self, placement: (u8, u8, u8), columns: [usize; 4]) {
+        let first = self.column.len();
+        for (i, header) in columns.into_iter().enumerate() {
+            let node = first + i;
+            self.left.push(if i == 0 { first + 3 } else { node - 1 });
+            self.right.push(if i == 3 { first } else { node + 1 });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.down[self.up[header]] = node;
+            self.up[header] = node;
+            self.column.push(header);
+            self.placement.push(placement);
+            self.size[header] += 1;
+        }
+    }
+
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut row = self.down[header];
+        while row != header {
+            let mut node = self.right[row];
+            while node != row {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row = self.down[row];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut row = self.up[header];
+        while row != header {
+            let mut node = self.left[row];
+            while node != row {
+                self.size[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row = self.up[row];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    /// Column with the fewest rows left, `None` once everything is covered
+    fn smallest_column(&self) -> Option<usize> {
+        let mut best = None;
+        let mut header = self.right[ROOT];
+        while header != ROOT {
+            if best.is_none_or(|best| self.size[header] < self.size[best]) {
+                best = Some(header);
+                if self.size[header] <= 1 {
+                    break;
+                }
+            }
+            header = self.right[header];
+        }
+        best
+    }
+
+    /// Returns `false` once `found` asks to stop
+    fn search_from(
+        &mut self,
+        work: &mut SudokuModel,
+        found: &mut dyn FnMut(&SudokuModel) -> bool,
+    ) -> bool {
+        let Some(header) = self.smallest_column() else {
+            return found(work);
+        };
+        if self.size[header] == 0 {
+            return true;
+        }
+
+        self.cover(header);
+        let mut row = self.down[header];
+        let mut go_on = true;
+        while go_on && row != header {
+            let (x, y, value) = self.placement[row];
+            let (x, y) = (x as usize, y as usize);
+            let given = work.get(x, y).value != 0;
+            if given
+                || work
+                    .constraints()
+                    .all(|constraint| constraint.allows(work, x, y, value))
+            {
+                work.get_mut(x, y).value = value;
+                let mut node = self.right[row];
+                while node != row {
+                    self.cover(self.column[node]);
+                    node = self.right[node];
+                }
+
+                go_on = self.search_from(work, found);
+
+                let mut node = self.left[row];
+                while node != row {
+                    self.uncover(self.column[node]);
+                    node = self.left[node];
+                }
+                if !given {
+                    work.get_mut(x, y).value = 0;
+                }
+            }
+            row = self.down[row];
+        }
+        self.uncover(header);
+        go_on
+    }
+}
+
+impl Solver for Dlx {
+    fn search(&mut self, model: &SudokuModel, found: &mut dyn FnMut(&SudokuModel) -> bool) {
+        if model.has_conflicts() {
+            return;
+        }
+        self.load(model);
+        let mut work = *model;
+        self.search_from(&mut work, found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Diagonal;
+    use crate::{Backtracking, Pack};
+
+    #[test]
+    fn agrees_with_backtracking() {
+        let pack = Pack::default_pack();
+        let mut dlx = Dlx::new();
+        for index in 0..pack.len() {
+            let model = pack.model(index);
+            let solution = dlx.solve(&model).unwrap();
+            assert!(solution.is_solved());
+            assert_eq!(solution.givens_line(), model.givens_line());
+            assert_eq!(
+                dlx.count_solutions(&model, 2),
+                Backtracking.count_solutions(&model, 2)
+            );
+        }
+    }
+
+    #[test]
+    fn counts_and_constraints() {
+        let mut dlx = Dlx::new();
+        let empty = SudokuModel::new();
+        assert_eq!(dlx.count_solutions(&empty, 10), 10);
+
+        let diagonal = empty
+            .with_constraint(&Diagonal::Negative)
+            .with_constraint(&Diagonal::Positive);
+        let mut puzzle = Backtracking.solve(&diagonal).unwrap();
+        for y in 0..4 {
+            for x in 0..9 {
+                puzzle.set(x, y, 0);
+            }
+        }
+        assert!(dlx.solve(&puzzle).unwrap().is_solved());
+
+        let mut conflicting = SudokuModel::new();
+        conflicting.set(0, 0, 5);
+        conflicting.set(1, 1, 5);
+        assert_eq!(dlx.count_solutions(&conflicting, 2), 0);
+    }
+}
@@ -19,120 +19,211 @@
 //! [adding support for tray icon]: https://github.com/hoothin/RustClock
 //! [also by replacing `winit` with `tao`]: https://github.com/sidit77/headset-controller
 
-use std::ops::Add;
+use std::sync::Arc;
 
-use eframe::egui::{Button, Context, IconData};
+use eframe::egui::{Button, Context, FontData, FontDefinitions, FontFamily, IconData};
 use eframe::{Frame, egui};
 use egui::Color32;
 
-use crate::{Colour, SudokuModel};
-
-pub fn main(sudoku_model: SudokuModel) -> eframe::Result {
-    let favicon = image::ImageReader::open("www/favicon.png")
-        .unwrap()
-        .decode()
-        .unwrap()
-        .to_rgba8();
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([585.0, 585.0])
-            .with_icon(IconData {
+use crate::assets;
+use crate::locale::Message;
+use crate::rpc::{self, Rpc};
+use crate::session;
+use crate::{Action, Keys, Options, Pack, Palette, Rgb, SudokuModel};
+
+/// Size the window and the contents are laid out for, without zoom
+const WINDOW_SIZE: f32 = 585.0;
+/// Pixels per point the contents are laid out for, without zoom
+const PIXELS_PER_POINT: f32 = 3.5;
+/// Size of cells in points, without zoom
+const CELL_SIZE: f32 = 30.0;
+/// Space between 3x3 regions, along with the spacing of the grid on either side
+const REGION_GAP: f32 = 13.0;
+
+/// Plays the puzzle of the pack, and the next ones that aren't solved on [`Action::Next`], progress
+/// is saved when the window is closed
+pub fn main(pack: Pack, puzzle: usize, options: Options, rpc: Option<Rpc>) -> eframe::Result {
+    let (width, height) = options.window_size(WINDOW_SIZE, WINDOW_SIZE, CELL_SIZE);
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size([width, height])
+        .with_resizable(false);
+    match image::load_from_memory(&assets::icon()) {
+        Ok(favicon) => {
+            let favicon = favicon.to_rgba8();
+            viewport = viewport.with_icon(IconData {
                 rgba: favicon.to_vec(),
                 width: favicon.width(),
                 height: favicon.height(),
-            })
-            .with_resizable(false),
+            });
+        }
+        Err(err) => tracing::warn!("{} of the assets isn't an image: {err}", assets::ICON),
+    }
+    let native_options = eframe::NativeOptions {
+        viewport,
         ..Default::default()
     };
     eframe::run_native(
-        "Sudoku",
-        options,
-        Box::new(|_cc| Ok(Box::new(sudoku_model))),
+        options.locale.text(Message::Title),
+        native_options,
+        Box::new(move |cc| {
+            cc.egui_ctx
+                .set_pixels_per_point(PIXELS_PER_POINT * options.zoom(CELL_SIZE));
+            cc.egui_ctx.set_fonts(fonts(assets::font()));
+            cc.egui_ctx.set_visuals(match options.theme.is_dark() {
+                true => egui::Visuals::dark(),
+                false => egui::Visuals::light(),
+            });
+            Ok(Box::new(Game {
+                model: pack.model(puzzle).with_assist(options.assist),
+                pack,
+                puzzle,
+                palette: options.theme.palette(),
+                keys: options.keys,
+                rpc,
+                cursor: (0, 0),
+            }))
+        }),
     )
 }
 
-impl eframe::App for SudokuModel {
+/// Board, and requests that drive it, if any. The board has no cursor, so one is kept for them.
+struct Game {
+    model: SudokuModel,
+    pack: Pack,
+    /// Index of the puzzle of the board in the pack
+    puzzle: usize,
+    palette: &'static Palette,
+    keys: Keys,
+    rpc: Option<Rpc>,
+    cursor: (usize, usize),
+}
+
+impl eframe::App for Game {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        ctx.set_pixels_per_point(3.5);
+        if let Some(rpc) = &mut self.rpc {
+            rpc.poll(&mut self.model, &mut self.cursor);
+            // Requests come without any input, so the frame is redrawn anyway
+            ctx.request_repaint_after(rpc::TICK);
+        }
+        let palette = self.palette;
+        let model = &mut self.model;
+        if ctx.input(|input| input.modifiers.command && input.key_pressed(egui::Key::Z)) {
+            let _input = tracing::debug_span!("input", source = "key", key = "undo").entered();
+            model.undo();
+        }
+        let next = self.keys.key(Action::Next);
+        if ctx.input(|input| {
+            input.events.iter().any(|event| match event {
+                egui::Event::Text(text) => text.starts_with(next),
+                _ => false,
+            })
+        }) {
+            let _input = tracing::debug_span!("input", source = "key", key = "next").entered();
+            self.pack.next(&mut self.puzzle, model);
+        }
 
         egui::CentralPanel::default()
             // Margins set otherwise seem to be ignored
             .frame(
                 egui::Frame::default()
                     .inner_margin(13.5)
-                    .fill(Color32::from_gray(27)),
+                    .fill(palette.background.into()),
             )
             .show(ctx, |ui| {
                 // buttons we create are too small, and will by default be extra padded
-                ui.spacing_mut().interact_size = egui::vec2(30.0, 30.0);
-
-                egui::Grid::new("top_grid")
-                    .spacing([15.0, 15.0])
-                    .show(ui, |ui| {
-                        for top_y in 0..3 {
-                            for top_x in 0..3 {
-                                egui::Grid::new(format!("grid_{}_{}", top_x, top_y))
-                                    .spacing([1.0, 1.0])
-                                    .show(ui, |ui| {
-                                        for inner_y in 0..3 {
-                                            for inner_x in 0..3 {
-                                                let x = top_x * 3 + inner_x;
-                                                let y = top_y * 3 + inner_y;
-                                                let color: Color32 = self.colour(x, y).into();
-                                                let enabled = self.get(x, y).enabled;
-                                                let text = self.text(x, y);
-
-                                                let button = Button::new(text)
-                                                    .frame(true)
-                                                    .min_size(egui::vec2(30.0, 30.0));
-
-                                                let response = ui
-                                                    .scope(|ui| {
-                                                        let styles = ui.style_mut();
-                                                        styles
-                                                            .visuals
-                                                            .widgets
-                                                            .inactive
-                                                            .weak_bg_fill = color;
-                                                        styles
-                                                            .visuals
-                                                            .widgets
-                                                            .hovered
-                                                            .weak_bg_fill =
-                                                            color.add(Color32::from_gray(27));
-                                                        ui.add_enabled(enabled, button)
-                                                    })
-                                                    .inner;
-
-                                                if response.clicked() {
-                                                    self.add(x, y, 1);
-                                                }
-                                                if response.secondary_clicked() {
-                                                    self.add(x, y, -1);
-                                                }
-                                                if enabled {
-                                                    response.on_hover_cursor(
-                                                        egui::CursorIcon::PointingHand,
-                                                    );
-                                                }
-                                            }
-                                            ui.end_row();
-                                        }
-                                    });
+                ui.spacing_mut().interact_size = egui::vec2(CELL_SIZE, CELL_SIZE);
+
+                // Gaps between 3x3 regions, or regions tinted for irregular ones, whose borders
+                // aren't straight lines
+                let regions = *model.regions();
+                let standard = regions.is_standard();
+                let tints = palette.region_tints(&regions);
+                egui::Grid::new("grid").spacing([1.0, 1.0]).show(ui, |ui| {
+                    for y in 0..9 {
+                        for x in 0..9 {
+                            let mut colour = palette.cell(model.colour(x, y));
+                            if !standard && colour == palette.cell {
+                                colour = tints[regions.region(x, y)];
+                            }
+                            if let Some(annotation) = model.annotation(x, y) {
+                                colour = colour.mix(palette.annotation(annotation));
+                            }
+                            let enabled = model.get(x, y).enabled();
+                            let text = egui::RichText::new(model.text(x, y))
+                                .color(palette.text(model.origin(x, y)));
+
+                            let mut button = Button::new(text)
+                                .frame(true)
+                                .min_size(egui::vec2(CELL_SIZE, CELL_SIZE));
+                            if model.is_marked(x, y) {
+                                button = button.stroke((1.0, Color32::from(palette.marked)));
+                            }
+
+                            let response = ui
+                                .scope(|ui| {
+                                    let styles = ui.style_mut();
+                                    styles.visuals.widgets.inactive.weak_bg_fill = colour.into();
+                                    styles.visuals.widgets.hovered.weak_bg_fill =
+                                        colour.mix(palette.hover).into();
+                                    ui.add_enabled(enabled, button)
+                                })
+                                .inner;
+
+                            if response.clicked() {
+                                let _input = session::click("primary");
+                                model.add(x, y, 1);
+                            }
+                            if response.secondary_clicked() {
+                                let _input = session::click("secondary");
+                                model.add(x, y, -1);
+                            }
+                            if response.middle_clicked() {
+                                let _input = session::click("middle");
+                                model.cycle_annotation(x, y);
+                            }
+                            if enabled {
+                                response.on_hover_cursor(egui::CursorIcon::PointingHand);
+                            }
+                            if standard && x < 8 && !regions.same(x, y, 1, 0) {
+                                ui.allocate_space(egui::vec2(REGION_GAP, 0.0));
                             }
+                        }
+                        ui.end_row();
+                        if standard && y < 8 && !regions.same(0, y, 0, 1) {
+                            ui.allocate_space(egui::vec2(0.0, REGION_GAP));
                             ui.end_row();
                         }
-                    })
+                    }
+                })
             });
     }
-}
 
-impl From<Colour> for Color32 {
-    fn from(c: Colour) -> Self {
-        match c {
-            Colour::Black => Color32::BLACK,
-            Colour::Red => Color32::DARK_RED,
-            Colour::Green => Color32::DARK_GREEN,
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.pack.record(self.puzzle, &self.model);
+        if let Err(err) = self.pack.save_progress() {
+            tracing::warn!("progress of the pack can't be saved: {err}");
         }
     }
 }
+
+/// Default fonts, with the one of the assets in front of them
+fn fonts(font: &'static [u8]) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+    fonts
+        .font_data
+        .insert(assets::FONT.into(), Arc::new(FontData::from_static(font)));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .insert(0, assets::FONT.into());
+    }
+    fonts
+}
+
+impl From<Rgb> for Color32 {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        Color32::from_rgb(r, g, b)
+    }
+}
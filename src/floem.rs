@@ -5,40 +5,68 @@
 
 use std::array;
 use std::cell::RefCell;
+use std::rc::Rc;
 
 use floem::IntoView;
-use floem::event::EventPropagation;
+use floem::event::{Event, EventListener, EventPropagation};
+use floem::keyboard::Key;
 use floem::kurbo::Size;
 use floem::peniko::Color;
 use floem::prelude::{RwSignal, button, h_stack_from_iter, v_stack_from_iter};
 use floem::reactive::{SignalGet, SignalUpdate, create_signal, create_updater};
-use floem::style::StyleValue;
 use floem::views::Decorators;
 use floem::window::{Icon, WindowConfig};
-use itertools::Itertools;
 
-use crate::{Colour, SudokuModel};
+use crate::assets;
+use crate::locale::Message;
+use crate::rpc::{self, Rpc};
+use crate::session;
+use crate::{Action, Colour, Keys, Options, Origin, Pack, Palette, Rgb, SudokuModel};
 
-pub fn main(sudoku_model: SudokuModel) {
-    let icon = image::ImageReader::open("www/favicon.png")
-        .unwrap()
-        .decode()
-        .unwrap()
-        .to_rgba8();
-    let window_config = WindowConfig::default()
-        .window_icon(Icon::from_rgba(icon.to_vec(), icon.width(), icon.height()).unwrap())
-        .title("Sudoku")
-        .size(Size {
-            width: 585.0,
-            height: 585.0,
-        });
+/// Size of cells in logical pixels, 15 at the scale of 3 that the view sets, without zoom
+const CELL_SIZE: f32 = 45.0;
+
+/// Plays the puzzle of the pack, and the next ones that aren't solved on [`Action::Next`], progress
+/// is saved when the window is closed
+pub fn main(pack: Pack, puzzle: usize, options: Options, rpc: Option<Rpc>) {
+    let mut window_config = WindowConfig::default().title(options.locale.text(Message::Title));
+    match image::load_from_memory(&assets::icon()) {
+        Ok(icon) => {
+            let icon = icon.to_rgba8();
+            window_config = window_config
+                .window_icon(Icon::from_rgba(icon.to_vec(), icon.width(), icon.height()).unwrap());
+        }
+        Err(err) => tracing::warn!("{} of the assets isn't an image: {err}", assets::ICON),
+    }
+    let window_config = window_config.size({
+        let (width, height) = options.window_size(585.0, 585.0, CELL_SIZE);
+        Size::new(width as f64, height as f64)
+    });
+    floem::text::FONT_SYSTEM
+        .lock()
+        .db_mut()
+        .load_font_data(assets::font().to_vec());
     floem::Application::new()
-        .window(move |_app| sudoku_model.into_view(), Some(window_config))
+        .window(
+            move |_app| {
+                floem::action::set_window_scale(options.zoom(CELL_SIZE) as f64);
+                let model = pack.model(puzzle).with_assist(options.assist);
+                model.into_view(pack, puzzle, options.keys, options.theme.palette(), rpc)
+            },
+            Some(window_config),
+        )
         .run();
 }
 
 impl SudokuModel {
-    fn into_view(self) -> impl IntoView {
+    fn into_view(
+        self,
+        pack: Pack,
+        puzzle: usize,
+        keys: Keys,
+        palette: &'static Palette,
+        rpc: Option<Rpc>,
+    ) -> impl IntoView {
         let colours = array::from_fn::<_, 9, _>(|x| {
             array::from_fn::<_, 9, _>(|y| RwSignal::<Colour>::new(self.colour(x, y)))
         });
@@ -46,22 +74,42 @@ impl SudokuModel {
             array::from_fn::<_, 9, _>(|y| RwSignal::<String>::new(self.text(x, y).to_string()))
         });
         let enabled = array::from_fn::<_, 9, _>(|x| {
-            array::from_fn::<_, 9, _>(|y| RwSignal::<bool>::new(self.get(x, y).enabled))
+            array::from_fn::<_, 9, _>(|y| RwSignal::<bool>::new(self.get(x, y).enabled()))
+        });
+        let origins = array::from_fn::<_, 9, _>(|x| {
+            array::from_fn::<_, 9, _>(|y| RwSignal::<Origin>::new(self.origin(x, y)))
         });
+        let marked = array::from_fn::<_, 9, _>(|x| {
+            array::from_fn::<_, 9, _>(|y| RwSignal::<bool>::new(self.is_marked(x, y)))
+        });
+        let regions = RwSignal::new(*self.regions());
         let (on_click, click) = create_signal((0usize, 0usize, 0i8));
-        let sudoku = RefCell::new(self);
-        create_updater(
-            move || on_click.get(),
+        let sudoku = Rc::new(RefCell::new(self));
+        // Puzzle of the pack that is played, givens change with it
+        let playing = Rc::new(RefCell::new((pack, puzzle)));
+        let refresh = move |sudoku: &SudokuModel| {
+            for x in 0..9 {
+                for y in 0..9 {
+                    text[x][y].set(sudoku.text(x, y).to_string());
+                    origins[x][y].set(sudoku.origin(x, y));
+                    colours[x][y].set(sudoku.colour(x, y));
+                    enabled[x][y].set(sudoku.get(x, y).enabled());
+                    marked[x][y].set(sudoku.is_marked(x, y));
+                }
+            }
+            regions.set(*sudoku.regions());
+        };
+        if let Some(rpc) = rpc {
+            poll(rpc, sudoku.clone(), (0, 0), refresh);
+        }
+        create_updater(move || on_click.get(), {
+            let sudoku = sudoku.clone();
             move |(x, y, v)| {
+                let _input = session::click(if v > 0 { "primary" } else { "secondary" });
                 sudoku.borrow_mut().add(x, y, v);
-                text[x][y].set(sudoku.borrow().get(x, y).text().to_string());
-                for x in 0..9 {
-                    for y in 0..9 {
-                        colours[x][y].set(sudoku.borrow().colour(x, y))
-                    }
-                }
-            },
-        );
+                refresh(&sudoku.borrow());
+            }
+        });
         let buttons: Vec<Vec<_>> = (0..9)
             .map(|y| {
                 (0..9)
@@ -76,45 +124,101 @@ impl SudokuModel {
                             })
                             .disabled(move || !enabled[x][y].get())
                             .style(move |s| {
+                                // Gaps between 3x3 regions, or regions tinted for irregular ones,
+                                // whose borders aren't straight lines
+                                let regions = regions.get();
+                                let standard = regions.is_standard();
+                                let mut background = palette.cell(colours[x][y].get());
+                                if !standard && background == palette.cell {
+                                    background =
+                                        palette.region_tints(&regions)[regions.region(x, y)];
+                                }
+                                let text = palette.text(origins[x][y].get());
+                                let marked = marked[x][y].get();
+                                let border = match marked {
+                                    true => palette.marked,
+                                    false => palette.border,
+                                };
                                 s.width(15)
                                     .height(15)
-                                    .disabled(|s| s.color(colours[x][y].get()))
-                                    .color(colours[x][y].get())
+                                    .apply_if(standard && x < 8 && !regions.same(x, y, 1, 0), |s| {
+                                        s.margin_right(15)
+                                    })
+                                    .apply_if(standard && y < 8 && !regions.same(x, y, 0, 1), |s| {
+                                        s.margin_bottom(15)
+                                    })
+                                    .font_family(assets::font_family().to_string())
+                                    .background(Color::from(background))
+                                    .color(Color::from(text))
+                                    .border_color(Color::from(border))
+                                    .hover(|s| {
+                                        s.background(Color::from(background.mix(palette.hover)))
+                                    })
+                                    .disabled(|s| {
+                                        s.background(Color::from(background))
+                                            .color(Color::from(text))
+                                            .apply_if(!marked, |s| {
+                                                s.border_color(Color::from(palette.given_border))
+                                            })
+                                    })
                             })
                     })
                     .collect()
             })
             .collect();
 
-        v_stack_from_iter(buttons.into_iter().chunks(3).into_iter().map(|chunk| {
-            v_stack_from_iter(chunk.into_iter().map(|buttons| {
-                h_stack_from_iter(
-                    buttons
-                        .into_iter()
-                        .chunks(3)
-                        .into_iter()
-                        .map(|chunk| h_stack_from_iter(chunk.into_iter())),
-                )
-                .style(|s| s.gap(15))
-            }))
-        }))
-        .style(|s| {
-            s.gap(15)
-                .padding_left(15)
-                .padding_top(15)
-                .max_width(225)
-                .max_height(225)
-        })
-        .window_scale(|| 3.0)
+        v_stack_from_iter(buttons.into_iter().map(h_stack_from_iter))
+            .style(move |s| {
+                s.background(Color::from(palette.background))
+                    .padding_left(15)
+                    .padding_top(15)
+                    .max_width(225)
+                    .max_height(225)
+            })
+            .window_scale(|| 3.0)
+            .on_event_stop(EventListener::KeyDown, {
+                let sudoku = sudoku.clone();
+                let playing = playing.clone();
+                move |event| {
+                    if let Event::KeyDown(event) = event
+                        && let Key::Character(key) = &event.key.logical_key
+                        && key.starts_with(keys.key(Action::Next))
+                    {
+                        let _input =
+                            tracing::debug_span!("input", source = "key", key = "next").entered();
+                        let (pack, puzzle) = &mut *playing.borrow_mut();
+                        pack.next(puzzle, &mut sudoku.borrow_mut());
+                        refresh(&sudoku.borrow());
+                    }
+                }
+            })
+            .on_event_stop(EventListener::WindowClosed, move |_| {
+                let (pack, puzzle) = &mut *playing.borrow_mut();
+                pack.record(*puzzle, &sudoku.borrow());
+                if let Err(err) = pack.save_progress() {
+                    tracing::warn!("progress of the pack can't be saved: {err}");
+                }
+            })
     }
 }
 
-impl From<Colour> for StyleValue<Color> {
-    fn from(c: Colour) -> Self {
-        match c {
-            Colour::Black => StyleValue::Val(Color::BLACK),
-            Colour::Red => StyleValue::Val(Color::RED),
-            Colour::Green => StyleValue::Val(Color::GREEN),
+/// Handles requests every [`rpc::TICK`], the board has no cursor, so one is kept for them
+fn poll(
+    mut rpc: Rpc,
+    sudoku: Rc<RefCell<SudokuModel>>,
+    mut cursor: (usize, usize),
+    refresh: impl Fn(&SudokuModel) + Copy + 'static,
+) {
+    floem::action::exec_after(rpc::TICK, move |_| {
+        if rpc.poll(&mut sudoku.borrow_mut(), &mut cursor) {
+            refresh(&sudoku.borrow());
         }
+        poll(rpc, sudoku, cursor, refresh);
+    });
+}
+
+impl From<Rgb> for Color {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        Color::from_rgb8(r, g, b)
     }
 }
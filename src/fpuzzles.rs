@@ -0,0 +1,428 @@
+//! f-puzzles JSON, the format variant setters share puzzles in, also used by SudokuPad links.
+//!
+//! Links carry the JSON compressed with lz-string, so [`decode`] accepts a whole f-puzzles or
+//! SudokuPad link, just the compressed part, or plain JSON, and works on pasted text without any
+//! network access. Givens, player's digits, jigsaw regions and the rules this crate knows are kept,
+//! everything else is reported as a [`Warning`] and left out.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use serde_json::{Map, Value, json};
+
+use crate::constraints::{self, Windoku};
+use crate::{Origin, Puzzle, RegionError, Regions, SudokuModel, lz};
+
+/// Rules that are a plain flag in f-puzzles, and have the same name here
+const FLAGS: [&str; 5] = [
+    "diagonal-",
+    "diagonal+",
+    "antiknight",
+    "antiking",
+    "nonconsecutive",
+];
+
+/// Fields that aren't rules: metadata, the grid itself and settings of the f-puzzles editor
+const IGNORED: [&str; 9] = [
+    "size",
+    "title",
+    "author",
+    "ruleset",
+    "grid",
+    "solution",
+    "highlightConflicts",
+    "disabledlogic",
+    "truecandidatesoptions",
+];
+
+#[derive(Debug)]
+pub enum FPuzzlesError {
+    Json(serde_json::Error),
+    /// Neither JSON, nor lz-string compressed JSON
+    Compressed,
+    /// Only 9x9 grids are supported
+    Size(usize),
+    /// Cell is missing, or has a value that isn't a digit. Rows and columns are counted from 1.
+    Cell {
+        row: usize,
+        column: usize,
+    },
+    Regions(RegionError),
+}
+
+impl Display for FPuzzlesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FPuzzlesError::Json(error) => write!(f, "invalid puzzle JSON: {error}"),
+            FPuzzlesError::Compressed => write!(f, "not an f-puzzles or SudokuPad puzzle"),
+            FPuzzlesError::Size(size) => {
+                write!(f, "{size}x{size} grids are not supported, only 9x9")
+            }
+            FPuzzlesError::Cell { row, column } => write!(f, "invalid cell R{row}C{column}"),
+            FPuzzlesError::Regions(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for FPuzzlesError {}
+
+impl From<serde_json::Error> for FPuzzlesError {
+    fn from(error: serde_json::Error) -> Self {
+        FPuzzlesError::Json(error)
+    }
+}
+
+impl From<RegionError> for FPuzzlesError {
+    fn from(error: RegionError) -> Self {
+        FPuzzlesError::Regions(error)
+    }
+}
+
+/// Part of the puzzle that was left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// Rule this crate doesn't have, e.g. `killercage`, with the number of its instances
+    Unsupported { name: String, count: usize },
+    /// Extra regions that aren't the four windoku windows
+    ExtraRegions(usize),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::Unsupported { name, count: 1 } => {
+                write!(f, "'{name}' is not supported and was left out")
+            }
+            Warning::Unsupported { name, count } => {
+                write!(
+                    f,
+                    "'{name}' is not supported, {count} of them were left out"
+                )
+            }
+            Warning::ExtraRegions(count) => write!(
+                f,
+                "only windoku extra regions are supported, {count} regions were left out"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub puzzle: Puzzle,
+    pub warnings: Vec<Warning>,
+}
+
+/// Link, compressed string or JSON, see the module docs
+pub fn decode(text: &str) -> Result<Import, FPuzzlesError> {
+    let text = text.trim();
+    if text.starts_with('{') {
+        return from_json(text);
+    }
+    // f-puzzles links have `?load=`, SudokuPad ones a `fpuzzles` prefix, either in the path or in
+    // `?puzzleid=`
+    let compressed = ["?load=", "fpuzzles"]
+        .iter()
+        .find_map(|marker| text.find(marker).map(|at| &text[at + marker.len()..]))
+        .unwrap_or(text);
+    let compressed = compressed.split(['&', '#']).next().unwrap_or_default();
+    let compressed = compressed
+        .replace("%2B", "+")
+        .replace("%2F", "/")
+        .replace("%3D", "=");
+    let json = lz::decompress_from_base64(&compressed).ok_or(FPuzzlesError::Compressed)?;
+    from_json(&json)
+}
+
+pub fn from_json(json: &str) -> Result<Import, FPuzzlesError> {
+    let fields: Map<String, Value> = serde_json::from_str(json)?;
+    let size = fields.get("size").and_then(Value::as_u64).unwrap_or(9) as usize;
+    if size != 9 {
+        return Err(FPuzzlesError::Size(size));
+    }
+
+    let mut givens = [[0; 9]; 9];
+    let mut entries = [[0; 9]; 9];
+    let mut regions = Regions::STANDARD;
+    let mut map = [[0; 9]; 9];
+    let mut custom_regions = false;
+    let rows = fields.get("grid").and_then(Value::as_array);
+    for y in 0..9 {
+        for x in 0..9 {
+            let invalid = || FPuzzlesError::Cell {
+                row: y + 1,
+                column: x + 1,
+            };
+            let cell = rows
+                .and_then(|rows| rows.get(y)?.as_array()?.get(x)?.as_object())
+                .ok_or_else(invalid)?;
+            let value = match cell.get("value") {
+                None => 0,
+                Some(value) => match value.as_u64() {
+                    Some(value @ 0..=9) => value as u8,
+                    _ => return Err(invalid()),
+                },
+            };
+            if cell.get("given").and_then(Value::as_bool) == Some(true) {
+                givens[x][y] = value;
+            } else {
+                entries[x][y] = value;
+            }
+            map[x][y] = match cell.get("region").and_then(Value::as_u64) {
+                Some(region) => {
+                    custom_regions = true;
+                    region.min(u8::MAX as u64) as u8
+                }
+                None => regions.region(x, y) as u8,
+            };
+        }
+    }
+    if custom_regions {
+        regions = Regions::new(map)?;
+    }
+
+    let mut model = SudokuModel::from(givens).with_regions(regions);
+    for (x, column) in entries.iter().enumerate() {
+        for (y, &value) in column.iter().enumerate() {
+            if value != 0 {
+                model.place(x, y, value, Origin::Imported);
+            }
+        }
+    }
+    model.clear_history();
+
+    let mut warnings = Vec::new();
+    for (name, value) in &fields {
+        let count = match value {
+            Value::Null | Value::Bool(false) => 0,
+            Value::Array(items) => items.len(),
+            _ => 1,
+        };
+        if count == 0 || IGNORED.contains(&name.as_str()) {
+            continue;
+        }
+        if FLAGS.contains(&name.as_str()) {
+            if let Some(constraint) = constraints::by_name(name) {
+                model.add_constraint(constraint);
+            }
+        } else if name == "extraregion" {
+            if windows(value) {
+                model.add_constraint(&Windoku);
+            } else {
+                warnings.push(Warning::ExtraRegions(count));
+            }
+        } else {
+            warnings.push(Warning::Unsupported {
+                name: name.clone(),
+                count,
+            });
+        }
+    }
+
+    let text = |name| fields.get(name).and_then(Value::as_str).map(str::to_string);
+    let mut puzzle = Puzzle::new(text("title").unwrap_or_default(), model);
+    puzzle.author = text("author");
+    Ok(Import { puzzle, warnings })
+}
+
+/// Whether the extra regions are exactly the four windows of windoku, in any order
+fn windows(value: &Value) -> bool {
+    let Some(regions) = value.as_array() else {
+        return false;
+    };
+    let mut found: Vec<Vec<String>> = regions
+        .iter()
+        .map(|region| {
+            let mut cells: Vec<String> = region
+                .get("cells")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|cell| cell.as_str().map(str::to_uppercase))
+                .collect();
+            cells.sort();
+            cells
+        })
+        .collect();
+    found.sort();
+    let mut expected = window_cells();
+    expected.sort();
+    found == expected
+}
+
+/// Cells of the windoku windows as f-puzzles names them, sorted within every window
+fn window_cells() -> Vec<Vec<String>> {
+    [(1, 1), (5, 1), (1, 5), (5, 5)]
+        .into_iter()
+        .map(|(left, top)| {
+            let mut cells: Vec<String> = (top..top + 3)
+                .flat_map(|y| (left..left + 3).map(move |x| format!("R{}C{}", y + 1, x + 1)))
+                .collect();
+            cells.sort();
+            cells
+        })
+        .collect()
+}
+
+/// JSON with the givens, the player's digits, regions when they aren't 3x3 boxes, the rules, and
+/// the title and author of the puzzle
+pub fn to_json(puzzle: &Puzzle) -> String {
+    let model = &puzzle.model;
+    let custom_regions = !model.regions().is_standard();
+    let grid: Vec<Vec<Value>> = (0..9)
+        .map(|y| {
+            (0..9)
+                .map(|x| {
+                    let cell = model.get(x, y);
+                    let mut fields = Map::new();
+                    if cell.value != 0 {
+                        fields.insert("value".into(), cell.value.into());
+                        if !cell.enabled() {
+                            fields.insert("given".into(), true.into());
+                        }
+                    }
+                    if custom_regions {
+                        fields.insert("region".into(), model.regions().region(x, y).into());
+                    }
+                    Value::Object(fields)
+                })
+                .collect()
+        })
+        .collect();
+
+    // Sorted, so that the same puzzle always gives the same link
+    let mut fields: BTreeMap<&str, Value> = BTreeMap::new();
+    fields.insert("size", 9.into());
+    fields.insert("grid", json!(grid));
+    if !puzzle.title.is_empty() {
+        fields.insert("title", puzzle.title.clone().into());
+    }
+    if let Some(author) = &puzzle.author {
+        fields.insert("author", author.clone().into());
+    }
+    for constraint in model.constraints() {
+        match constraint.name() {
+            "windoku" => {
+                let regions: Vec<Value> = window_cells()
+                    .into_iter()
+                    .map(|cells| json!({ "cells": cells }))
+                    .collect();
+                fields.insert("extraregion", regions.into());
+            }
+            name => {
+                if let Some(flag) = FLAGS.iter().find(|flag| **flag == name) {
+                    fields.insert(*flag, true.into());
+                }
+            }
+        }
+    }
+    json!(fields).to_string()
+}
+
+/// [`to_json`] compressed the way links carry it
+pub fn encode(puzzle: &Puzzle) -> String {
+    lz::compress_to_base64(&to_json(puzzle))
+}
+
+/// Link that opens the puzzle in SudokuPad
+pub fn sudokupad_link(puzzle: &Puzzle) -> String {
+    format!("https://sudokupad.app/fpuzzles{}", encode(puzzle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Diagonal;
+
+    #[test]
+    fn links_round_trip() {
+        let mut model = SudokuModel::jigsaw_example()
+            .with_constraint(&Diagonal::Positive)
+            .with_constraint(&Windoku);
+        let (x, y) = (0..81)
+            .map(|i| (i % 9, i / 9))
+            .find(|&(x, y)| model.get(x, y).value == 0)
+            .unwrap();
+        model.set(x, y, 5);
+        let mut puzzle = Puzzle::new("Jigsaw", model);
+        puzzle.author = Some("Somebody".to_string());
+
+        let import = decode(&sudokupad_link(&puzzle)).unwrap();
+        assert!(import.warnings.is_empty());
+        let imported = import.puzzle;
+        assert_eq!(imported.title, "Jigsaw");
+        assert_eq!(imported.author.as_deref(), Some("Somebody"));
+        assert_eq!(imported.model.givens_line(), model.givens_line());
+        assert_eq!(imported.model.regions(), model.regions());
+        assert_eq!(imported.model.get(x, y).value, 5);
+        assert_eq!(imported.model.origin(x, y), Origin::Imported);
+        let names = |model: &SudokuModel| {
+            let mut names: Vec<_> = model.constraints().map(|c| c.name()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&imported.model), names(&model));
+
+        let link = format!("https://www.f-puzzles.com/?load={}", encode(&puzzle));
+        assert_eq!(decode(&link).unwrap().puzzle.model.line(), model.line());
+    }
+
+    #[test]
+    fn unsupported_rules_are_reported() {
+        let mut grid = vec![vec![json!({}); 9]; 9];
+        grid[0][2] = json!({ "value": 7, "given": true });
+        let json = json!({
+            "size": 9,
+            "grid": grid,
+            "antiking": true,
+            "killercage": [{ "cells": ["R1C1", "R1C2"], "value": "3" }, { "cells": ["R9C9"] }],
+            "thermometer": [],
+            "extraregion": [{ "cells": ["R1C1", "R1C2"] }],
+        })
+        .to_string();
+
+        let import = from_json(&json).unwrap();
+        assert_eq!(import.puzzle.model.get(2, 0).value, 7);
+        assert!(!import.puzzle.model.get(2, 0).enabled());
+        assert_eq!(import.puzzle.model.constraints().count(), 1);
+        assert_eq!(
+            import.warnings,
+            [
+                Warning::ExtraRegions(1),
+                Warning::Unsupported {
+                    name: "killercage".to_string(),
+                    count: 2
+                }
+            ]
+        );
+
+        assert!(matches!(
+            from_json(r#"{"size": 6, "grid": []}"#),
+            Err(FPuzzlesError::Size(6))
+        ));
+        assert!(matches!(
+            decode("https://sudokupad.app/sclN4Ig"),
+            Err(FPuzzlesError::Compressed)
+        ));
+    }
+
+    /// Laid out like the editor exports it: pencil marks, highlights, regions of every cell and
+    /// settings of the solver, which aren't rules
+    #[test]
+    fn editor_export_is_read() {
+        let imported = decode(include_str!("../puzzles/fpuzzles-jigsaw.json")).unwrap();
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+
+        let puzzle = imported.puzzle;
+        let example = SudokuModel::jigsaw_example();
+        assert_eq!(puzzle.title, "Jigsaw X");
+        assert_eq!(puzzle.author.as_deref(), Some("Somebody"));
+        assert_eq!(puzzle.model.givens_line(), example.givens_line());
+        assert_eq!(puzzle.model.regions(), example.regions());
+        let names: Vec<_> = puzzle.model.constraints().map(|c| c.name()).collect();
+        assert_eq!(names, ["diagonal-"]);
+        assert_eq!(puzzle.model.get(0, 0).value, 6);
+        assert_eq!(puzzle.model.origin(0, 0), Origin::Imported);
+        assert_eq!(puzzle.model.get(0, 1).value, 0);
+    }
+}
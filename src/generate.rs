@@ -0,0 +1,168 @@
+//! Random puzzles that have a single solution.
+//!
+//! A full grid is filled in a random order first, then givens are taken away one by one, as long
+//! as the solution stays unique. The same seed always gives the same puzzle, so seeds can be shared
+//! instead of puzzles.
+//!
+//! Minimal puzzles are rarely easy, so for a given difficulty givens of the solution are put back
+//! until the puzzle is easy enough, and other seeds are tried when it's too easy.
+
+use crate::{Difficulty, SudokuModel};
+
+/// How many seeds are tried for a puzzle that is hard enough
+const ATTEMPTS: u64 = 64;
+
+/// Small deterministic generator (SplitMix64), good enough for shuffling cells and digits
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, (self.next() % (i as u64 + 1)) as usize);
+        }
+    }
+}
+
+impl SudokuModel {
+    /// Puzzle with a single solution, where no given can be taken away without losing that
+    pub fn generate(seed: u64) -> SudokuModel {
+        let mut rng = Rng::new(seed);
+        let mut grid = SudokuModel::new();
+        fill(&mut grid, &mut rng);
+
+        let mut cells: [(usize, usize); 81] = core::array::from_fn(|i| (i % 9, i / 9));
+        rng.shuffle(&mut cells);
+        for (x, y) in cells {
+            let value = grid.get(x, y).value;
+            grid.get_mut(x, y).value = 0;
+            if grid.count_solutions(2) != 1 {
+                grid.get_mut(x, y).value = value;
+            }
+        }
+
+        let values = core::array::from_fn(|x| core::array::from_fn(|y| grid.get(x, y).value));
+        SudokuModel::from(values)
+    }
+
+    /// Puzzle with a single solution and the given difficulty, or the hardest one found when
+    /// that is too hard to come by. The same seed always gives the same puzzle here too, and
+    /// attempts are seeded from it, so that consecutive seeds don't share puzzles.
+    pub fn generate_graded(difficulty: Difficulty, seed: u64) -> SudokuModel {
+        let mut seeds = Rng::new(seed);
+        let mut hardest = None;
+        for _ in 0..ATTEMPTS {
+            let seed = seeds.next();
+            let (found, puzzle) = ease(SudokuModel::generate(seed), difficulty, seed);
+            if found == difficulty {
+                return puzzle;
+            }
+            if hardest.is_none_or(|(hardest, _)| found > hardest) {
+                hardest = Some((found, puzzle));
+            }
+        }
+        hardest.map_or_else(|| SudokuModel::generate(seed), |(_, puzzle)| puzzle)
+    }
+}
+
+fn difficulty(puzzle: &SudokuModel) -> Difficulty {
+    puzzle
+        .grade()
+        .map_or(Difficulty::Expert, |grade| grade.difficulty())
+}
+
+/// Puts back givens of the solution, in a random order, until the puzzle is no harder than asked
+fn ease(puzzle: SudokuModel, target: Difficulty, seed: u64) -> (Difficulty, SudokuModel) {
+    let found = difficulty(&puzzle);
+    if found <= target {
+        return (found, puzzle);
+    }
+    let Some(solution) = puzzle.solve() else {
+        return (found, puzzle);
+    };
+    let mut values: [[u8; 9]; 9] =
+        core::array::from_fn(|x| core::array::from_fn(|y| puzzle.get(x, y).value));
+    let mut cells: [(usize, usize); 81] = core::array::from_fn(|i| (i % 9, i / 9));
+    Rng::new(!seed).shuffle(&mut cells);
+    let mut eased = puzzle;
+    for (x, y) in cells {
+        if values[x][y] != 0 {
+            continue;
+        }
+        values[x][y] = solution.get(x, y).value;
+        eased = SudokuModel::from(values);
+        let found = difficulty(&eased);
+        if found <= target {
+            return (found, eased);
+        }
+    }
+    (difficulty(&eased), eased)
+}
+
+/// Completes the grid with digits tried in a random order, returns whether that worked
+fn fill(model: &mut SudokuModel, rng: &mut Rng) -> bool {
+    let best = (0..9)
+        .flat_map(|y| (0..9).map(move |x| (x, y)))
+        .filter(|&(x, y)| model.get(x, y).value == 0)
+        .min_by_key(|&(x, y)| model.candidates(x, y).len());
+    let Some((x, y)) = best else {
+        return true;
+    };
+    let candidates = model.candidates(x, y);
+    let mut digits = [0; 9];
+    let mut count = 0;
+    for value in candidates.iter() {
+        digits[count] = value;
+        count += 1;
+    }
+    rng.shuffle(&mut digits[..count]);
+    for &value in &digits[..count] {
+        model.get_mut(x, y).value = value;
+        if fill(model, rng) {
+            return true;
+        }
+    }
+    model.get_mut(x, y).value = 0;
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_puzzles_are_unique_and_minimal() {
+        let puzzle = SudokuModel::generate(42);
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.line(), SudokuModel::generate(42).line());
+        assert_ne!(puzzle.line(), SudokuModel::generate(43).line());
+
+        let mut reduced = puzzle;
+        let (x, y) = (0..81)
+            .map(|i| (i % 9, i / 9))
+            .find(|&(x, y)| puzzle.get(x, y).value != 0)
+            .unwrap();
+        reduced.get_mut(x, y).value = 0;
+        assert!(reduced.count_solutions(2) > 1);
+    }
+
+    #[test]
+    fn easy_puzzles_can_be_asked_for() {
+        let puzzle = SudokuModel::generate_graded(Difficulty::Easy, 7);
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.grade().unwrap().difficulty(), Difficulty::Easy);
+    }
+}
@@ -7,13 +7,28 @@
 //! * <https://github.com/zed-industries/zed/tree/main/crates/gpui/examples>
 
 use gpui::{
-    App, Application, Bounds, Context, Hsla, IntoElement, MouseButton, ParentElement, Render,
-    Styled, TitlebarOptions, Window, WindowBounds, WindowOptions, div, prelude::*, px, rgb,
+    App, Application, Bounds, Context, FocusHandle, Hsla, IntoElement, KeyDownEvent, MouseButton,
+    ParentElement, Render, Styled, TitlebarOptions, Window, WindowBounds, WindowOptions, div,
+    prelude::*, px, rgb,
 };
 
-use crate::{Colour, SudokuModel};
+use std::borrow::Cow;
 
-pub fn main(sudoku_model: SudokuModel) {
+use crate::assets;
+use crate::locale::Message;
+use crate::rpc::{self, Rpc};
+use crate::session;
+use crate::{Action, Keys, Options, Pack, Palette, Rgb, SudokuModel};
+
+/// Size of cells in pixels, without the configured one
+const CELL_SIZE: f32 = 58.;
+/// Space between 3x3 regions, along with the gap of the grid on either side
+const REGION_GAP: f32 = 13.;
+
+/// Elements are sized in pixels, so the scale only zooms the window, the cell size zooms both.
+/// The next puzzle of the pack that isn't solved is played on [`Action::Next`], progress is saved
+/// when the window is closed.
+pub fn main(pack: Pack, puzzle: usize, options: Options, rpc: Option<Rpc>) {
     Application::new().run(move |cx: &mut App| {
         // required to make sure the app exits after the window is closed
         cx.on_window_closed(|cx| {
@@ -22,21 +37,45 @@ pub fn main(sudoku_model: SudokuModel) {
             }
         })
         .detach();
+        if let Err(err) = cx
+            .text_system()
+            .add_fonts(vec![Cow::Borrowed(assets::font())])
+        {
+            tracing::warn!("{} of the assets can't be loaded: {err}", assets::FONT);
+        }
 
-        let bounds = Bounds::centered(None, gpui::size(px(585.), px(585.)), cx);
+        let (width, height) = options.window_size(585., 585., CELL_SIZE);
+        let bounds = Bounds::centered(None, gpui::size(px(width), px(height)), cx);
         cx.open_window(
             WindowOptions {
                 window_bounds: Some(WindowBounds::Windowed(bounds)),
                 titlebar: Some(TitlebarOptions {
-                    title: Some("Sudoku".into()),
+                    title: Some(options.locale.text(Message::Title).into()),
                     ..Default::default()
                 }),
                 ..Default::default()
             },
-            |_, cx| {
-                cx.new(|_| SudokuApp {
-                    model: sudoku_model,
-                })
+            move |window, cx| {
+                let app = cx.new(|cx| {
+                    if rpc.is_some() {
+                        SudokuApp::poll(cx);
+                    }
+                    cx.on_release(|app: &mut SudokuApp, _| app.save()).detach();
+                    SudokuApp {
+                        model: pack.model(puzzle).with_assist(options.assist),
+                        pack,
+                        puzzle,
+                        cell_size: options.cell_size.unwrap_or(CELL_SIZE),
+                        palette: options.theme.palette(),
+                        keys: options.keys,
+                        focus: cx.focus_handle(),
+                        rpc,
+                        cursor: (0, 0),
+                    }
+                });
+                // Keys only reach the focused element
+                window.focus(&app.read(cx).focus);
+                app
             },
         )
         .unwrap();
@@ -46,100 +85,156 @@ pub fn main(sudoku_model: SudokuModel) {
 
 struct SudokuApp {
     model: SudokuModel,
-}
-
-/// Lightens a color by adding grey to it (similar to egui's hover effect)
-fn lighten_color(color: Hsla) -> Hsla {
-    Hsla {
-        h: color.h,
-        s: color.s,
-        l: (color.l + 0.15).min(1.0), // Increase lightness by 15%, capped at 1.0
-        a: color.a,
-    }
+    pack: Pack,
+    /// Index of the puzzle of the board in the pack
+    puzzle: usize,
+    cell_size: f32,
+    palette: &'static Palette,
+    keys: Keys,
+    focus: FocusHandle,
+    rpc: Option<Rpc>,
+    /// Cell of requests, the board has no cursor of its own
+    cursor: (usize, usize),
 }
 
 impl Render for SudokuApp {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        // Build the grid imperatively to avoid borrow checker issues
-        let mut block_rows = Vec::new();
-
-        for top_y in 0..3 {
-            let mut block_row = div().flex().flex_row().gap(px(15.));
+        // Gaps between 3x3 regions, or regions tinted for irregular ones, whose borders aren't
+        // straight lines
+        let regions = *self.model.regions();
+        let standard = regions.is_standard();
+        let tints = (!standard).then(|| self.palette.region_tints(&regions));
 
-            for top_x in 0..3 {
-                let mut block = div().flex().flex_col().gap(px(1.));
-
-                for inner_y in 0..3 {
-                    let mut cell_row = div().flex().flex_row().gap(px(1.));
-
-                    for inner_x in 0..3 {
-                        let x = top_x * 3 + inner_x;
-                        let y = top_y * 3 + inner_y;
-                        let cell = self.render_cell(x, y, cx);
-                        cell_row = cell_row.child(cell);
-                    }
-
-                    block = block.child(cell_row);
+        // Build the grid imperatively to avoid borrow checker issues
+        let mut grid = div().flex().flex_col().gap(px(1.));
+        for y in 0..9 {
+            let mut row = div().flex().flex_row().gap(px(1.));
+            for x in 0..9 {
+                row = row.child(self.render_cell(x, y, tints.as_ref(), cx));
+                if standard && x < 8 && !regions.same(x, y, 1, 0) {
+                    row = row.child(div().w(px(REGION_GAP)));
                 }
-
-                block_row = block_row.child(block);
             }
-
-            block_rows.push(block_row);
-        }
-
-        // Main container with dark grey background
-        let mut grid = div().flex().flex_col().gap(px(15.));
-        for row in block_rows {
             grid = grid.child(row);
+            if standard && y < 8 && !regions.same(0, y, 0, 1) {
+                grid = grid.child(div().h(px(REGION_GAP)));
+            }
         }
 
+        // Main container with the background of the theme
         div()
             .flex()
             .flex_col()
-            .bg(rgb(0x1b1b1b))
+            .bg(Hsla::from(self.palette.background))
+            .font_family(assets::font_family())
             .size_full()
             .p(px(13.5))
+            .track_focus(&self.focus)
+            .on_key_down(cx.listener(|app, event: &KeyDownEvent, _window, cx| {
+                let next = app.keys.key(Action::Next);
+                if event
+                    .keystroke
+                    .key_char
+                    .as_ref()
+                    .is_some_and(|key| key.starts_with(next))
+                {
+                    let _input =
+                        tracing::debug_span!("input", source = "key", key = "next").entered();
+                    app.pack.next(&mut app.puzzle, &mut app.model);
+                    cx.notify();
+                }
+            }))
             .child(grid)
     }
 }
 
 impl SudokuApp {
-    fn render_cell(&mut self, x: usize, y: usize, cx: &mut Context<Self>) -> impl IntoElement {
+    fn save(&mut self) {
+        self.pack.record(self.puzzle, &self.model);
+        if let Err(err) = self.pack.save_progress() {
+            tracing::warn!("progress of the pack can't be saved: {err}");
+        }
+    }
+
+    /// Handles requests every [`rpc::TICK`], until the window is closed
+    fn poll(cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(rpc::TICK).await;
+                let polled = this.update(cx, |app, cx| {
+                    if let Some(rpc) = &mut app.rpc
+                        && rpc.poll(&mut app.model, &mut app.cursor)
+                    {
+                        cx.notify();
+                    }
+                });
+                if polled.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Cell, tinted by its region if there are tints, and it has no colour of its own
+    fn render_cell(
+        &mut self,
+        x: usize,
+        y: usize,
+        tints: Option<&[Rgb; 9]>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
         let text = self.model.text(x, y).to_string();
-        let colour = self.model.colour(x, y);
-        let color: Hsla = colour.into();
-        let enabled = self.model.get(x, y).enabled;
+        let palette = self.palette;
+        let mut colour = palette.cell(self.model.colour(x, y));
+        if let Some(tints) = tints
+            && colour == palette.cell
+        {
+            colour = tints[self.model.regions().region(x, y)];
+        }
+        if let Some(annotation) = self.model.annotation(x, y) {
+            colour = colour.mix(palette.annotation(annotation));
+        }
+        let enabled = self.model.get(x, y).enabled();
+        let origin = self.model.origin(x, y);
+        let marked = self.model.is_marked(x, y);
 
         let mut cell = div()
             .flex()
             .items_center()
             .justify_center()
-            .w(px(58.))
-            .h(px(58.))
-            .bg(color)
+            .w(px(self.cell_size))
+            .h(px(self.cell_size))
+            .bg(Hsla::from(colour))
             .border_1()
             .rounded(px(10.))
-            .border_color(rgb(0x999999))
-            .text_size(px(32.))
+            .border_color(Hsla::from(match marked {
+                true => palette.marked,
+                false => palette.border,
+            }))
+            .text_size(px(self.cell_size * 32. / CELL_SIZE))
+            .text_color(Hsla::from(palette.text(origin)))
             .child(text);
 
-        // Add white text color for black backgrounds
-        if enabled {
-            cell = cell.text_color(gpui::white());
-        } else {
-            cell = cell.text_color(rgb(0xaaaaaa));
-        }
+        // Colour annotations can be put on any cell
+        cell = cell.on_mouse_down(
+            MouseButton::Middle,
+            cx.listener(move |app, _event, _window, _cx| {
+                let _input = session::click("middle");
+                app.model.cycle_annotation(x, y);
+                _cx.notify();
+            }),
+        );
 
         // Only add click handlers if the cell is enabled
         if enabled {
-            // Create a lighter shade for hover by adding gray
-            let hover_color = lighten_color(color);
+            let hover_color = Hsla::from(colour.mix(palette.hover));
 
             cell = cell
                 .on_mouse_down(
                     MouseButton::Left,
                     cx.listener(move |app, _event, _window, _cx| {
+                        let _input = session::click("primary");
                         app.model.add(x, y, 1);
                         _cx.notify();
                     }),
@@ -147,6 +242,7 @@ impl SudokuApp {
                 .on_mouse_down(
                     MouseButton::Right,
                     cx.listener(move |app, _event, _window, _cx| {
+                        let _input = session::click("secondary");
                         app.model.add(x, y, -1);
                         _cx.notify();
                     }),
@@ -155,19 +251,18 @@ impl SudokuApp {
                 .hover(|style| style.bg(hover_color));
         } else {
             // Disabled cells have a stronger border
-            cell = cell.border_2().border_color(gpui::black());
+            cell = cell.border_2();
+            if !marked {
+                cell = cell.border_color(Hsla::from(palette.given_border));
+            }
         }
 
         cell
     }
 }
 
-impl From<Colour> for Hsla {
-    fn from(c: Colour) -> Self {
-        match c {
-            Colour::Black => rgb(0x000000).into(),
-            Colour::Red => rgb(0x8b0000).into(),
-            Colour::Green => rgb(0x006400).into(),
-        }
+impl From<Rgb> for Hsla {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        rgb(u32::from_be_bytes([0, r, g, b])).into()
     }
 }
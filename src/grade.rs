@@ -0,0 +1,218 @@
+//! Rates how hard a puzzle is, by solving it the way a person would.
+//!
+//! Naked singles (a cell with one candidate) and hidden singles (a digit with one place in a row,
+//! a column or a region) are applied for as long as they work. When they get stuck, the cell with
+//! the fewest candidates is guessed, and every guess counts towards the rating.
+
+use crate::{Candidates, SudokuModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Difficulty> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Steps it took to solve a puzzle
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Grade {
+    pub naked_singles: usize,
+    pub hidden_singles: usize,
+    /// Values tried when singles didn't help, including the ones that turned out wrong
+    pub guesses: usize,
+}
+
+impl Grade {
+    pub fn difficulty(&self) -> Difficulty {
+        match (self.hidden_singles, self.guesses) {
+            (0, 0) => Difficulty::Easy,
+            (_, 0) => Difficulty::Medium,
+            (_, 1..=4) => Difficulty::Hard,
+            _ => Difficulty::Expert,
+        }
+    }
+
+    /// From 1 to 5, on the same scale as ratings in puzzle packs
+    pub fn rating(&self) -> f32 {
+        let singles = (self.naked_singles + self.hidden_singles).max(1) as f32;
+        let within = match self.difficulty() {
+            Difficulty::Easy => 0.0,
+            Difficulty::Medium => self.hidden_singles as f32 / singles,
+            Difficulty::Hard => (self.guesses - 1) as f32 / 4.0,
+            Difficulty::Expert => 1.0 - 5.0 / self.guesses as f32,
+        };
+        1.0 + Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == self.difficulty())
+            .unwrap_or_default() as f32
+            + within
+    }
+}
+
+impl SudokuModel {
+    /// How the puzzle gets solved, `None` if it can't be solved at all
+    pub fn grade(&self) -> Option<Grade> {
+        let mut grade = Grade::default();
+        if self.has_conflicts() || !guess(*self, &mut grade) {
+            return None;
+        }
+        Some(grade)
+    }
+}
+
+/// Whether the model can be solved, trying the candidates of the most constrained cell when
+/// singles don't lead anywhere
+fn guess(mut model: SudokuModel, grade: &mut Grade) -> bool {
+    if !apply_singles(&mut model, grade) {
+        return false;
+    }
+    let best = (0..9)
+        .flat_map(|y| (0..9).map(move |x| (x, y)))
+        .filter(|&(x, y)| model.get(x, y).value == 0)
+        .min_by_key(|&(x, y)| model.candidates(x, y).len());
+    let Some((x, y)) = best else {
+        return true;
+    };
+    model.candidates(x, y).iter().any(|value| {
+        grade.guesses += 1;
+        let mut next = model;
+        next.get_mut(x, y).value = value;
+        guess(next, grade)
+    })
+}
+
+/// Fills singles until there are none left, returns `false` on a contradiction.
+///
+/// Candidates of all cells are taken once per pass, and only shrink as the pass goes on, so every
+/// single is checked against the current candidates before it's placed.
+fn apply_singles(model: &mut SudokuModel, grade: &mut Grade) -> bool {
+    loop {
+        let mut candidates = [[Candidates::default(); 9]; 9];
+        for (x, column) in candidates.iter_mut().enumerate() {
+            for (y, cell) in column.iter_mut().enumerate() {
+                *cell = model.candidates(x, y);
+                if cell.is_empty() && model.get(x, y).value == 0 {
+                    return false;
+                }
+            }
+        }
+
+        let mut progress = false;
+        for (x, column) in candidates.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                if cell.len() != 1 {
+                    continue;
+                }
+                let current = model.candidates(x, y);
+                let Some(value) = current.iter().next() else {
+                    return false;
+                };
+                model.get_mut(x, y).value = value;
+                grade.naked_singles += 1;
+                progress = true;
+            }
+        }
+        if progress {
+            continue;
+        }
+
+        for group in 0..27 {
+            for value in 1..=9 {
+                let cells = group_cells(model, group);
+                if cells.iter().any(|&(x, y)| model.get(x, y).value == value) {
+                    continue;
+                }
+                let mut places = cells
+                    .into_iter()
+                    .filter(|&(x, y)| candidates[x][y].contains(value));
+                match (places.next(), places.next()) {
+                    (None, _) => return false,
+                    (Some((x, y)), None) => {
+                        if !model.candidates(x, y).contains(value) {
+                            return false;
+                        }
+                        model.get_mut(x, y).value = value;
+                        grade.hidden_singles += 1;
+                        progress = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !progress {
+            return true;
+        }
+    }
+}
+
+/// Rows, then columns, then regions
+fn group_cells(model: &SudokuModel, group: usize) -> [(usize, usize); 9] {
+    let index = group % 9;
+    match group / 9 {
+        0 => core::array::from_fn(|x| (x, index)),
+        1 => core::array::from_fn(|y| (index, y)),
+        _ => {
+            let mut cells = [(0, 0); 9];
+            for (cell, position) in model.regions().cells(index).zip(&mut cells) {
+                *position = cell;
+            }
+            cells
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pack;
+
+    #[test]
+    fn harder_puzzles_rate_higher() {
+        let pack = Pack::default_pack();
+        let grade = |title: &str| {
+            let index = (0..pack.len())
+                .find(|&index| pack.puzzles()[index].title == title)
+                .unwrap();
+            pack.model(index).grade().unwrap()
+        };
+        let euler = grade("Grid 01");
+        let inkala = grade("World's Hardest");
+        assert_eq!(euler.guesses, 0);
+        assert!(inkala.guesses > 0);
+        assert!(euler.rating() < inkala.rating());
+        assert!((1.0..=5.0).contains(&inkala.rating()));
+    }
+
+    #[test]
+    fn unsolvable_has_no_grade() {
+        let mut model = SudokuModel::new();
+        model.set(0, 0, 5);
+        model.set(0, 8, 5);
+        assert_eq!(model.grade(), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Undo history of the model.
+//!
+//! The model is `Copy`, so the history is a fixed ring buffer: once it's full, the oldest edits are
+//! forgotten.
+
+use crate::SudokuValue;
+
+/// How many edits can be undone
+pub const HISTORY: usize = 64;
+
+/// State of a cell before it was changed
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Edit {
+    Value { x: u8, y: u8, before: SudokuValue },
+    Annotation { x: u8, y: u8, before: u8 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct History {
+    edits: [Option<Edit>; HISTORY],
+    start: usize,
+    len: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            edits: [None; HISTORY],
+            start: 0,
+            len: 0,
+        }
+    }
+}
+
+impl History {
+    pub fn push(&mut self, edit: Edit) {
+        self.edits[(self.start + self.len) % HISTORY] = Some(edit);
+        if self.len == HISTORY {
+            self.start = (self.start + 1) % HISTORY;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Edit> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.edits[(self.start + self.len) % HISTORY].take()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Origin, SudokuModel};
+
+    #[test]
+    fn undo_values_and_annotations() {
+        let mut model = SudokuModel::example();
+        assert!(!model.can_undo());
+        model.set(4, 3, 3);
+        model.place(4, 3, 5, Origin::Hint);
+        model.annotate(4, 3, Some(2));
+        model.annotate(4, 3, Some(2));
+        assert_eq!(model.annotation(4, 3), Some(2));
+
+        assert!(model.undo());
+        assert_eq!(model.annotation(4, 3), None);
+        assert!(model.undo());
+        assert_eq!(model.origin(4, 3), Origin::Player);
+        assert_eq!(model.get(4, 3).value, 3);
+        assert!(model.undo());
+        assert_eq!(model.get(4, 3).value, 0);
+        assert!(!model.undo());
+    }
+
+    #[test]
+    fn oldest_edits_are_forgotten() {
+        let mut history = History::default();
+        for x in 0..HISTORY as u8 + 2 {
+            history.push(Edit::Annotation { x, y: 0, before: 0 });
+        }
+        let mut count = 0;
+        let mut last = None;
+        while let Some(Edit::Annotation { x, .. }) = history.pop() {
+            count += 1;
+            last = Some(x);
+        }
+        assert_eq!(count, HISTORY);
+        assert_eq!(last, Some(2));
+        assert!(history.is_empty());
+    }
+}
@@ -1,22 +1,36 @@
 //! No means to connect to external events, not very flexible, but somewhat easy to understand.
+//! Events of elsewhere, like requests of [`Rpc`], come through a subscription to a stream.
 
 use iced::border::Radius;
-use iced::font::Weight;
+use iced::font::{Family, Weight};
+use iced::keyboard::{self, Key};
 use iced::widget::button::{Status, Style};
-use iced::widget::{Column, Row, button};
-use iced::{Background, Border, Color, Element, Font, Pixels, Settings, Task, window};
+use iced::widget::{Column, Container, Row, button, container, mouse_area};
+use iced::{
+    Background, Border, Color, Element, Font, Pixels, Settings, Subscription, Task, Theme, window,
+};
 
-use crate::{Colour, SudokuModel};
+use crate::rpc::{self, Rpc};
+use crate::session;
+use crate::{Action, Keys, Options, Pack, Palette, Rgb, SudokuModel};
+use crate::{assets, locale};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Message {
     Click(usize, usize),
+    Annotate(usize, usize),
+    /// Time to poll for requests
+    Tick,
+    /// Character typed, that may be bound to an action
+    Key(char),
+    /// Window is about to close, progress is saved first
+    Close(window::Id),
 }
 
 pub const CELL_SIZE: f32 = 50.0;
 pub const WINDOW_SIZE: f32 = CELL_SIZE * 11.0;
 const DEFAULT_BORDER: Border = Border {
-    color: Color::from_rgb(0.6, 0.6, 0.6),
+    color: Color::BLACK,
     width: 1.0,
     radius: Radius {
         top_left: CELL_SIZE / 6.0,
@@ -26,35 +40,56 @@ const DEFAULT_BORDER: Border = Border {
     },
 };
 
-fn style_button_by_state(status: Status, mut style: Style) -> Style {
-    match status {
-        Status::Active => {
-            style.background = Some(Background::Color(Color::from_rgb(0.9, 0.9, 0.9)));
-        }
-        Status::Hovered => {
-            style.background = Some(Background::Color(Color::from_rgb(0.8, 0.8, 0.8)));
-        }
-        Status::Pressed => {
-            style.background = Some(Background::Color(Color::from_rgb(0.7, 0.7, 0.7)));
-        }
+/// Style of a cell with the background and text colours, and borders, of the palette
+fn cell_style(
+    palette: &Palette,
+    background: Rgb,
+    text: Rgb,
+    marked: bool,
+    status: Status,
+) -> Style {
+    let mut border = DEFAULT_BORDER;
+    border.color = palette.border.into();
+    if marked {
+        border.color = palette.marked.into();
+    }
+    let background = match status {
+        Status::Active => background,
+        Status::Hovered => background.mix(palette.hover),
+        Status::Pressed => background.mix(palette.hover).mix(palette.hover),
         Status::Disabled => {
-            style.background = Some(Background::Color(Color::WHITE));
-            let mut border = DEFAULT_BORDER.clone();
-            border.width = border.width * 1.5;
-            border.color = Color::BLACK;
-            style.border = border;
+            border.width *= 1.5;
+            if !marked {
+                border.color = palette.given_border.into();
+            }
+            background
         }
+    };
+    Style {
+        background: Some(Background::Color(background.into())),
+        text_color: text.into(),
+        border,
+        ..Style::default()
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        Color::from_rgb8(r, g, b)
     }
-    style
 }
 
-pub fn main(sudoku_model: SudokuModel) -> iced::Result {
+/// Plays the puzzle of the pack, and the next ones that aren't solved on [`Action::Next`], progress
+/// is saved when the window is closed
+pub fn main(pack: Pack, puzzle: usize, options: Options, rpc: Option<Rpc>) -> iced::Result {
+    let (width, height) = options.window_size(WINDOW_SIZE, WINDOW_SIZE, CELL_SIZE);
     let window_settings = window::Settings {
-        size: iced::Size {
-            width: WINDOW_SIZE,
-            height: WINDOW_SIZE,
-        },
-        icon: Some(window::icon::from_file("www/favicon.png").unwrap()),
+        size: iced::Size { width, height },
+        icon: window::icon::from_file_data(&assets::icon(), None)
+            .inspect_err(|err| {
+                tracing::warn!("{} of the assets isn't an image: {err}", assets::ICON)
+            })
+            .ok(),
         resizable: false,
         decorations: true,
         ..Default::default()
@@ -62,73 +97,156 @@ pub fn main(sudoku_model: SudokuModel) -> iced::Result {
     let settings: Settings = Settings {
         default_text_size: Pixels(CELL_SIZE / 1.75),
         default_font: Font {
+            family: Family::Name(assets::font_family()),
             weight: Weight::Bold,
             ..Default::default()
         },
         ..Default::default()
     };
 
-    iced::application("Sudoku", SudokuModel::update, SudokuModel::view)
-        .settings(settings)
-        .window(window_settings)
-        .run_with(move || (sudoku_model, Task::none()))
+    iced::application(
+        options.locale.text(locale::Message::Title),
+        Game::update,
+        Game::view,
+    )
+    .subscription(Game::subscription)
+    .settings(settings)
+    .font(assets::font())
+    .window(window_settings)
+    .exit_on_close_request(false)
+    .scale_factor(move |_| options.zoom(CELL_SIZE) as f64)
+    .theme(move |_| match options.theme.is_dark() {
+        true => Theme::Dark,
+        false => Theme::Light,
+    })
+    .run_with(move || {
+        let game = Game {
+            model: pack.model(puzzle).with_assist(options.assist),
+            pack,
+            puzzle,
+            palette: options.theme.palette(),
+            keys: options.keys,
+            rpc,
+            cursor: (0, 0),
+        };
+        (game, Task::none())
+    })
 }
 
-impl SudokuModel {
-    pub fn view(&self) -> Column<'_, Message> {
-        let default_button_style: Style = Style {
-            background: Some(Background::Color(Color::WHITE)),
-            border: DEFAULT_BORDER,
-            ..Style::default()
-        };
-        let black = {
-            let mut result = default_button_style.clone();
-            result.text_color = Color::BLACK;
-            result
-        };
-        let red = {
-            let mut result = default_button_style.clone();
-            result.text_color = Color::from_rgb(0.8, 0.0, 0.0);
-            result
-        };
-        let green = {
-            let mut result = default_button_style.clone();
-            result.text_color = Color::from_rgb(0.0, 0.6, 0.0);
-            result
+/// Board, and requests that drive it, if any. The board has no cursor, so one is kept for them.
+struct Game {
+    model: SudokuModel,
+    pack: Pack,
+    /// Index of the puzzle of the board in the pack
+    puzzle: usize,
+    palette: &'static Palette,
+    keys: Keys,
+    rpc: Option<Rpc>,
+    cursor: (usize, usize),
+}
+
+impl Game {
+    fn view(&self) -> Container<'_, Message> {
+        let background = self.palette.background;
+        container(self.model.view(self.palette))
+            .style(move |_| container::Style::default().background(Color::from(background)))
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Key(key) if self.keys.action(key) == Some(Action::Next) => {
+                let _input = tracing::debug_span!("input", source = "key", key = "next").entered();
+                self.pack.next(&mut self.puzzle, &mut self.model);
+            }
+            Message::Close(id) => {
+                self.pack.record(self.puzzle, &self.model);
+                if let Err(err) = self.pack.save_progress() {
+                    tracing::warn!("progress of the pack can't be saved: {err}");
+                }
+                return window::close(id);
+            }
+            message => self.model.update(message),
+        }
+        if let Some(rpc) = &mut self.rpc {
+            rpc.poll(&mut self.model, &mut self.cursor);
+        }
+        Task::none()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let ticks = match self.rpc {
+            Some(_) => Subscription::run(ticks),
+            None => Subscription::none(),
         };
-        Column::with_children((0..9).flat_map(|y| {
+        let keys = keyboard::on_key_press(|key, _| match key {
+            Key::Character(text) => text.chars().next().map(Message::Key),
+            _ => None,
+        });
+        Subscription::batch([ticks, keys, window::close_requests().map(Message::Close)])
+    }
+}
+
+/// [`Message::Tick`] every [`rpc::TICK`], from a thread, as there's no timer without an async
+/// runtime feature
+fn ticks() -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(1, |mut output| async move {
+        std::thread::spawn(move || {
+            while !output.is_closed() {
+                std::thread::sleep(rpc::TICK);
+                // Ticks that weren't handled yet are enough
+                let _ = output.try_send(Message::Tick);
+            }
+        });
+        std::future::pending().await
+    })
+}
+
+impl SudokuModel {
+    /// Grid of cells, with gaps between 3x3 regions, or with regions tinted for irregular ones,
+    /// whose borders aren't straight lines
+    pub fn view(&self, palette: &'static Palette) -> Column<'_, Message> {
+        let standard = self.regions().is_standard();
+        let tints = palette.region_tints(self.regions());
+        Column::with_children((0..9).flat_map(move |y| {
             let mut children = vec![];
-            if y % 3 == 0 {
+            if standard && y % 3 == 0 {
                 children.push(Element::from(iced::widget::vertical_space()))
             }
             let row = Element::from(Row::with_children((0..9).flat_map(move |x| {
                 let mut children = vec![];
-                if x % 3 == 0 {
+                if standard && x % 3 == 0 {
                     children.push(Element::from(iced::widget::horizontal_space()))
                 }
-                let enabled = self.get(x, y).enabled;
+                let enabled = self.get(x, y).enabled();
+                let marked = self.is_marked(x, y);
+                let mut background = palette.cell(self.colour(x, y));
+                if !standard && background == palette.cell {
+                    background = tints[self.regions().region(x, y)];
+                }
+                if let Some(annotation) = self.annotation(x, y) {
+                    background = background.mix(palette.annotation(annotation));
+                }
+                let text = palette.text(self.origin(x, y));
                 children.push(Element::from(
-                    button(self.text(x, y))
-                        .on_press_maybe(enabled.then_some(Message::Click(x, y)))
-                        .width(CELL_SIZE)
-                        .height(CELL_SIZE)
-                        .padding([5, 16])
-                        .style(move |_, status| {
-                            let style = match self.colour(x, y) {
-                                Colour::Black => black.clone(),
-                                Colour::Red => red.clone(),
-                                Colour::Green => green.clone(),
-                            };
-                            style_button_by_state(status, style)
-                        }),
+                    mouse_area(
+                        button(self.text(x, y))
+                            .on_press_maybe(enabled.then_some(Message::Click(x, y)))
+                            .width(CELL_SIZE)
+                            .height(CELL_SIZE)
+                            .padding([5, 16])
+                            .style(move |_, status| {
+                                cell_style(palette, background, text, marked, status)
+                            }),
+                    )
+                    .on_middle_press(Message::Annotate(x, y)),
                 ));
-                if x == 8 {
+                if standard && x == 8 {
                     children.push(Element::from(iced::widget::horizontal_space()))
                 }
                 children.into_iter()
             })));
             children.push(row);
-            if y == 8 {
+            if standard && y == 8 {
                 children.push(Element::from(iced::widget::vertical_space()))
             }
             children.into_iter()
@@ -139,7 +257,15 @@ impl SudokuModel {
 
     pub fn update(&mut self, message: Message) {
         match message {
-            Message::Click(x, y) => self.add(x, y, 1),
+            Message::Click(x, y) => {
+                let _input = session::click("primary");
+                self.add(x, y, 1)
+            }
+            Message::Annotate(x, y) => {
+                let _input = session::click("middle");
+                self.cycle_annotation(x, y)
+            }
+            Message::Tick | Message::Key(_) | Message::Close(_) => {}
         }
     }
 }
@@ -1,3 +1,9 @@
-pub fn main(sudoku_model: SudokuModel) -> Result<(), Box<dyn std::error::Error>> {
-    unimplemented!()
-}
\ No newline at end of file
+use crate::{Options, Pack};
+
+pub fn main(
+    _pack: Pack,
+    _puzzle: usize,
+    _options: Options,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("kas version isn't implemented yet".into())
+}
@@ -0,0 +1,9 @@
+use crate::{Options, Pack};
+
+pub fn main(
+    _pack: Pack,
+    _puzzle: usize,
+    _options: Options,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("leptos version isn't implemented yet".into())
+}
@@ -1,4 +1,61 @@
-use std::collections::HashSet;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use history::{Edit, History};
+
+#[cfg(feature = "std")]
+pub mod assets;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod booklet;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod constraints;
+#[cfg(feature = "std")]
+pub mod convert;
+#[cfg(feature = "std")]
+mod dlx;
+#[cfg(feature = "std")]
+pub mod fpuzzles;
+mod generate;
+mod grade;
+mod history;
+pub mod locale;
+#[cfg(feature = "std")]
+mod lz;
+mod options;
+#[cfg(feature = "std")]
+pub mod pack;
+#[cfg(feature = "std")]
+pub mod plain;
+#[cfg(feature = "python")]
+pub mod python;
+mod regions;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod rpc;
+pub mod samurai;
+#[cfg(feature = "std")]
+pub mod session;
+mod solver;
+mod theme;
+
+pub use constraints::Constraint;
+#[cfg(feature = "std")]
+pub use dlx::Dlx;
+pub use grade::{Difficulty, Grade};
+pub use history::HISTORY;
+pub use locale::Locale;
+pub use options::{Action, Keys, Options};
+#[cfg(feature = "std")]
+pub use pack::{Pack, Puzzle};
+pub use regions::{RegionError, Regions};
+pub use samurai::SamuraiModel;
+pub use solver::{Backtracking, Candidates, Solver};
+pub use theme::{Palette, Rgb, Theme};
 
 #[cfg(feature = "floem")]
 pub mod floem;
@@ -33,10 +90,63 @@ pub mod ratatui;
 #[cfg(feature = "kas")]
 pub mod kas;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy)]
 pub struct SudokuValue {
     value: u8,
-    enabled: bool,
+    origin: Origin,
+}
+
+/// Where the value of a cell came from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    /// Part of the puzzle, can't be changed
+    Given,
+    /// Entered by the player, also the origin of empty cells
+    #[default]
+    Player,
+    /// Revealed by a hint
+    Hint,
+    /// Filled in automatically, because it was the only candidate
+    AutoFill,
+    /// Came from a file, but isn't a part of the puzzle, e.g. somebody else's progress
+    Imported,
+}
+
+/// How much the game helps the player
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Assist {
+    /// Neither conflicts are shown, nor hints given
+    Off,
+    /// Conflicting and completed groups are coloured, but nothing is filled in
+    Conflicts,
+    /// Hints and filling in of singles too
+    #[default]
+    Full,
+}
+
+impl Assist {
+    pub const ALL: [Assist; 3] = [Assist::Off, Assist::Conflicts, Assist::Full];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Assist::Off => "off",
+            Assist::Conflicts => "conflicts",
+            Assist::Full => "full",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Assist> {
+        Self::ALL
+            .into_iter()
+            .find(|assist| assist.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl Origin {
+    /// Whether the player got help with the value
+    pub fn is_assisted(&self) -> bool {
+        matches!(self, Origin::Hint | Origin::AutoFill)
+    }
 }
 
 const VALUES: [&str; 10] = [" ", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
@@ -44,25 +154,35 @@ impl SudokuValue {
     pub fn text(&self) -> &'static str {
         VALUES[self.value as usize]
     }
-}
 
-impl Default for SudokuValue {
-    fn default() -> Self {
-        Self {
-            value: 0,
-            enabled: true,
-        }
+    /// Digit of the cell, `0` for an empty one
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Everything but givens can be changed
+    pub fn enabled(&self) -> bool {
+        self.origin != Origin::Given
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
-pub struct SudokuCell {
-    values: [[SudokuValue; 3]; 3],
+/// How many filled cells came from where, see [`SudokuModel::stats`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub given: usize,
+    pub player: usize,
+    pub hint: usize,
+    pub auto_fill: usize,
+    pub imported: usize,
 }
 
-impl SudokuCell {
-    pub fn new() -> SudokuCell {
-        SudokuCell::default()
+impl Stats {
+    pub fn assisted(&self) -> usize {
+        self.hint + self.auto_fill
     }
 }
 
@@ -77,15 +197,39 @@ impl From<[[u8; 9]; 9]> for SudokuModel {
                 }
             }
         }
+        result.clear_history();
         result
     }
 }
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct SudokuModel {
-    cells: [[SudokuCell; 3]; 3],
+    values: [[SudokuValue; 9]; 9],
+    regions: Regions,
+    constraints: [Option<&'static dyn Constraint>; MAX_CONSTRAINTS],
+    /// Palette index of the player's colour for every cell, `0` for none
+    annotations: [[u8; 9]; 9],
+    history: History,
+    assist: Assist,
 }
 
+/// How many extra rules a single puzzle can have at once
+pub const MAX_CONSTRAINTS: usize = 8;
+
+/// Colours players can mark cells with, as RGB. Annotations are indices `1..=9` into it, themes
+/// have their own shades of them, see [`Palette::annotation`].
+pub const PALETTE: [[u8; 3]; 9] = [
+    [220, 80, 80],
+    [230, 150, 60],
+    [220, 200, 60],
+    [90, 180, 90],
+    [60, 170, 170],
+    [80, 130, 220],
+    [150, 100, 210],
+    [220, 110, 170],
+    [140, 140, 140],
+];
+
 #[derive(Debug, Clone, Copy)]
 pub enum Colour {
     Black,
@@ -112,84 +256,504 @@ impl SudokuModel {
         ])
     }
 
+    pub fn jigsaw_example() -> Self {
+        let regions = Regions::new([
+            [0, 0, 0, 3, 3, 3, 6, 6, 6],
+            [0, 0, 0, 3, 3, 3, 6, 6, 6],
+            [1, 0, 0, 3, 3, 4, 7, 6, 6],
+            [1, 1, 0, 3, 4, 4, 7, 7, 6],
+            [1, 1, 1, 4, 4, 4, 7, 7, 7],
+            [1, 1, 2, 5, 4, 4, 7, 7, 8],
+            [1, 2, 2, 5, 5, 4, 7, 8, 8],
+            [2, 2, 2, 5, 5, 5, 8, 8, 8],
+            [2, 2, 2, 5, 5, 5, 8, 8, 8],
+        ])
+        .expect("example regions are valid");
+        Self::from([
+            [0, 0, 0, 0, 0, 4, 0, 0, 8],
+            [0, 0, 0, 0, 9, 0, 0, 0, 2],
+            [0, 0, 0, 3, 0, 0, 0, 0, 7],
+            [4, 0, 0, 0, 0, 0, 7, 0, 6],
+            [0, 0, 8, 0, 2, 0, 0, 3, 1],
+            [2, 0, 0, 0, 1, 0, 0, 0, 0],
+            [0, 0, 0, 5, 0, 9, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 8, 3, 0, 7, 0, 0, 0, 0],
+        ])
+        .with_regions(regions)
+    }
+
     pub fn text(&self, x: usize, y: usize) -> &str {
         self.get(x, y).text()
     }
 
     pub fn get_mut(&mut self, x: usize, y: usize) -> &mut SudokuValue {
-        let top_x = x / 3;
-        let top_y = y / 3;
-        let cell_x = x % 3;
-        let cell_y = y % 3;
-        &mut self.cells[top_x][top_y].values[cell_x][cell_y]
+        &mut self.values[x][y]
     }
 
     pub fn get(&self, x: usize, y: usize) -> &SudokuValue {
-        let top_x = x / 3;
-        let top_y = y / 3;
-        let cell_x = x % 3;
-        let cell_y = y % 3;
-        &self.cells[top_x][top_y].values[cell_x][cell_y]
+        &self.values[x][y]
     }
 
+    /// Value entered by the player, givens are left as they are
     pub fn set(&mut self, x: usize, y: usize, value: u8) {
-        let target = self.get_mut(x, y);
-        if target.enabled {
-            target.value = if value == u8::MAX { 9 } else { value % 10 };
+        self.place(x, y, value, Origin::Player);
+    }
+
+    /// Like [`SudokuModel::set`], but with a different origin, e.g. for hints
+    pub fn place(&mut self, x: usize, y: usize, value: u8, origin: Origin) {
+        let before = *self.get(x, y);
+        if before.enabled() {
+            let value = if value == u8::MAX { 9 } else { value % 10 };
+            if (before.value, before.origin) == (value, origin) {
+                return;
+            }
+            #[cfg(feature = "std")]
+            tracing::debug!(
+                row = y + 1,
+                column = x + 1,
+                old = before.value,
+                new = value,
+                ?origin,
+                "edit"
+            );
+            let target = self.get_mut(x, y);
+            target.value = value;
+            target.origin = origin;
+            self.history.push(Edit::Value {
+                x: x as u8,
+                y: y as u8,
+                before,
+            });
+        }
+    }
+
+    /// Palette index (`1..=9`, see [`PALETTE`]) of the colour the player marked the cell with
+    pub fn annotation(&self, x: usize, y: usize) -> Option<u8> {
+        Some(self.annotations[x][y]).filter(|&colour| colour != 0)
+    }
+
+    /// Marks the cell with a colour from [`PALETTE`], `None` or anything out of `1..=9` clears it.
+    /// Annotations don't affect values, and can be set on givens as well.
+    pub fn annotate(&mut self, x: usize, y: usize, colour: Option<u8>) {
+        let colour = colour
+            .filter(|colour| (1..=9).contains(colour))
+            .unwrap_or(0);
+        let before = self.annotations[x][y];
+        if before != colour {
+            #[cfg(feature = "std")]
+            tracing::debug!(
+                row = y + 1,
+                column = x + 1,
+                old = before,
+                new = colour,
+                "annotate"
+            );
+            self.annotations[x][y] = colour;
+            self.history.push(Edit::Annotation {
+                x: x as u8,
+                y: y as u8,
+                before,
+            });
+        }
+    }
+
+    /// Switches the cell to the next colour of [`PALETTE`], and back to none after the last one
+    pub fn cycle_annotation(&mut self, x: usize, y: usize) {
+        let next = self.annotation(x, y).map_or(1, |colour| colour + 1);
+        self.annotate(x, y, Some(next));
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// Reverts the last change of a value or an annotation, returns `false` if there was none.
+    /// Only the last [`HISTORY`] changes are remembered.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(Edit::Value { x, y, before }) => {
+                #[cfg(feature = "std")]
+                tracing::debug!(
+                    row = y + 1,
+                    column = x + 1,
+                    old = self.get(x as usize, y as usize).value,
+                    new = before.value,
+                    "undo"
+                );
+                *self.get_mut(x as usize, y as usize) = before;
+                true
+            }
+            Some(Edit::Annotation { x, y, before }) => {
+                #[cfg(feature = "std")]
+                tracing::debug!(
+                    row = y + 1,
+                    column = x + 1,
+                    old = self.annotations[x as usize][y as usize],
+                    new = before,
+                    "undo annotation"
+                );
+                self.annotations[x as usize][y as usize] = before;
+                true
+            }
+            None => false,
         }
     }
 
+    /// Makes the current state the starting point, e.g. after a game is loaded
+    pub fn clear_history(&mut self) {
+        self.history = History::default();
+    }
+
+    /// Disabled cell becomes a given, enabling a given turns it into player's value
     pub fn set_enabled(&mut self, x: usize, y: usize, enabled: bool) {
         let target = self.get_mut(x, y);
-        target.enabled = enabled;
+        if !enabled {
+            target.origin = Origin::Given;
+        } else if target.origin == Origin::Given {
+            target.origin = Origin::Player;
+        }
+    }
+
+    pub fn origin(&self, x: usize, y: usize) -> Origin {
+        self.get(x, y).origin
+    }
+
+    /// Counts filled cells by origin
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        for value in self
+            .values
+            .iter()
+            .flatten()
+            .filter(|value| value.value != 0)
+        {
+            *match value.origin {
+                Origin::Given => &mut stats.given,
+                Origin::Player => &mut stats.player,
+                Origin::Hint => &mut stats.hint,
+                Origin::AutoFill => &mut stats.auto_fill,
+                Origin::Imported => &mut stats.imported,
+            } += 1;
+        }
+        stats
     }
 
     pub fn add(&mut self, x: usize, y: usize, value: i8) {
         self.set(x, y, self.get(x, y).value.wrapping_add_signed(value));
     }
 
+    pub fn regions(&self) -> &Regions {
+        &self.regions
+    }
+
+    /// Replaces regions, keeping the values. Use [`Regions::STANDARD`] to go back to 3x3 boxes.
+    pub fn with_regions(mut self, regions: Regions) -> Self {
+        self.regions = regions;
+        self
+    }
+
+    pub fn constraints(&self) -> impl Iterator<Item = &'static dyn Constraint> + '_ {
+        self.constraints.iter().flatten().copied()
+    }
+
+    /// Adds an extra rule, unless the rule with the same name is already there. Returns `false` if
+    /// there is no room for another rule.
+    pub fn add_constraint(&mut self, constraint: &'static dyn Constraint) -> bool {
+        if self.constraints().any(|c| c.name() == constraint.name()) {
+            return true;
+        }
+        match self.constraints.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(constraint);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn with_constraint(mut self, constraint: &'static dyn Constraint) -> Self {
+        assert!(
+            self.add_constraint(constraint),
+            "at most {MAX_CONSTRAINTS} constraints are supported"
+        );
+        self
+    }
+
+    /// Whether any extra rule highlights the cell, e.g. it is on a diagonal
+    pub fn is_marked(&self, x: usize, y: usize) -> bool {
+        self.constraints().any(|constraint| constraint.marks(x, y))
+    }
+
+    /// Cells sharing a row, a column or a region with the given one, excluding the cell itself.
+    /// Cells that share both row and region (or column and region) are listed twice.
+    pub fn peers(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let region = self.regions.region(x, y);
+        (0..9)
+            .map(move |lookup_x| (lookup_x, y))
+            .chain((0..9).map(move |lookup_y| (x, lookup_y)))
+            .chain(self.regions.cells(region))
+            .filter(move |&cell| cell != (x, y))
+    }
+
+    pub fn assist(&self) -> Assist {
+        self.assist
+    }
+
+    pub fn set_assist(&mut self, assist: Assist) {
+        self.assist = assist;
+    }
+
+    pub fn with_assist(mut self, assist: Assist) -> Self {
+        self.assist = assist;
+        self
+    }
+
+    /// Red for conflicts, green when the cell completes its row, column or region, and always
+    /// black when assistance is off
     pub fn colour(&self, x: usize, y: usize) -> Colour {
-        let top_x = x / 3;
-        let top_y = y / 3;
-        let cell_x = x % 3;
-        let cell_y = y % 3;
-        let cell = &self.cells[top_x][top_y];
-        let target = cell.values[cell_x][cell_y].value;
-        let mut cell_values = HashSet::<u8>::from_iter(1..=9);
-        let mut row_values = HashSet::<u8>::from_iter(1..=9);
-        let mut col_values = HashSet::<u8>::from_iter(1..=9);
+        if self.assist == Assist::Off {
+            return Colour::Black;
+        }
+        let target = self.get(x, y).value;
+        let mut region_values = Candidates::ALL;
+        let mut row_values = Candidates::ALL;
+        let mut col_values = Candidates::ALL;
         if target != 0 {
-            for lookup_x in 0..3 {
-                for lookup_y in 0..3 {
-                    let value = cell.values[lookup_x][lookup_y].value;
-                    cell_values.remove(&value);
-                    if lookup_x == cell_x && lookup_y == cell_y {
-                        continue;
-                    }
-                    if target == value {
-                        return Colour::Red;
-                    }
+            for (lookup_x, lookup_y) in self.regions.cells(self.regions.region(x, y)) {
+                let value = self.get(lookup_x, lookup_y).value;
+                region_values.remove(value);
+                if lookup_x == x && lookup_y == y {
+                    continue;
+                }
+                if target == value {
+                    return Colour::Red;
                 }
             }
             for lookup_x in 0..9 {
                 let value = self.get(lookup_x, y).value;
-                row_values.remove(&value);
+                row_values.remove(value);
                 if x != lookup_x && target == value {
                     return Colour::Red;
                 }
             }
             for lookup_y in 0..9 {
                 let value = self.get(x, lookup_y).value;
-                col_values.remove(&value);
+                col_values.remove(value);
                 if y != lookup_y && target == value {
                     return Colour::Red;
                 }
             }
+            if self
+                .constraints()
+                .any(|constraint| !constraint.allows(self, x, y, target))
+            {
+                return Colour::Red;
+            }
         }
-        if cell_values.is_empty() || row_values.is_empty() || col_values.is_empty() {
+        if region_values.is_empty() || row_values.is_empty() || col_values.is_empty() {
             Colour::Green
         } else {
             Colour::Black
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::sample::subsequence;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    enum Change {
+        Set(usize, usize, u8),
+        Add(usize, usize, i8),
+        Place(usize, usize, u8, Origin),
+        Annotate(usize, usize, Option<u8>),
+        Undo,
+    }
+
+    fn cell() -> impl Strategy<Value = (usize, usize)> {
+        (0..9usize, 0..9usize)
+    }
+
+    /// `u8::MAX` is special for `set`, so it is tried more often than at random
+    fn value() -> impl Strategy<Value = u8> {
+        prop_oneof![Just(u8::MAX), 0..=9u8, any::<u8>()]
+    }
+
+    /// Mostly empty cells, with conflicts now and then, standard or jigsaw regions, and some rules
+    fn model() -> impl Strategy<Value = SudokuModel> {
+        // Cells row by row in a `Vec`, nested arrays of strategies don't fit on the test's stack
+        let cells = prop::collection::vec(prop_oneof![3 => Just(0u8), 1 => 1..=9u8], 81);
+        let rules = subsequence(constraints::BUILT_IN.to_vec(), 0..3);
+        (cells, rules, any::<bool>()).prop_map(|(cells, rules, jigsaw)| {
+            let grid = core::array::from_fn(|x| core::array::from_fn(|y| cells[x + 9 * y]));
+            let mut model = SudokuModel::from(grid);
+            if jigsaw {
+                model = model.with_regions(*SudokuModel::jigsaw_example().regions());
+            }
+            for rule in rules {
+                model.add_constraint(rule);
+            }
+            model
+        })
+    }
+
+    fn change() -> impl Strategy<Value = Change> {
+        let origin = prop_oneof![
+            Just(Origin::Player),
+            Just(Origin::Hint),
+            Just(Origin::AutoFill),
+            Just(Origin::Imported),
+        ];
+        prop_oneof![
+            (cell(), value()).prop_map(|((x, y), value)| Change::Set(x, y, value)),
+            (cell(), any::<i8>()).prop_map(|((x, y), value)| Change::Add(x, y, value)),
+            (cell(), value(), origin)
+                .prop_map(|((x, y), value, origin)| Change::Place(x, y, value, origin)),
+            (cell(), any::<Option<u8>>())
+                .prop_map(|((x, y), colour)| Change::Annotate(x, y, colour)),
+            Just(Change::Undo),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn set_then_get(mut model in model(), (x, y) in cell(), value in value()) {
+            let before = model.get(x, y).value;
+            model.set(x, y, value);
+            let expected = match value {
+                _ if !model.get(x, y).enabled() => before,
+                u8::MAX => 9,
+                value => value % 10,
+            };
+            prop_assert_eq!(model.get(x, y).value, expected);
+        }
+
+        #[test]
+        fn add_steps_around_the_digits(
+            mut model in model(),
+            (x, y) in cell(),
+            step in prop_oneof![Just(1i8), Just(-1i8)],
+        ) {
+            prop_assume!(model.get(x, y).enabled());
+            let before = model.get(x, y).value as i8;
+            model.add(x, y, step);
+            prop_assert_eq!(model.get(x, y).value as i8, (before + step).rem_euclid(10));
+        }
+
+        #[test]
+        fn givens_never_change(mut model in model(), changes in prop::collection::vec(change(), 0..40)) {
+            let givens = model.givens_line();
+            for change in changes {
+                match change {
+                    Change::Set(x, y, value) => model.set(x, y, value),
+                    Change::Add(x, y, value) => model.add(x, y, value),
+                    Change::Place(x, y, value, origin) => model.place(x, y, value, origin),
+                    Change::Annotate(x, y, colour) => model.annotate(x, y, colour),
+                    Change::Undo => {
+                        model.undo();
+                    }
+                }
+                prop_assert_eq!(model.givens_line(), givens.clone());
+                prop_assert!(model.values.iter().flatten().all(|value| value.value <= 9));
+            }
+        }
+
+        #[test]
+        fn conflicts_are_symmetric(model in model()) {
+            for (x, y) in (0..81).map(|index| (index % 9, index / 9)) {
+                let value = model.get(x, y).value;
+                let red = matches!(model.colour(x, y), Colour::Red);
+                for (peer_x, peer_y) in model.peers(x, y) {
+                    if value != 0 && model.get(peer_x, peer_y).value == value {
+                        prop_assert!(red);
+                        prop_assert!(matches!(model.colour(peer_x, peer_y), Colour::Red));
+                    }
+                }
+                // Extra rules are symmetric too, so the cells a rule clashes with are red as well.
+                // Built-in rules are between pairs of cells, so a partner is found by keeping
+                // only its value on the board.
+                if value == 0 {
+                    continue;
+                }
+                for constraint in model.constraints() {
+                    if constraint.allows(&model, x, y, value) {
+                        continue;
+                    }
+                    prop_assert!(red);
+                    let partners: Vec<_> = (0..81)
+                        .map(|other| (other % 9, other / 9))
+                        .filter(|&(other_x, other_y)| {
+                            let mut pair = model;
+                            for (clear_x, clear_y) in (0..81).map(|index| (index % 9, index / 9)) {
+                                if (clear_x, clear_y) != (other_x, other_y) {
+                                    pair.values[clear_x][clear_y].value = 0;
+                                }
+                            }
+                            (other_x, other_y) != (x, y) && !constraint.allows(&pair, x, y, value)
+                        })
+                        .collect();
+                    prop_assert!(!partners.is_empty(), "{} at {x}, {y}", constraint.name());
+                    prop_assert!(
+                        partners.iter().any(|&(other_x, other_y)| {
+                            matches!(model.colour(other_x, other_y), Colour::Red)
+                        }),
+                        "{} at {x}, {y}",
+                        constraint.name()
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn lines_round_trip(model in model()) {
+            let line = model.line();
+            prop_assert_eq!(SudokuModel::from_line(&line).unwrap().line(), line);
+        }
+
+        #[test]
+        fn packs_round_trip(model in model()) {
+            let mut text = "# title: Random\n".to_string();
+            if !model.regions().is_standard() {
+                let regions: String = (0..81)
+                    .map(|index| char::from(b'0' + model.regions().region(index % 9, index / 9) as u8))
+                    .collect();
+                text += &format!("# regions: {regions}\n");
+            }
+            let names: Vec<_> = model.constraints().map(|c| c.name()).collect();
+            if !names.is_empty() {
+                text += &format!("# constraints: {}\n", names.join(", "));
+            }
+            text += &format!("{}\n", model.givens_line());
+
+            let pack = Pack::parse(&text).unwrap();
+            prop_assert_eq!(pack.to_string(), text);
+            let parsed = pack.puzzles()[0].model;
+            prop_assert_eq!(parsed.line(), model.line());
+            prop_assert_eq!(parsed.regions(), model.regions());
+        }
+
+        #[test]
+        fn fpuzzles_round_trip(mut model in model(), entries in prop::collection::vec((cell(), 1..=9u8), 0..10)) {
+            for ((x, y), value) in entries {
+                model.set(x, y, value);
+            }
+            let import = fpuzzles::from_json(&fpuzzles::to_json(&Puzzle::new("", model))).unwrap();
+            prop_assert!(import.warnings.is_empty());
+            let imported = import.puzzle.model;
+            prop_assert_eq!(imported.givens_line(), model.givens_line());
+            prop_assert_eq!(imported.entries_line(), model.entries_line());
+            prop_assert_eq!(imported.regions(), model.regions());
+            let names = |model: &SudokuModel| {
+                let mut names: Vec<_> = model.constraints().map(|c| c.name()).collect();
+                names.sort();
+                names
+            };
+            prop_assert_eq!(names(&imported), names(&model));
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Texts of the frontends in the language of the player. [`Message`]s are looked up in the
+//! catalogue of the [`Locale`], and templates among them have `{}` where [`Locale::format`] puts
+//! values. Commands of the plain text, keys, and the command line stay English.
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+    Russian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Message {
+    Title,
+    SamuraiTitle,
+    Arrows,
+    Move,
+    SetValue,
+    /// Shorter [`Message::SetValue`], for narrow footers
+    Set,
+    Clear,
+    IncDec,
+    Next,
+    Zoom,
+    Quit,
+    /// Keys that quit are put in place of `{}`
+    TooSmall,
+    NotATerminal,
+    NeedsTerminal,
+    Error,
+    Solved,
+    HintFor,
+    Filled,
+    Help,
+    UnknownCommand,
+    MissingCell,
+    Unexpected,
+    NotACell,
+    NotADigit,
+    Given,
+    NoUndo,
+    NoHint,
+    AssistanceOff,
+}
+
+impl Message {
+    pub const ALL: [Message; 28] = [
+        Message::Title,
+        Message::SamuraiTitle,
+        Message::Arrows,
+        Message::Move,
+        Message::SetValue,
+        Message::Set,
+        Message::Clear,
+        Message::IncDec,
+        Message::Next,
+        Message::Zoom,
+        Message::Quit,
+        Message::TooSmall,
+        Message::NotATerminal,
+        Message::NeedsTerminal,
+        Message::Error,
+        Message::Solved,
+        Message::HintFor,
+        Message::Filled,
+        Message::Help,
+        Message::UnknownCommand,
+        Message::MissingCell,
+        Message::Unexpected,
+        Message::NotACell,
+        Message::NotADigit,
+        Message::Given,
+        Message::NoUndo,
+        Message::NoHint,
+        Message::AssistanceOff,
+    ];
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::English, Locale::German, Locale::Russian];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::German => "de",
+            Locale::Russian => "ru",
+        }
+    }
+
+    /// Locale of a language code, with or without a territory and an encoding, e.g. `de_AT.UTF-8`
+    pub fn by_name(name: &str) -> Option<Locale> {
+        let language = name.split(['_', '-', '.', '@']).next()?;
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.name().eq_ignore_ascii_case(language))
+    }
+
+    /// Locale of messages of the environment, `LC_ALL`, `LC_MESSAGES` or `LANG`, whichever is set
+    /// first, and English for others than the supported ones
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::by_name(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn text(&self, message: Message) -> &'static str {
+        match self {
+            Locale::English => english(message),
+            Locale::German => german(message),
+            Locale::Russian => russian(message),
+        }
+    }
+
+    /// Template of the message with its `{}` replaced by the values, in order
+    #[cfg(feature = "std")]
+    pub fn format(&self, message: Message, values: &[&dyn std::fmt::Display]) -> String {
+        let mut parts = self.text(message).split("{}");
+        let mut text = parts.next().unwrap_or_default().to_string();
+        for (part, value) in parts.zip(values) {
+            text += &value.to_string();
+            text += part;
+        }
+        text
+    }
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::Title => "Sudoku",
+        Message::SamuraiTitle => "Samurai Sudoku",
+        Message::Arrows => "Arrows",
+        Message::Move => "Move",
+        Message::SetValue => "Set value",
+        Message::Set => "Set",
+        Message::Clear => "Clear",
+        Message::IncDec => "Inc/Dec",
+        Message::Next => "Next",
+        Message::Zoom => "Zoom",
+        Message::Quit => "Quit",
+        Message::TooSmall => "terminal too small to render, press {} or {} to quit",
+        Message::NotATerminal => "Not a terminal, playing line by line, `help` lists the commands",
+        Message::NeedsTerminal => {
+            "Error: This application requires a terminal (TTY) to run.\n\
+             Please run it directly in a terminal, not through a pipe or non-TTY environment."
+        }
+        Message::Error => "error: {}",
+        Message::Solved => "solved",
+        Message::HintFor => "hint for {}",
+        Message::Filled => "filled {} cells",
+        Message::Help => {
+            "\
+r3c5=7      set the cell in row 3, column 5, `=0` clears it
+clear r3c5  clear the cell
+undo        undo the last change
+hint        fill in a cell with the value of the solution
+fill        fill in every cell that has a single candidate
+show        print the board
+quit        stop playing"
+        }
+        Message::UnknownCommand => "unknown command `{}`, `help` lists them",
+        Message::MissingCell => "which cell? like `clear r3c5`",
+        Message::Unexpected => "unexpected `{}`",
+        Message::NotACell => "`{}` isn't a cell, like `r3c5`",
+        Message::NotADigit => "`{}` isn't a digit from 0 to 9",
+        Message::Given => "{} is given",
+        Message::NoUndo => "nothing to undo",
+        Message::NoHint => "no hint, the puzzle can't be solved as it is",
+        Message::AssistanceOff => "assistance is off, see `--assist`",
+    }
+}
+
+fn german(message: Message) -> &'static str {
+    match message {
+        Message::Title => "Sudoku",
+        Message::SamuraiTitle => "Samurai-Sudoku",
+        Message::Arrows => "Pfeile",
+        Message::Move => "Bewegen",
+        Message::SetValue => "Wert setzen",
+        Message::Set => "Setzen",
+        Message::Clear => "Löschen",
+        Message::IncDec => "Plus/Minus",
+        Message::Next => "Weiter",
+        Message::Zoom => "Zoom",
+        Message::Quit => "Beenden",
+        Message::TooSmall => "Terminal zu klein zum Zeichnen, {} oder {} beendet",
+        Message::NotATerminal => {
+            "Kein Terminal, es wird Zeile für Zeile gespielt, `help` zeigt die Befehle"
+        }
+        Message::NeedsTerminal => {
+            "Fehler: Diese Anwendung braucht ein Terminal (TTY).\n\
+             Bitte direkt in einem Terminal starten, nicht über eine Pipe oder ohne TTY."
+        }
+        Message::Error => "Fehler: {}",
+        Message::Solved => "gelöst",
+        Message::HintFor => "Tipp für {}",
+        Message::Filled => "{} Zellen ausgefüllt",
+        Message::Help => {
+            "\
+r3c5=7      setzt die Zelle in Zeile 3, Spalte 5, `=0` löscht sie
+clear r3c5  löscht die Zelle
+undo        macht die letzte Änderung rückgängig
+hint        füllt eine Zelle mit dem Wert der Lösung
+fill        füllt jede Zelle, die nur einen Kandidaten hat
+show        zeigt das Brett
+quit        beendet das Spiel"
+        }
+        Message::UnknownCommand => "unbekannter Befehl `{}`, `help` zeigt alle",
+        Message::MissingCell => "welche Zelle? etwa `clear r3c5`",
+        Message::Unexpected => "unerwartetes `{}`",
+        Message::NotACell => "`{}` ist keine Zelle wie `r3c5`",
+        Message::NotADigit => "`{}` ist keine Ziffer von 0 bis 9",
+        Message::Given => "{} ist vorgegeben",
+        Message::NoUndo => "nichts rückgängig zu machen",
+        Message::NoHint => "kein Tipp, so ist das Rätsel nicht lösbar",
+        Message::AssistanceOff => "Hilfe ist aus, siehe `--assist`",
+    }
+}
+
+fn russian(message: Message) -> &'static str {
+    match message {
+        Message::Title => "Судоку",
+        Message::SamuraiTitle => "Судоку «Самурай»",
+        Message::Arrows => "Стрелки",
+        Message::Move => "Перейти",
+        Message::SetValue => "Поставить цифру",
+        Message::Set => "Цифра",
+        Message::Clear => "Стереть",
+        Message::IncDec => "Больше/меньше",
+        Message::Next => "Дальше",
+        Message::Zoom => "Масштаб",
+        Message::Quit => "Выход",
+        Message::TooSmall => "терминал слишком мал, {} или {} для выхода",
+        Message::NotATerminal => "Не терминал, игра идёт построчно, `help` покажет команды",
+        Message::NeedsTerminal => {
+            "Ошибка: этому приложению нужен терминал (TTY).\n\
+             Запустите его прямо в терминале, а не через канал или без TTY."
+        }
+        Message::Error => "ошибка: {}",
+        Message::Solved => "решено",
+        Message::HintFor => "подсказка для {}",
+        Message::Filled => "заполнено клеток: {}",
+        Message::Help => {
+            "\
+r3c5=7      ставит цифру в клетку строки 3, столбца 5, `=0` стирает её
+clear r3c5  стирает клетку
+undo        отменяет последнее изменение
+hint        заполняет клетку цифрой из решения
+fill        заполняет все клетки с единственным кандидатом
+show        выводит поле
+quit        завершает игру"
+        }
+        Message::UnknownCommand => "неизвестная команда `{}`, `help` покажет все",
+        Message::MissingCell => "какая клетка? например, `clear r3c5`",
+        Message::Unexpected => "лишнее `{}`",
+        Message::NotACell => "`{}` — не клетка, как `r3c5`",
+        Message::NotADigit => "`{}` — не цифра от 0 до 9",
+        Message::Given => "{} задана в условии",
+        Message::NoUndo => "нечего отменять",
+        Message::NoHint => "подсказки нет, в таком виде головоломка не решается",
+        Message::AssistanceOff => "помощь выключена, см. `--assist`",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_have_the_same_placeholders() {
+        for locale in Locale::ALL {
+            for message in Message::ALL {
+                let count = |text: &str| text.matches("{}").count();
+                assert_eq!(
+                    count(locale.text(message)),
+                    count(Locale::English.text(message)),
+                    "{locale:?} {message:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn locales_are_found_by_language() {
+        assert_eq!(Locale::by_name("de_AT.UTF-8"), Some(Locale::German));
+        assert_eq!(Locale::by_name("ru-RU"), Some(Locale::Russian));
+        assert_eq!(Locale::by_name("EN"), Some(Locale::English));
+        assert_eq!(Locale::by_name("C"), None);
+        assert_eq!(
+            Locale::German.format(Message::Filled, &[&3]),
+            "3 Zellen ausgefüllt"
+        );
+    }
+}
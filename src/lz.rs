@@ -0,0 +1,277 @@
+//! lz-string, the compression f-puzzles and SudokuPad use to fit a whole puzzle into a link.
+//!
+//! Only the base64 variant is implemented. The format works on UTF-16 code units, like JavaScript
+//! strings, so text is converted to them first and the output matches the JavaScript library.
+
+use std::collections::{HashMap, HashSet};
+
+const BASE64: &[u8; 65] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+
+pub fn compress_to_base64(text: &str) -> String {
+    let units: Vec<u16> = text.encode_utf16().collect();
+    let mut compressed = compress(&units, 6, |value| BASE64[value as usize] as char);
+    while compressed.len() % 4 != 0 {
+        compressed.push('=');
+    }
+    compressed
+}
+
+/// `None` if the text isn't base64, or the stream in it is broken. URL-safe characters (`-`, `$`)
+/// and spaces in place of `+` are accepted, since links get mangled on the way.
+pub fn decompress_from_base64(text: &str) -> Option<String> {
+    let values = text
+        .bytes()
+        .map(|byte| match byte {
+            b' ' => Some(62),
+            b'-' => Some(63),
+            b'$' => Some(64),
+            _ => BASE64
+                .iter()
+                .position(|&char| char == byte)
+                .map(|value| value as u8),
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    if values.is_empty() {
+        return None;
+    }
+    String::from_utf16(&decompress(&values, 32)?).ok()
+}
+
+struct Writer<F> {
+    output: String,
+    value: u32,
+    position: u32,
+    bits_per_char: u32,
+    char: F,
+}
+
+impl<F: Fn(u32) -> char> Writer<F> {
+    fn bit(&mut self, bit: u32) {
+        self.value = (self.value << 1) | bit;
+        if self.position == self.bits_per_char - 1 {
+            self.position = 0;
+            self.output.push((self.char)(self.value));
+            self.value = 0;
+        } else {
+            self.position += 1;
+        }
+    }
+
+    /// Lowest bit first
+    fn write(&mut self, mut value: u32, count: u32) {
+        for _ in 0..count {
+            self.bit(value & 1);
+            value >>= 1;
+        }
+    }
+
+    fn finish(mut self) -> String {
+        loop {
+            self.value <<= 1;
+            if self.position == self.bits_per_char - 1 {
+                self.output.push((self.char)(self.value));
+                return self.output;
+            }
+            self.position += 1;
+        }
+    }
+}
+
+struct Compressor<F> {
+    dictionary: HashMap<Vec<u16>, u32>,
+    /// Characters seen, but not written out yet
+    pending: HashSet<u16>,
+    enlarge_in: u32,
+    bits: u32,
+    writer: Writer<F>,
+}
+
+impl<F: Fn(u32) -> char> Compressor<F> {
+    fn enlarge(&mut self) {
+        self.enlarge_in -= 1;
+        if self.enlarge_in == 0 {
+            self.enlarge_in = 1 << self.bits;
+            self.bits += 1;
+        }
+    }
+
+    fn emit(&mut self, phrase: &[u16]) {
+        if let [char] = phrase
+            && self.pending.remove(char)
+        {
+            // New characters are written out in full, 8 bits when they fit, 16 otherwise
+            if *char < 256 {
+                self.writer.write(0, self.bits);
+                self.writer.write(*char as u32, 8);
+            } else {
+                self.writer.write(1, self.bits);
+                self.writer.write(*char as u32, 16);
+            }
+            self.enlarge();
+        } else {
+            self.writer.write(self.dictionary[phrase], self.bits);
+        }
+        self.enlarge();
+    }
+}
+
+fn compress(units: &[u16], bits_per_char: u32, char: impl Fn(u32) -> char) -> String {
+    let mut compressor = Compressor {
+        dictionary: HashMap::new(),
+        pending: HashSet::new(),
+        enlarge_in: 2,
+        bits: 2,
+        writer: Writer {
+            output: String::new(),
+            value: 0,
+            position: 0,
+            bits_per_char,
+            char,
+        },
+    };
+    // Codes 0, 1 and 2 mean an 8-bit character, a 16-bit character and the end of the stream
+    let mut size = 3;
+    let mut phrase = Vec::new();
+    for &unit in units {
+        if !compressor.dictionary.contains_key(&[unit][..]) {
+            compressor.dictionary.insert(vec![unit], size);
+            size += 1;
+            compressor.pending.insert(unit);
+        }
+        let mut longer = phrase.clone();
+        longer.push(unit);
+        if compressor.dictionary.contains_key(&longer) {
+            phrase = longer;
+        } else {
+            compressor.emit(&phrase);
+            compressor.dictionary.insert(longer, size);
+            size += 1;
+            phrase = vec![unit];
+        }
+    }
+    if !phrase.is_empty() {
+        compressor.emit(&phrase);
+    }
+    compressor.writer.write(2, compressor.bits);
+    compressor.writer.finish()
+}
+
+struct Reader<'a> {
+    values: &'a [u8],
+    /// Next value to read
+    index: usize,
+    value: u8,
+    position: u8,
+    reset: u8,
+}
+
+impl Reader<'_> {
+    /// Lowest bit first, the input is padded with zeroes
+    fn read(&mut self, count: u32) -> u32 {
+        let mut bits = 0;
+        for power in 0..count {
+            if self.value & self.position != 0 {
+                bits |= 1 << power;
+            }
+            self.position >>= 1;
+            if self.position == 0 {
+                self.position = self.reset;
+                self.value = self.values.get(self.index).copied().unwrap_or(0);
+                self.index += 1;
+            }
+        }
+        bits
+    }
+}
+
+fn decompress(values: &[u8], reset: u8) -> Option<Vec<u16>> {
+    let mut reader = Reader {
+        values,
+        index: 1,
+        value: values[0],
+        position: reset,
+        reset,
+    };
+    // Codes of the special values aren't looked up, but keep the indices in line
+    let mut dictionary: Vec<Vec<u16>> = vec![Vec::new(); 3];
+    let mut enlarge_in = 4;
+    let mut bits = 3;
+
+    let first = match reader.read(2) {
+        0 => reader.read(8),
+        1 => reader.read(16),
+        _ => return Some(Vec::new()),
+    } as u16;
+    dictionary.push(vec![first]);
+    let mut previous = vec![first];
+    let mut result = previous.clone();
+
+    loop {
+        if reader.index > values.len() {
+            return None;
+        }
+        let mut code = reader.read(bits) as usize;
+        match code {
+            0 | 1 => {
+                let char = reader.read(if code == 0 { 8 } else { 16 }) as u16;
+                dictionary.push(vec![char]);
+                code = dictionary.len() - 1;
+                enlarge_in -= 1;
+            }
+            2 => return Some(result),
+            _ => {}
+        }
+        if enlarge_in == 0 {
+            enlarge_in = 1 << bits;
+            bits += 1;
+        }
+
+        let entry = match dictionary.get(code) {
+            Some(entry) => entry.clone(),
+            // Phrase that is being defined right now
+            None if code == dictionary.len() => {
+                let mut entry = previous.clone();
+                entry.push(previous[0]);
+                entry
+            }
+            None => return None,
+        };
+        result.extend(&entry);
+        previous.push(entry[0]);
+        dictionary.push(previous);
+        enlarge_in -= 1;
+        previous = entry;
+        if enlarge_in == 0 {
+            enlarge_in = 1 << bits;
+            bits += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(compress_to_base64(""), "Q===");
+        for text in [
+            "a",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            r#"{"size":9,"grid":[[{"value":1,"given":true},{},{}]]}"#,
+            "Судоку, 数独 and 🧩",
+        ] {
+            let compressed = compress_to_base64(text);
+            assert_eq!(decompress_from_base64(&compressed).as_deref(), Some(text));
+        }
+        assert_eq!(decompress_from_base64("not base64!"), None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn any_text_round_trips(text in ".*") {
+            let compressed = compress_to_base64(&text);
+            proptest::prop_assert_eq!(decompress_from_base64(&compressed), Some(text));
+        }
+    }
+}
@@ -1,40 +1,959 @@
-use gui_experiment::SudokuModel;
-use tracing_subscriber::EnvFilter;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use gui_experiment::assets;
+use gui_experiment::batch::{self, Verdict};
+use gui_experiment::booklet::{self, BookletOptions};
+use gui_experiment::config::{Config, ConfigError};
+use gui_experiment::convert::Format;
+use gui_experiment::rpc::Rpc;
+use gui_experiment::session::SessionLog;
+use gui_experiment::{
+    Assist, Colour, Difficulty, Dlx, Locale, Options, Pack, Puzzle, SudokuModel, Theme,
+};
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, fmt};
+
+/// Where the puzzles to play come from
+enum Source {
+    /// Pack file, or the built-in one, progress of which is kept
+    Pack(Pack),
+    /// Single puzzle given on the command line, or a generated one
+    Puzzle(Box<Puzzle>),
+}
+
+impl Source {
+    fn puzzles(&self) -> &[Puzzle] {
+        match self {
+            Source::Pack(pack) => pack.puzzles(),
+            Source::Puzzle(puzzle) => std::slice::from_ref(puzzle),
+        }
+    }
+
+    /// Index of the puzzle to start with, the first unsolved one of a pack
+    fn first(&self) -> usize {
+        match self {
+            Source::Pack(pack) => pack.next_unsolved(None).unwrap_or(0),
+            Source::Puzzle(_) => 0,
+        }
+    }
+
+    fn model(&self, index: usize) -> SudokuModel {
+        match self {
+            Source::Pack(pack) => pack.model(index),
+            Source::Puzzle(puzzle) => puzzle.model,
+        }
+    }
+
+    /// Pack to play, a single puzzle is one without a file to keep progress in
+    #[cfg(any(
+        feature = "floem",
+        feature = "iced",
+        feature = "slint",
+        feature = "egui",
+        feature = "gpui",
+        feature = "xilem",
+        feature = "leptos",
+        feature = "rui",
+        feature = "kas"
+    ))]
+    fn into_pack(self) -> Pack {
+        match self {
+            Source::Pack(pack) => pack,
+            Source::Puzzle(puzzle) => Pack::from(vec![*puzzle]),
+        }
+    }
+}
 
 fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    let mut command = command();
+    let matches = command.get_matches_mut();
+    init_tracing(&matches);
+    let config = config(&matches);
+    if let Some(dir) = matches.get_one::<String>("assets") {
+        assets::set_dir(PathBuf::from(dir));
+    }
 
-    let sudoku_model = SudokuModel::example();
+    // Tools that work on puzzles without playing them, and without any frontend
+    if let Some((name, tool_matches)) = matches.subcommand() {
+        // Only the config applies to tools too, the rest is for playing
+        if let Some(id) = matches.ids().find(|id| {
+            *id != "config" && matches.value_source(id.as_str()) == Some(ValueSource::CommandLine)
+        }) {
+            command
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!("`{id}` can't be used with `{name}`"),
+                )
+                .exit();
+        }
+        run_tool(name, tool_matches, &config);
+        return;
+    }
 
-    #[cfg(feature = "floem")]
-    gui_experiment::floem::main(sudoku_model);
+    // Batch mode solves a whole puzzle file instead of opening the game
+    if let Some(path) = matches.get_one::<String>("batch") {
+        run_batch(path, &matches);
+        return;
+    }
 
-    #[cfg(feature = "iced")]
-    gui_experiment::iced::main(sudoku_model).unwrap();
+    let source = source(&mut command, &matches, &config);
 
-    #[cfg(feature = "slint")]
-    gui_experiment::slint::main(sudoku_model).unwrap();
+    // Booklet of the whole pack to print, instead of playing it
+    if let Some(output) = matches.get_one::<String>("booklet") {
+        run_booklet(source.puzzles(), output, &matches, &config);
+        return;
+    }
 
-    #[cfg(feature = "egui")]
-    gui_experiment::egui::main(sudoku_model).unwrap();
+    // Command line overrides the config
+    let mut options = config.options();
+    options.size = matches.get_one::<(f32, f32)>("size").copied();
+    options.scale = matches.get_one::<f32>("scale").copied().unwrap_or(1.0);
+    if let Some(cell_size) = matches.get_one::<f32>("cell-size") {
+        options.cell_size = Some(*cell_size);
+    }
+    if let Some(theme) = matches.get_one::<Theme>("theme") {
+        options.theme = *theme;
+    }
+    if let Some(locale) = matches.get_one::<Locale>("locale") {
+        options.locale = *locale;
+    }
+    if let Some(assist) = matches.get_one::<Assist>("assist") {
+        options.assist = *assist;
+    }
 
-    #[cfg(feature = "gpui")]
-    gui_experiment::gpui::main(sudoku_model);
+    let frontend = frontend(&mut command, &matches);
+    let rpc = rpc(&mut command, &matches, frontend);
+    play(frontend, source, options, rpc, matches.get_flag("samurai"));
+}
 
-    #[cfg(feature = "xilem")]
-    gui_experiment::xilem::main(sudoku_model).unwrap();
+/// Logs of `RUST_LOG` go to stderr, as stdout is played on with `plain` and `--rpc -`. Events of
+/// the game also go to the session log, if there's one.
+fn init_tracing(matches: &ArgMatches) {
+    let session_log = matches.get_one::<String>("session-log").map(|path| {
+        SessionLog::create(Path::new(path)).unwrap_or_else(|err| {
+            eprintln!("Failed to create session log {path}: {err}");
+            exit(1);
+        })
+    });
+    tracing_subscriber::registry()
+        .with(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(EnvFilter::from_default_env()),
+        )
+        .with(
+            session_log.map(|log| {
+                log.with_filter(Targets::new().with_target("gui_experiment", Level::DEBUG))
+            }),
+        )
+        .init();
+}
 
+/// Frontends that were compiled in. Without `--ui` the first one is played, so windowed ones go
+/// first, then the terminal, and the ones that aren't implemented yet. Plain text is last, it is
+/// always there, and needs neither a display nor a terminal.
+const FRONTENDS: &[&str] = &[
+    #[cfg(feature = "egui")]
+    "egui",
+    #[cfg(feature = "iced")]
+    "iced",
+    #[cfg(feature = "slint")]
+    "slint",
+    #[cfg(feature = "floem")]
+    "floem",
+    #[cfg(feature = "gpui")]
+    "gpui",
+    #[cfg(feature = "ratatui")]
+    TERMINAL,
+    #[cfg(feature = "kas")]
+    "kas",
+    #[cfg(feature = "xilem")]
+    "xilem",
     #[cfg(feature = "leptos")]
-    gui_experiment::leptos::main(sudoku_model).unwrap();
-
+    "leptos",
     #[cfg(feature = "rui")]
-    gui_experiment::rui::main(sudoku_model).unwrap();
+    "rui",
+    PLAIN,
+];
 
-    #[cfg(feature = "ratatui")]
-    gui_experiment::ratatui::main(sudoku_model).unwrap();
+/// Frontend that needs no display
+const TERMINAL: &str = "ratatui";
 
-    #[cfg(feature = "kas")]
-    gui_experiment::kas::main(sudoku_model).unwrap();
+/// Frontend that is played line by line, see [`gui_experiment::plain`]
+const PLAIN: &str = "plain";
+
+/// The one asked for with `--ui`, or the first one. The terminal, or plain text without it, is
+/// used instead of windows when there is no display, and the terminal for the samurai example,
+/// which only it can show.
+fn frontend(command: &mut Command, matches: &ArgMatches) -> &'static str {
+    let requested = matches
+        .get_one::<String>("ui")
+        .and_then(|name| FRONTENDS.iter().find(|frontend| **frontend == name));
+    let terminal = FRONTENDS.contains(&TERMINAL);
+    if matches.get_flag("samurai") {
+        if !terminal || requested.is_some_and(|&frontend| frontend != TERMINAL) {
+            command
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!("samurai can only be played with `--ui {TERMINAL}`"),
+                )
+                .exit();
+        }
+        return TERMINAL;
+    }
+    let frontend = requested.unwrap_or(&FRONTENDS[0]);
+    if ![TERMINAL, PLAIN].contains(frontend) && !has_display() {
+        if terminal {
+            eprintln!("There is no display to open a window on, playing in the terminal instead");
+            return TERMINAL;
+        }
+        eprintln!("There is no display to open a window on, playing line by line instead");
+        return PLAIN;
+    }
+    frontend
+}
+
+/// Frontends that poll for requests of `--rpc` in their event loops
+const AUTOMATED: &[&str] = &["egui", "iced", "slint", "floem", "gpui", TERMINAL];
+
+/// Requests of `--rpc`, on stdio for `-`, which the terminal draws on, so it needs a socket
+fn rpc(command: &mut Command, matches: &ArgMatches, frontend: &str) -> Option<Rpc> {
+    let endpoint = matches.get_one::<String>("rpc")?;
+    if !AUTOMATED.contains(&frontend) {
+        command
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!("`{frontend}` can't be driven with `--rpc`"),
+            )
+            .exit();
+    }
+    if endpoint == "-" {
+        if frontend == TERMINAL {
+            command
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!("`{TERMINAL}` draws on stdout, so `--rpc` needs a socket for it"),
+                )
+                .exit();
+        }
+        return Some(Rpc::stdio());
+    }
+    match Rpc::listen(Path::new(endpoint)) {
+        Ok(rpc) => Some(rpc),
+        Err(err) => {
+            eprintln!("Failed to listen on {endpoint}: {err}");
+            exit(1);
+        }
+    }
+}
+
+/// Whether windows can be opened, only X11 and Wayland sessions can be missing
+fn has_display() -> bool {
+    if !cfg!(unix) || cfg!(target_os = "macos") {
+        return true;
+    }
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .into_iter()
+        .any(|variable| std::env::var_os(variable).is_some_and(|value| !value.is_empty()))
+}
+
+fn play(frontend: &str, source: Source, options: Options, rpc: Option<Rpc>, samurai: bool) {
+    let puzzle = source.first();
+    let model = source.model(puzzle).with_assist(options.assist);
+    tracing::info!(frontend, puzzle = %model.givens_line(), samurai, "session");
+    // Only some frontends are driven, `rpc` refuses the others
+    #[cfg(not(any(
+        feature = "floem",
+        feature = "iced",
+        feature = "slint",
+        feature = "egui",
+        feature = "gpui",
+        feature = "ratatui"
+    )))]
+    let _ = rpc;
+    match frontend {
+        #[cfg(feature = "floem")]
+        "floem" => gui_experiment::floem::main(source.into_pack(), puzzle, options, rpc),
+        #[cfg(feature = "iced")]
+        "iced" => report(gui_experiment::iced::main(
+            source.into_pack(),
+            puzzle,
+            options,
+            rpc,
+        )),
+        #[cfg(feature = "slint")]
+        "slint" => report(gui_experiment::slint::main(
+            source.into_pack(),
+            puzzle,
+            options,
+            rpc,
+        )),
+        #[cfg(feature = "egui")]
+        "egui" => report(gui_experiment::egui::main(
+            source.into_pack(),
+            puzzle,
+            options,
+            rpc,
+        )),
+        #[cfg(feature = "gpui")]
+        "gpui" => gui_experiment::gpui::main(source.into_pack(), puzzle, options, rpc),
+        #[cfg(feature = "xilem")]
+        "xilem" => report(gui_experiment::xilem::main(
+            source.into_pack(),
+            puzzle,
+            options,
+        )),
+        #[cfg(feature = "leptos")]
+        "leptos" => report(gui_experiment::leptos::main(
+            source.into_pack(),
+            puzzle,
+            options,
+        )),
+        #[cfg(feature = "rui")]
+        "rui" => report(gui_experiment::rui::main(
+            source.into_pack(),
+            puzzle,
+            options,
+        )),
+        // Only the terminal plays samurai, `frontend` picks it for it
+        #[cfg(feature = "ratatui")]
+        TERMINAL if samurai => {
+            let samurai_model = gui_experiment::SamuraiModel::example();
+            report(gui_experiment::ratatui::main_samurai(
+                samurai_model,
+                options,
+            ))
+        }
+        #[cfg(feature = "ratatui")]
+        TERMINAL => report(match source {
+            Source::Pack(pack) => gui_experiment::ratatui::main_pack(pack, puzzle, options, rpc),
+            Source::Puzzle(_) => gui_experiment::ratatui::main(model, options, rpc),
+        }),
+        #[cfg(feature = "kas")]
+        "kas" => report(gui_experiment::kas::main(
+            source.into_pack(),
+            puzzle,
+            options,
+        )),
+        PLAIN => report(match source {
+            Source::Pack(pack) => gui_experiment::plain::main_pack(pack, puzzle, options),
+            Source::Puzzle(_) => gui_experiment::plain::main(model, options),
+        }),
+        _ => unreachable!("only frontends that were compiled in can be picked"),
+    }
+}
+
+/// Arguments are checked by clap, which exits with `2` when they are wrong. Failures after that,
+/// like a missing file, exit with `1`.
+fn command() -> Command {
+    let puzzle_source = ["puzzle", "generate", "seed", "daily"];
+    Command::new("gui-experiment")
+        .about("Sudoku, in any of the frontends that were compiled in")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .global(true)
+                .help(format!(
+                    "Config of themes, keys and defaults [default: {}]",
+                    Config::path().map_or("none".into(), |path| path.display().to_string())
+                )),
+        )
+        .arg(
+            Arg::new("assets")
+                .long("assets")
+                .value_name("DIR")
+                .help(format!(
+                    "Files that replace the icon, the default pack or the font [default: {}]",
+                    assets::default_dir().map_or("none".into(), |path| path.display().to_string())
+                )),
+        )
+        .arg(
+            Arg::new("ui")
+                .long("ui")
+                .value_name("FRONTEND")
+                .value_parser(PossibleValuesParser::new(FRONTENDS))
+                .help("Frontend to play in [default: the first one, or the terminal without a display]"),
+        )
+        .arg(
+            Arg::new("puzzle")
+                .value_name("PUZZLE")
+                .help("Pack file, or a puzzle of 81 digits row by row, with `.` or `0` for empty cells [default: built-in pack]"),
+        )
+        .arg(
+            Arg::new("generate")
+                .long("generate")
+                .value_name("DIFFICULTY")
+                .value_parser(difficulties())
+                .ignore_case(true)
+                .conflicts_with("puzzle")
+                .help("Play a new puzzle of this difficulty [default: from the config, or medium]"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .value_parser(value_parser!(u64))
+                .conflicts_with("puzzle")
+                .help("Generate the puzzle from this seed, the same seed gives the same puzzle"),
+        )
+        .arg(
+            Arg::new("daily")
+                .long("daily")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["puzzle", "seed"])
+                .help("Generate the puzzle of the day, the same for everybody on a day (UTC)"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .value_name("WIDTHxHEIGHT")
+                .value_parser(parse_size)
+                .help("Window size in logical pixels"),
+        )
+        .arg(
+            Arg::new("scale")
+                .long("scale")
+                .value_name("FACTOR")
+                .value_parser(parse_scale)
+                .help("Zoom of the window and its contents"),
+        )
+        .arg(
+            Arg::new("cell-size")
+                .long("cell-size")
+                .value_name("PIXELS")
+                .value_parser(parse_scale)
+                .help("Size of a cell in logical pixels, the rest of the board is zoomed along"),
+        )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .value_name("THEME")
+                .value_parser(
+                    PossibleValuesParser::new(Theme::ALL.map(|theme| theme.name()))
+                        .map(|name| Theme::by_name(&name).expect("only names are possible")),
+                )
+                .ignore_case(true)
+                .help("Colours of the board, the same in every frontend [default: from the config, or dark]"),
+        )
+        .arg(
+            Arg::new("locale")
+                .long("locale")
+                .value_name("LOCALE")
+                .value_parser(
+                    PossibleValuesParser::new(Locale::ALL.map(|locale| locale.name()))
+                        .map(|name| Locale::by_name(&name).expect("only names are possible")),
+                )
+                .ignore_case(true)
+                .help("Language of the game [default: from the config, or of LC_ALL, LC_MESSAGES or LANG]"),
+        )
+        .arg(
+            Arg::new("assist")
+                .long("assist")
+                .value_name("LEVEL")
+                .value_parser(
+                    PossibleValuesParser::new(Assist::ALL.map(|assist| assist.name()))
+                        .map(|name| Assist::by_name(&name).expect("only names are possible")),
+                )
+                .ignore_case(true)
+                .help("Whether conflicts are shown, and hints and filling in of singles allowed [default: from the config, or full]"),
+        )
+        .arg(
+            Arg::new("samurai")
+                .long("samurai")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(puzzle_source)
+                .help("Play the samurai example, in the terminal"),
+        )
+        .arg(
+            Arg::new("rpc")
+                .long("rpc")
+                .value_name("ENDPOINT")
+                .conflicts_with("samurai")
+                .help("Take JSON-RPC requests that drive the game, on stdio for `-`, or on a Unix socket at this path"),
+        )
+        .arg(
+            Arg::new("session-log")
+                .long("session-log")
+                .value_name("FILE")
+                .help("Write every edit, with the input it came from, to this file as JSON lines"),
+        )
+        .arg(
+            Arg::new("batch")
+                .long("batch")
+                .value_name("PUZZLES")
+                .conflicts_with_all(puzzle_source)
+                .conflicts_with_all(["booklet", "samurai", "rpc"])
+                .help("Solve and rate every puzzle of a file, one per line, instead of playing"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("RESULTS")
+                .requires("batch")
+                .help("File for the results of batch solving [default: stdout]"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("COUNT")
+                .value_parser(value_parser!(NonZeroUsize))
+                .requires("batch")
+                .help("Threads for batch solving [default: all cores]"),
+        )
+        .arg(
+            Arg::new("booklet")
+                .long("booklet")
+                .value_name("OUTPUT")
+                .conflicts_with_all(["samurai", "rpc"])
+                .help("Write the puzzles as a booklet to print, a PDF, or SVG pages for `.svg`"),
+        )
+        .arg(
+            Arg::new("per-page")
+                .long("per-page")
+                .value_name("COUNT")
+                .value_parser(value_parser!(NonZeroUsize))
+                .requires("booklet")
+                .help("Puzzles on a booklet page [default: 4]"),
+        )
+        .arg(
+            Arg::new("no-solutions")
+                .long("no-solutions")
+                .action(ArgAction::SetTrue)
+                .requires("booklet")
+                .help("Leave solutions out of the booklet"),
+        )
+        .subcommand(
+            Command::new("solve")
+                .about("Print the solution of every puzzle, as a line of 81 digits")
+                .arg(input())
+                .arg(from()),
+        )
+        .subcommand(
+            Command::new("generate")
+                .about("Print new puzzles with a single solution")
+                .arg(
+                    Arg::new("difficulty")
+                        .long("difficulty")
+                        .value_name("DIFFICULTY")
+                        .value_parser(difficulties())
+                        .ignore_case(true)
+                        .help("Difficulty of the puzzles, by how they are graded [default: from the config, or medium]"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .long("count")
+                        .value_name("COUNT")
+                        .value_parser(value_parser!(NonZeroUsize))
+                        .default_value("1")
+                        .help("How many puzzles to print"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .value_parser(value_parser!(u64))
+                        .help("Seed of the first puzzle, the next ones count up from it [default: random]"),
+                )
+                .arg(to().default_value(Format::Line.name()))
+                .arg(output()),
+        )
+        .subcommand(
+            Command::new("grade")
+                .about("Print the difficulty and rating of every puzzle, tab separated")
+                .arg(input())
+                .arg(from()),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Write puzzles in another format")
+                .arg(input())
+                .arg(from())
+                .arg(to().required(true))
+                .arg(output()),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Check that every puzzle has givens that don't conflict and a single solution")
+                .arg(input())
+                .arg(from()),
+        )
+}
+
+fn difficulties() -> impl TypedValueParser<Value = Difficulty> {
+    PossibleValuesParser::new(Difficulty::ALL.map(|difficulty| difficulty.name()))
+        .map(|name| Difficulty::by_name(&name).expect("only names are possible"))
+}
+
+fn formats(formats: impl Iterator<Item = Format>) -> impl TypedValueParser<Value = Format> {
+    PossibleValuesParser::new(formats.map(|format| format.name()))
+        .map(|name| Format::by_name(&name).expect("only names are possible"))
+}
+
+/// Puzzles to read by subcommands
+fn input() -> Arg {
+    Arg::new("input")
+        .value_name("PUZZLES")
+        .default_value("-")
+        .help("File, `-` for stdin, or a puzzle of 81 digits row by row")
+}
+
+fn from() -> Arg {
+    Arg::new("from")
+        .long("from")
+        .value_name("FORMAT")
+        .value_parser(formats(Format::ALL.into_iter().filter(Format::is_readable)))
+        .ignore_case(true)
+        .help("Format of the puzzles [default: by the extension and contents]")
+}
+
+fn to() -> Arg {
+    Arg::new("to")
+        .long("to")
+        .value_name("FORMAT")
+        .value_parser(formats(Format::ALL.into_iter()))
+        .ignore_case(true)
+        .help("Format to write the puzzles in")
+}
+
+fn output() -> Arg {
+    Arg::new("output")
+        .long("output")
+        .value_name("FILE")
+        .help("File to write to [default: stdout]")
+}
+
+/// `WIDTHxHEIGHT`, e.g. `800x600`
+fn parse_size(size: &str) -> Result<(f32, f32), String> {
+    let (width, height) = size
+        .split_once(['x', 'X'])
+        .ok_or("expected WIDTHxHEIGHT, like 800x600")?;
+    let parse = |length: &str| match length.trim().parse::<f32>() {
+        Ok(length) if length.is_finite() && length > 0.0 => Ok(length),
+        _ => Err(format!("{length} isn't a positive number of pixels")),
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+fn parse_scale(scale: &str) -> Result<f32, String> {
+    match scale.parse::<f32>() {
+        Ok(scale) if scale.is_finite() && scale > 0.0 => Ok(scale),
+        _ => Err(format!("{scale} isn't a positive number")),
+    }
+}
+
+fn source(command: &mut Command, matches: &ArgMatches, config: &Config) -> Source {
+    let daily = matches.get_flag("daily");
+    let difficulty = matches.get_one::<Difficulty>("generate").copied();
+    let seed = matches.get_one::<u64>("seed").copied();
+    if daily || difficulty.is_some() || seed.is_some() {
+        let seed = seed.unwrap_or_else(|| if daily { daily_seed() } else { random_seed() });
+        let difficulty = difficulty.unwrap_or(config.difficulty);
+        let model = SudokuModel::generate_graded(difficulty, seed);
+        let mut puzzle = Puzzle::new(format!("Seed {seed}"), model);
+        puzzle.difficulty = model.grade().map(|grade| grade.difficulty());
+        eprintln!(
+            "{}, {}",
+            puzzle.title,
+            puzzle.difficulty.unwrap_or(difficulty).name()
+        );
+        return Source::Puzzle(Box::new(puzzle));
+    }
+
+    let Some(argument) = matches.get_one::<String>("puzzle") else {
+        return Source::Pack(assets::pack());
+    };
+    // Puzzle itself is only tried when there's no such file
+    if argument.chars().count() == 81 && !Path::new(argument).exists() {
+        return match SudokuModel::from_line(argument) {
+            Ok(model) => Source::Puzzle(Box::new(Puzzle::new("Command line", model))),
+            Err(err) => command
+                .error(ErrorKind::InvalidValue, format!("invalid puzzle: {err}"))
+                .exit(),
+        };
+    }
+    Source::Pack(Pack::open(argument).unwrap_or_else(|err| {
+        eprintln!("Failed to open {argument}: {err}");
+        exit(1);
+    }))
+}
+
+/// Config of `--config`, which has to be there, or of the config directory, which may be missing
+fn config(matches: &ArgMatches) -> Config {
+    let (path, config) = match matches.get_one::<String>("config") {
+        Some(path) => (
+            PathBuf::from(path),
+            std::fs::read_to_string(path)
+                .map_err(ConfigError::from)
+                .and_then(|text| Config::parse(&text)),
+        ),
+        None => match Config::path() {
+            Some(path) => {
+                let config = Config::load(&path);
+                (path, config)
+            }
+            None => return Config::default(),
+        },
+    };
+    config.unwrap_or_else(|err| {
+        eprintln!("Failed to read config {}: {err}", path.display());
+        exit(1);
+    })
+}
+
+/// Days since the epoch, so that everybody gets the same puzzle on the same day
+fn daily_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / (24 * 60 * 60))
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64)
+}
+
+/// Exits with `1` on errors, like ones of frontends that failed to start
+fn report<E: Display>(result: Result<(), E>) {
+    if let Err(err) = result {
+        eprintln!("{err}");
+        exit(1);
+    }
+}
+
+/// `--batch <puzzles> [--output <results>] [--threads <count>]`, results go to stdout by default
+/// and the summary to stderr
+fn run_batch(path: &str, matches: &ArgMatches) {
+    let input = File::open(path).unwrap_or_else(|err| {
+        eprintln!("Failed to open {path}: {err}");
+        exit(1);
+    });
+    let threads = matches.get_one::<NonZeroUsize>("threads").copied();
+    let input = BufReader::new(input);
+    let summary = match matches.get_one::<String>("output") {
+        Some(output) => {
+            let file = File::create(output).unwrap_or_else(|err| {
+                eprintln!("Failed to create {output}: {err}");
+                exit(1);
+            });
+            batch::solve_to(input, BufWriter::new(file), threads)
+        }
+        None => batch::solve_to(input, std::io::stdout().lock(), threads),
+    };
+    match summary {
+        Ok(summary) => eprintln!("{summary}"),
+        Err(err) => {
+            eprintln!("Failed to solve {path}: {err}");
+            exit(1);
+        }
+    }
+}
+
+/// `--booklet <output.pdf|output.svg> [--per-page <count>] [--no-solutions]`, in the light theme
+/// unless another one is chosen
+fn run_booklet(puzzles: &[Puzzle], output: &str, matches: &ArgMatches, config: &Config) {
+    let theme = matches.get_one::<Theme>("theme").copied().or(config.theme);
+    let mut options = BookletOptions {
+        solutions: !matches.get_flag("no-solutions"),
+        palette: theme.unwrap_or(Theme::Light).palette(),
+        ..BookletOptions::default()
+    };
+    if let Some(per_page) = matches.get_one::<NonZeroUsize>("per-page") {
+        options.per_page = per_page.get();
+    }
+    match booklet::save(puzzles, &options, output) {
+        Ok(files) => {
+            for file in files {
+                eprintln!("{}", file.display());
+            }
+        }
+        Err(err) => {
+            eprintln!("Failed to write {output}: {err}");
+            exit(1);
+        }
+    }
+}
+
+fn run_tool(name: &str, matches: &ArgMatches, config: &Config) {
+    match name {
+        "solve" => solve(&read_puzzles(matches)),
+        "generate" => generate(matches, config),
+        "grade" => grade(&read_puzzles(matches)),
+        "convert" => {
+            let format = *matches.get_one::<Format>("to").expect("required");
+            write_puzzles(matches, format, &read_puzzles(matches));
+        }
+        "check" => check(&read_puzzles(matches)),
+        _ => unreachable!("every subcommand is handled"),
+    }
+}
+
+/// `--from` format, or the guessed one, of the input, which can also be a single puzzle
+fn read_puzzles(matches: &ArgMatches) -> Vec<Puzzle> {
+    let input = matches.get_one::<String>("input").expect("has a default");
+    let (path, text) = if input == "-" {
+        let mut text = String::new();
+        if let Err(err) = std::io::stdin().read_to_string(&mut text) {
+            eprintln!("Failed to read stdin: {err}");
+            exit(1);
+        }
+        (None, text)
+    } else if input.chars().count() == 81 && !Path::new(input).exists() {
+        (None, input.clone())
+    } else {
+        match std::fs::read_to_string(input) {
+            Ok(text) => (Some(Path::new(input)), text),
+            Err(err) => {
+                eprintln!("Failed to read {input}: {err}");
+                exit(1);
+            }
+        }
+    };
+    let format = matches
+        .get_one::<Format>("from")
+        .copied()
+        .unwrap_or_else(|| Format::guess(path, &text));
+    format.read(&text).unwrap_or_else(|err| {
+        eprintln!("Failed to read {input} as {format}: {err}");
+        exit(1);
+    })
+}
+
+fn write_puzzles(matches: &ArgMatches, format: Format, puzzles: &[Puzzle]) {
+    let bytes = format.write(puzzles).unwrap_or_else(|err| {
+        eprintln!("Failed to write {format}: {err}");
+        exit(1);
+    });
+    write_output(matches.get_one::<String>("output"), &bytes);
+}
+
+/// To the file, or to stdout, which may be closed early, e.g. by `head`
+fn write_output(output: Option<&String>, bytes: &[u8]) {
+    let written = match output {
+        Some(output) => std::fs::write(output, bytes),
+        None => std::io::stdout().lock().write_all(bytes),
+    };
+    if let Err(err) = written {
+        eprintln!(
+            "Failed to write {}: {err}",
+            output.map_or("stdout", String::as_str)
+        );
+        exit(1);
+    }
+}
+
+/// Puzzles without a solution are reported and make it exit with `1`, for ones with several
+/// solutions the first one is printed
+fn solve(puzzles: &[Puzzle]) {
+    let mut solver = Dlx::new();
+    let mut failed = false;
+    let mut solutions = String::new();
+    for puzzle in puzzles {
+        match Verdict::of_model(&puzzle.model, &mut solver) {
+            Verdict::Unique(solution, _) => solutions += &(solution.line() + "\n"),
+            Verdict::Multiple(solution) => {
+                eprintln!(
+                    "{}: more than one solution, this is one of them",
+                    puzzle.title
+                );
+                solutions += &(solution.line() + "\n");
+            }
+            _ => {
+                eprintln!("{}: no solution", puzzle.title);
+                failed = true;
+            }
+        }
+    }
+    write_output(None, solutions.as_bytes());
+    if failed {
+        exit(1);
+    }
+}
+
+fn generate(matches: &ArgMatches, config: &Config) {
+    let difficulty = matches
+        .get_one::<Difficulty>("difficulty")
+        .copied()
+        .unwrap_or(config.difficulty);
+    let count = matches
+        .get_one::<NonZeroUsize>("count")
+        .expect("has a default");
+    let seed = matches
+        .get_one::<u64>("seed")
+        .copied()
+        .unwrap_or_else(random_seed);
+    let puzzles: Vec<Puzzle> = (0..count.get() as u64)
+        .map(|index| {
+            let seed = seed.wrapping_add(index);
+            let model = SudokuModel::generate_graded(difficulty, seed);
+            let mut puzzle = Puzzle::new(format!("Seed {seed}"), model);
+            let grade = model.grade();
+            puzzle.difficulty = grade.map(|grade| grade.difficulty());
+            puzzle.rating = grade.map(|grade| grade.rating());
+            puzzle
+        })
+        .collect();
+    let format = *matches.get_one::<Format>("to").expect("has a default");
+    write_puzzles(matches, format, &puzzles);
+}
+
+/// Tab separated: title, then the difficulty and rating, or why there are none
+fn grade(puzzles: &[Puzzle]) {
+    let mut solver = Dlx::new();
+    let mut grades = String::new();
+    for puzzle in puzzles {
+        grades += &match Verdict::of_model(&puzzle.model, &mut solver) {
+            Verdict::Unique(_, grade) => format!(
+                "{}\t{}\t{:.2}\n",
+                puzzle.title,
+                grade.difficulty().name(),
+                grade.rating()
+            ),
+            verdict => format!("{}\t{}\n", puzzle.title, verdict.name()),
+        };
+    }
+    write_output(None, grades.as_bytes());
+}
+
+/// Exits with `1` when any of the puzzles isn't valid
+fn check(puzzles: &[Puzzle]) {
+    let mut solver = Dlx::new();
+    let mut failed = false;
+    let mut results = String::new();
+    for puzzle in puzzles {
+        let model = &puzzle.model;
+        let conflict = (0..81)
+            .map(|index| (index % 9, index / 9))
+            .find(|&(x, y)| matches!(model.colour(x, y), Colour::Red));
+        let problem = match (conflict, Verdict::of_model(model, &mut solver)) {
+            (Some((x, y)), _) => format!("conflict in row {}, column {}", y + 1, x + 1),
+            (None, Verdict::Unique(_, grade)) => {
+                let givens = model.stats().given;
+                let difficulty = grade.difficulty().name();
+                results += &format!("{}: ok, {givens} givens, {difficulty}\n", puzzle.title);
+                continue;
+            }
+            (None, Verdict::Multiple(_)) => "more than one solution".to_string(),
+            (None, _) => "no solution".to_string(),
+        };
+        results += &format!("{}: {problem}\n", puzzle.title);
+        failed = true;
+    }
+    write_output(None, results.as_bytes());
+    if failed {
+        exit(1);
+    }
 }
@@ -0,0 +1,182 @@
+//! Settings the game is started with, read from the config file and the command line, and passed
+//! to the frontend.
+
+use crate::{Assist, Locale, Theme};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    /// Window size in logical pixels, every frontend has its own default
+    pub size: Option<(f32, f32)>,
+    /// Zoom of the contents, and of the default window size, `1.0` is the frontend's own look
+    pub scale: f32,
+    /// Cell size in logical pixels, every frontend has its own default, and zooms the rest along
+    pub cell_size: Option<f32>,
+    pub theme: Theme,
+    /// Language of the texts of the frontend
+    pub locale: Locale,
+    pub keys: Keys,
+    pub assist: Assist,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            size: None,
+            scale: 1.0,
+            cell_size: None,
+            theme: Theme::default(),
+            locale: Locale::default(),
+            keys: Keys::default(),
+            assist: Assist::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Zoom of a frontend, that lays its cells out at the given size
+    pub fn zoom(&self, cell_size: f32) -> f32 {
+        self.scale * self.cell_size.map_or(1.0, |size| size / cell_size)
+    }
+
+    /// Size that was asked for, or the frontend's default one zoomed along with its cells
+    pub fn window_size(&self, width: f32, height: f32, cell_size: f32) -> (f32, f32) {
+        let zoom = self.zoom(cell_size);
+        self.size.unwrap_or((width * zoom, height * zoom))
+    }
+}
+
+/// What a key does in frontends that are played with the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Down,
+    Up,
+    Right,
+    Hint,
+    Fill,
+    Colour,
+    ClearColour,
+    Undo,
+    Next,
+    Zoom,
+    Debug,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Left,
+        Action::Down,
+        Action::Up,
+        Action::Right,
+        Action::Hint,
+        Action::Fill,
+        Action::Colour,
+        Action::ClearColour,
+        Action::Undo,
+        Action::Next,
+        Action::Zoom,
+        Action::Debug,
+        Action::Quit,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Left => "left",
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Right => "right",
+            Action::Hint => "hint",
+            Action::Fill => "fill",
+            Action::Colour => "colour",
+            Action::ClearColour => "clear-colour",
+            Action::Undo => "undo",
+            Action::Next => "next",
+            Action::Zoom => "zoom",
+            Action::Debug => "debug",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Action> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Key of every action. Digits, `+`, `-` and their neighbours `=` and `_` always set values, so
+/// they can't be bound, and neither can arrows, Esc and Backspace, which always work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keys([char; Action::ALL.len()]);
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self([
+            'h', 'j', 'k', 'l', '?', 'f', 'c', 'C', 'u', 'n', 'z', 'd', 'q',
+        ])
+    }
+}
+
+impl Keys {
+    pub fn key(&self, action: Action) -> char {
+        self.0[action as usize]
+    }
+
+    /// Action bound to the key, if any
+    pub fn action(&self, key: char) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.key(*action) == key)
+    }
+
+    /// Binds the key to the action, the key that was bound to it before does nothing then. Fails
+    /// with the action that the key is already bound to, or without one for keys that set values.
+    pub fn bind(&mut self, action: Action, key: char) -> Result<(), Option<Action>> {
+        if key.is_ascii_digit() || "+-=_".contains(key) || key.is_control() {
+            return Err(None);
+        }
+        match self.action(key) {
+            Some(bound) if bound != action => Err(Some(bound)),
+            _ => {
+                self.0[action as usize] = key;
+                Ok(())
+            }
+        }
+    }
+
+    /// Frees the key of the action, so that it can be bound to another one before this one is
+    /// bound again
+    #[cfg(feature = "std")]
+    pub(crate) fn unbind(&mut self, action: Action) {
+        self.0[action as usize] = '\0';
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_can_be_rebound() {
+        let mut keys = Keys::default();
+        assert_eq!(keys.action('k'), Some(Action::Up));
+        assert_eq!(keys.bind(Action::Up, 'w'), Ok(()));
+        assert_eq!(keys.action('w'), Some(Action::Up));
+        assert_eq!(keys.action('k'), None);
+        assert_eq!(keys.bind(Action::Down, 'w'), Err(Some(Action::Up)));
+        assert_eq!(keys.bind(Action::Down, '5'), Err(None));
+        assert_eq!(keys.key(Action::Down), 'j');
+    }
+
+    #[test]
+    fn cells_zoom_the_window() {
+        let options = Options {
+            cell_size: Some(100.0),
+            scale: 1.5,
+            ..Options::default()
+        };
+        assert_eq!(options.zoom(50.0), 3.0);
+        assert_eq!(options.window_size(500.0, 400.0, 50.0), (1500.0, 1200.0));
+    }
+}
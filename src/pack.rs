@@ -0,0 +1,719 @@
+//! Puzzle packs: many puzzles in one text file, with metadata and completion state.
+//!
+//! Every puzzle is a single line of 81 cells, row by row, with `.` or `0` for an empty cell. Lines
+//! like `# key: value` right before it describe the puzzle, other comments and blank lines are
+//! ignored:
+//!
+//! ```text
+//! # title: Jigsaw
+//! # difficulty: medium
+//! # tags: jigsaw, beginner
+//! # regions: 001111122000111222...
+//! # constraints: diagonal-, diagonal+
+//! ...4.2...........8....8...3..3...5...9..21..74.....9.....7.........3....82761....
+//! ```
+//!
+//! Completion state is kept in a separate file next to the pack (`<pack>.progress`), so that the
+//! pack itself can be shared or updated. Puzzles are matched by their givens, not by position.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{Difficulty, Origin, RegionError, Regions, SudokuModel, SudokuValue, constraints};
+
+pub(crate) const DEFAULT_PACK: &str = include_str!("../puzzles/default.txt");
+
+/// Problem with a single line of 81 cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineError {
+    /// Line doesn't have exactly 81 cells
+    Length(usize),
+    /// Cell is neither a digit nor `.`
+    Cell { index: usize, char: char },
+}
+
+impl Display for LineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineError::Length(length) => write!(f, "expected 81 cells, got {length}"),
+            LineError::Cell { index, char } => {
+                write!(f, "unexpected '{char}' in cell {}", index + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LineError {}
+
+#[derive(Debug)]
+pub enum PackError {
+    Io(io::Error),
+    /// Puzzle or regions line is malformed, lines are counted from 1
+    Line {
+        line: usize,
+        error: LineError,
+    },
+    Regions {
+        line: usize,
+        error: RegionError,
+    },
+    Difficulty {
+        line: usize,
+        value: String,
+    },
+    Rating {
+        line: usize,
+        value: String,
+    },
+    /// Constraint is unknown, or there are too many of them
+    Constraint {
+        line: usize,
+        name: String,
+    },
+    /// There's not a single puzzle, only headers and comments, if anything
+    Empty,
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::Io(error) => write!(f, "{error}"),
+            PackError::Line { line, error } => write!(f, "line {line}: {error}"),
+            PackError::Regions { line, error } => write!(f, "line {line}: {error}"),
+            PackError::Difficulty { line, value } => {
+                write!(f, "line {line}: unknown difficulty '{value}'")
+            }
+            PackError::Rating { line, value } => write!(f, "line {line}: invalid rating '{value}'"),
+            PackError::Constraint { line, name } => {
+                write!(f, "line {line}: unsupported constraint '{name}'")
+            }
+            PackError::Empty => write!(f, "no puzzles"),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl From<io::Error> for PackError {
+    fn from(error: io::Error) -> Self {
+        PackError::Io(error)
+    }
+}
+
+/// Reads 81 cells row by row, `.` and `0` are empty cells. Digits become givens.
+fn parse_line(line: &str) -> Result<[[u8; 9]; 9], LineError> {
+    let length = line.chars().count();
+    if length != 81 {
+        return Err(LineError::Length(length));
+    }
+    let mut cells = [[0; 9]; 9];
+    for (index, char) in line.chars().enumerate() {
+        cells[index % 9][index / 9] = match char {
+            '.' => 0,
+            '0'..='9' => char as u8 - b'0',
+            _ => return Err(LineError::Cell { index, char }),
+        };
+    }
+    Ok(cells)
+}
+
+impl SudokuModel {
+    /// Puzzle from 81 cells row by row, like the ones in packs
+    pub fn from_line(line: &str) -> Result<SudokuModel, LineError> {
+        parse_line(line).map(SudokuModel::from)
+    }
+
+    /// All values row by row, `.` for empty cells
+    pub fn line(&self) -> String {
+        self.line_where(|_| true)
+    }
+
+    /// Only given values row by row, the rest is `.`
+    pub fn givens_line(&self) -> String {
+        self.line_where(|value| !value.enabled())
+    }
+
+    /// Only values entered by the player row by row, the rest is `.`
+    pub fn entries_line(&self) -> String {
+        self.line_where(SudokuValue::enabled)
+    }
+
+    /// Palette indices of colour annotations row by row, `.` for cells without one
+    pub fn annotations_line(&self) -> String {
+        (0..81)
+            .map(|index| match self.annotation(index % 9, index / 9) {
+                Some(colour) => char::from(b'0' + colour),
+                None => '.',
+            })
+            .collect()
+    }
+
+    /// Origins of values entered by the player row by row, see [`origin_char`], `.` for the rest
+    pub fn origins_line(&self) -> String {
+        (0..81)
+            .map(|index| {
+                let value = self.get(index % 9, index / 9);
+                match value.value {
+                    1..=9 if value.enabled() => origin_char(value.origin),
+                    _ => '.',
+                }
+            })
+            .collect()
+    }
+
+    fn line_where(&self, keep: impl Fn(&SudokuValue) -> bool) -> String {
+        (0..81)
+            .map(|index| {
+                let value = self.get(index % 9, index / 9);
+                match value.value {
+                    1..=9 if keep(value) => char::from(b'0' + value.value),
+                    _ => '.',
+                }
+            })
+            .collect()
+    }
+}
+
+/// Letter of the origin in progress files, `p` for [`Origin::Player`], `h` for [`Origin::Hint`],
+/// `a` for [`Origin::AutoFill`] and `i` for [`Origin::Imported`]
+fn origin_char(origin: Origin) -> char {
+    match origin {
+        Origin::Given => 'g',
+        Origin::Player => 'p',
+        Origin::Hint => 'h',
+        Origin::AutoFill => 'a',
+        Origin::Imported => 'i',
+    }
+}
+
+fn origin_of(char: char) -> Option<Origin> {
+    match char {
+        'p' => Some(Origin::Player),
+        'h' => Some(Origin::Hint),
+        'a' => Some(Origin::AutoFill),
+        'i' => Some(Origin::Imported),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub title: String,
+    pub author: Option<String>,
+    pub source: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub rating: Option<f32>,
+    pub tags: Vec<String>,
+    /// Givens, regions and constraints of the puzzle
+    pub model: SudokuModel,
+}
+
+impl Puzzle {
+    pub fn new(title: impl Into<String>, model: SudokuModel) -> Self {
+        Self {
+            title: title.into(),
+            author: None,
+            source: None,
+            difficulty: None,
+            rating: None,
+            tags: Vec::new(),
+            model,
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own.eq_ignore_ascii_case(tag))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Completion {
+    Unsolved,
+    /// Player's entries, colour annotations and origins of the entries so far, row by row like
+    /// [`SudokuModel::entries_line`], [`SudokuModel::annotations_line`] and
+    /// [`SudokuModel::origins_line`]
+    Started {
+        entries: String,
+        annotations: String,
+        origins: String,
+    },
+    Solved,
+}
+
+/// Criteria for [`Pack::filter`], unset fields match anything
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub difficulty: Option<Difficulty>,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    pub min_rating: Option<f32>,
+    /// Skip puzzles that are already solved
+    pub unsolved: bool,
+}
+
+impl Filter {
+    fn matches(&self, puzzle: &Puzzle, completion: &Completion) -> bool {
+        self.difficulty.is_none_or(|d| puzzle.difficulty == Some(d))
+            && self.tag.as_deref().is_none_or(|tag| puzzle.has_tag(tag))
+            && self.author.as_deref().is_none_or(|author| {
+                puzzle
+                    .author
+                    .as_deref()
+                    .is_some_and(|own| own.eq_ignore_ascii_case(author))
+            })
+            && self
+                .min_rating
+                .is_none_or(|min| puzzle.rating.is_some_and(|rating| rating >= min))
+            && !(self.unsolved && *completion == Completion::Solved)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Pack {
+    puzzles: Vec<Puzzle>,
+    /// Completion by givens line, only for puzzles that were started
+    progress: HashMap<String, Completion>,
+    /// Where the pack was read from, progress is saved next to it
+    path: Option<PathBuf>,
+}
+
+impl Pack {
+    /// Pack that comes with the crate, it has no file to save progress to
+    pub fn default_pack() -> Pack {
+        Self::parse(DEFAULT_PACK).expect("default pack is valid")
+    }
+
+    pub fn parse(text: &str) -> Result<Pack, PackError> {
+        let mut puzzles = Vec::new();
+        let mut next = Puzzle::new("", SudokuModel::new());
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some(comment) = line.strip_prefix('#') else {
+                let cells = parse_line(line).map_err(|error| PackError::Line {
+                    line: number,
+                    error,
+                })?;
+                let mut puzzle = std::mem::replace(&mut next, Puzzle::new("", SudokuModel::new()));
+                // Headers only set regions and constraints so far, givens come on top of them
+                let mut model = SudokuModel::from(cells).with_regions(*puzzle.model.regions());
+                for constraint in puzzle.model.constraints() {
+                    model.add_constraint(constraint);
+                }
+                puzzle.model = model;
+                if puzzle.title.is_empty() {
+                    puzzle.title = format!("Puzzle {}", puzzles.len() + 1);
+                }
+                puzzles.push(puzzle);
+                continue;
+            };
+            let Some((key, value)) = comment.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "title" => next.title = value.to_string(),
+                "author" => next.author = Some(value.to_string()),
+                "source" => next.source = Some(value.to_string()),
+                "difficulty" => {
+                    next.difficulty =
+                        Some(
+                            Difficulty::by_name(value).ok_or_else(|| PackError::Difficulty {
+                                line: number,
+                                value: value.to_string(),
+                            })?,
+                        )
+                }
+                "rating" => {
+                    next.rating = Some(value.parse().map_err(|_| PackError::Rating {
+                        line: number,
+                        value: value.to_string(),
+                    })?)
+                }
+                "tags" => {
+                    next.tags = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "regions" => {
+                    let map = parse_line(value).map_err(|error| PackError::Line {
+                        line: number,
+                        error,
+                    })?;
+                    let regions = Regions::new(map).map_err(|error| PackError::Regions {
+                        line: number,
+                        error,
+                    })?;
+                    next.model = next.model.with_regions(regions);
+                }
+                "constraints" => {
+                    for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                        let added = constraints::by_name(name)
+                            .is_some_and(|constraint| next.model.add_constraint(constraint));
+                        if !added {
+                            return Err(PackError::Constraint {
+                                line: number,
+                                name: name.to_string(),
+                            });
+                        }
+                    }
+                }
+                // Unknown keys are just comments, so that newer packs still open
+                _ => {}
+            }
+        }
+        if puzzles.is_empty() {
+            return Err(PackError::Empty);
+        }
+        Ok(Pack {
+            puzzles,
+            ..Pack::default()
+        })
+    }
+
+    /// Reads the pack, and its progress file if there is one
+    pub fn open(path: impl AsRef<Path>) -> Result<Pack, PackError> {
+        let path = path.as_ref();
+        let mut pack = Self::parse(&std::fs::read_to_string(path)?)?;
+        pack.path = Some(path.to_path_buf());
+        match std::fs::read_to_string(Self::progress_path(path)) {
+            Ok(progress) => pack.load_progress(&progress),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+        Ok(pack)
+    }
+
+    fn progress_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".progress");
+        PathBuf::from(name)
+    }
+
+    /// Progress file has a line per started puzzle: givens, then either `solved` or the entries
+    /// followed by colour annotations and origins of the entries. Malformed lines are skipped, losing progress is better than
+    /// not opening the pack.
+    fn load_progress(&mut self, text: &str) {
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(givens), Some(state)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            // Annotations and origins were added later, so they are optional
+            let annotations = fields.next().unwrap_or("").to_string();
+            let origins = fields.next().unwrap_or("").to_string();
+            let completion = match state {
+                "solved" => Completion::Solved,
+                entries if parse_line(entries).is_ok() => Completion::Started {
+                    entries: entries.to_string(),
+                    annotations,
+                    origins,
+                },
+                _ => continue,
+            };
+            self.progress.insert(givens.to_string(), completion);
+        }
+    }
+
+    /// Writes progress next to the pack file, does nothing for packs that weren't opened from a
+    /// file.
+    pub fn save_progress(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut lines: Vec<_> = self
+            .progress
+            .iter()
+            .map(|(givens, completion)| match completion {
+                Completion::Solved => format!("{givens} solved\n"),
+                Completion::Started {
+                    entries,
+                    annotations,
+                    origins,
+                } => format!("{givens} {entries} {annotations} {origins}\n"),
+                Completion::Unsolved => String::new(),
+            })
+            .collect();
+        lines.sort();
+        std::fs::write(Self::progress_path(path), lines.concat())
+    }
+
+    pub fn puzzles(&self) -> &[Puzzle] {
+        &self.puzzles
+    }
+
+    pub fn len(&self) -> usize {
+        self.puzzles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.puzzles.is_empty()
+    }
+
+    pub fn completion(&self, index: usize) -> &Completion {
+        self.progress
+            .get(&self.puzzles[index].model.givens_line())
+            .unwrap_or(&Completion::Unsolved)
+    }
+
+    /// Indices of puzzles that match the filter
+    pub fn filter<'a>(&'a self, filter: &'a Filter) -> impl Iterator<Item = usize> + 'a {
+        (0..self.puzzles.len())
+            .filter(|&index| filter.matches(&self.puzzles[index], self.completion(index)))
+    }
+
+    /// First puzzle that isn't solved after the given one, wrapping around to the start
+    pub fn next_unsolved(&self, after: Option<usize>) -> Option<usize> {
+        let start = after.map_or(0, |index| index + 1);
+        (start..self.puzzles.len())
+            .chain(0..start.min(self.puzzles.len()))
+            .find(|&index| *self.completion(index) != Completion::Solved)
+    }
+
+    /// Puzzle ready to be played, with player's entries, their origins and annotations restored.
+    /// Entries without a known origin are the player's.
+    pub fn model(&self, index: usize) -> SudokuModel {
+        let mut model = self.puzzles[index].model;
+        if let Completion::Started {
+            entries,
+            annotations,
+            origins,
+        } = self.completion(index)
+        {
+            let entries = parse_line(entries).unwrap_or_default();
+            let annotations = parse_line(annotations).unwrap_or_default();
+            let origins: Vec<_> = origins.chars().collect();
+            for x in 0..9 {
+                for y in 0..9 {
+                    let origin = origins
+                        .get(y * 9 + x)
+                        .and_then(|&char| origin_of(char))
+                        .unwrap_or(Origin::Player);
+                    model.place(x, y, entries[x][y], origin);
+                    model.annotate(x, y, Some(annotations[x][y]));
+                }
+            }
+            model.clear_history();
+        }
+        model
+    }
+
+    /// Remembers how far the player got with the puzzle
+    pub fn record(&mut self, index: usize, model: &SudokuModel) {
+        let givens = self.puzzles[index].model.givens_line();
+        let entries = model.entries_line();
+        let annotations = model.annotations_line();
+        let origins = model.origins_line();
+        if model.is_solved() {
+            self.progress.insert(givens, Completion::Solved);
+        } else if (entries.clone() + &annotations)
+            .chars()
+            .all(|char| char == '.')
+        {
+            self.progress.remove(&givens);
+        } else {
+            self.progress.insert(
+                givens,
+                Completion::Started {
+                    entries,
+                    annotations,
+                    origins,
+                },
+            );
+        }
+    }
+
+    /// Remembers how far the player got with the puzzle, and moves on to the next one that isn't
+    /// solved, with the assist of the one that was played
+    pub fn next(&mut self, puzzle: &mut usize, model: &mut SudokuModel) {
+        self.record(*puzzle, model);
+        if let Some(next) = self.next_unsolved(Some(*puzzle)) {
+            *puzzle = next;
+            *model = self.model(next).with_assist(model.assist());
+        }
+    }
+}
+
+/// Pack that isn't read from a file, e.g. one of generated puzzles
+impl From<Vec<Puzzle>> for Pack {
+    fn from(puzzles: Vec<Puzzle>) -> Self {
+        Self {
+            puzzles,
+            ..Self::default()
+        }
+    }
+}
+
+/// Writes the pack in the same format it is parsed from, without progress
+impl Display for Pack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, puzzle) in self.puzzles.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "# title: {}", puzzle.title)?;
+            if let Some(author) = &puzzle.author {
+                writeln!(f, "# author: {author}")?;
+            }
+            if let Some(source) = &puzzle.source {
+                writeln!(f, "# source: {source}")?;
+            }
+            if let Some(difficulty) = puzzle.difficulty {
+                writeln!(f, "# difficulty: {}", difficulty.name())?;
+            }
+            if let Some(rating) = puzzle.rating {
+                writeln!(f, "# rating: {rating}")?;
+            }
+            if !puzzle.tags.is_empty() {
+                writeln!(f, "# tags: {}", puzzle.tags.join(", "))?;
+            }
+            let regions = puzzle.model.regions();
+            if !regions.is_standard() {
+                let line: String = (0..81)
+                    .map(|index| char::from(b'0' + regions.region(index % 9, index / 9) as u8))
+                    .collect();
+                writeln!(f, "# regions: {line}")?;
+            }
+            let names: Vec<_> = puzzle.model.constraints().map(|c| c.name()).collect();
+            if !names.is_empty() {
+                writeln!(f, "# constraints: {}", names.join(", "))?;
+            }
+            writeln!(f, "{}", puzzle.model.givens_line())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_pack_is_solvable() {
+        let pack = Pack::default_pack();
+        assert_eq!(pack.len(), 6);
+        assert_eq!(pack.puzzles()[0].title, "Example");
+        assert!(!pack.puzzles()[2].model.regions().is_standard());
+        for puzzle in pack.puzzles() {
+            assert!(puzzle.model.solve().is_some(), "{}", puzzle.title);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "# title: X\n# difficulty: hard\n# rating: 4.5\n# tags: a, b\n\
+                    # constraints: diagonal-, antiking\n"
+            .to_string()
+            + &SudokuModel::example().givens_line()
+            + "\n";
+        let pack = Pack::parse(&text).unwrap();
+        let puzzle = &pack.puzzles()[0];
+        assert_eq!(puzzle.difficulty, Some(Difficulty::Hard));
+        assert_eq!(puzzle.rating, Some(4.5));
+        assert_eq!(puzzle.model.constraints().count(), 2);
+        assert_eq!(pack.to_string(), text);
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let error = Pack::parse("# title: X\n\n123\n").unwrap_err();
+        assert!(matches!(
+            error,
+            PackError::Line {
+                line: 3,
+                error: LineError::Length(3)
+            }
+        ));
+        let error = Pack::parse("# difficulty: trivial\n").unwrap_err();
+        assert!(matches!(error, PackError::Difficulty { line: 1, .. }));
+        assert!(matches!(Pack::parse("# title: x\n"), Err(PackError::Empty)));
+        assert!(matches!(Pack::parse(""), Err(PackError::Empty)));
+    }
+
+    #[test]
+    fn filter_and_progress() {
+        let mut pack = Pack::default_pack();
+        let hard = Filter {
+            difficulty: Some(Difficulty::Hard),
+            ..Filter::default()
+        };
+        assert_eq!(pack.filter(&hard).collect::<Vec<_>>(), vec![3, 4]);
+
+        let mut model = pack.model(1);
+        model.set(0, 0, 4);
+        model.place(1, 0, 5, Origin::Hint);
+        model.annotate(2, 0, Some(7));
+        pack.record(1, &model);
+        let restored = pack.model(1);
+        assert_eq!(restored.get(0, 0).value, 4);
+        assert_eq!(restored.origin(0, 0), Origin::Player);
+        assert_eq!(restored.get(1, 0).value, 5);
+        assert_eq!(restored.origin(1, 0), Origin::Hint);
+        assert_eq!(restored.annotation(2, 0), Some(7));
+        assert!(!restored.can_undo());
+        assert!(matches!(pack.completion(1), Completion::Started { .. }));
+
+        let mut reloaded = Pack::default_pack();
+        reloaded.load_progress("..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3.. 4................................................................................\n");
+        assert_eq!(reloaded.model(1).get(0, 0).value, 4);
+
+        let solved = pack.model(0).solve().unwrap();
+        pack.record(0, &solved);
+        assert_eq!(pack.next_unsolved(None), Some(1));
+        assert_eq!(pack.next_unsolved(Some(5)), Some(1));
+        let unsolved = Filter {
+            unsolved: true,
+            ..Filter::default()
+        };
+        assert_eq!(pack.filter(&unsolved).count(), 5);
+
+        pack.load_progress("junk\n");
+        assert_eq!(pack.next_unsolved(None), Some(1));
+    }
+
+    #[test]
+    fn progress_file_keeps_origins() {
+        let dir = std::env::temp_dir().join(format!("gui-experiment-pack-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pack.txt");
+        std::fs::write(&path, DEFAULT_PACK).unwrap();
+
+        let mut pack = Pack::open(&path).unwrap();
+        let mut model = pack.model(0);
+        model.set(3, 1, 1);
+        model.place(4, 1, 3, Origin::Hint);
+        model.place(5, 1, 4, Origin::AutoFill);
+        pack.record(0, &model);
+        pack.save_progress().unwrap();
+
+        let restored = Pack::open(&path).unwrap().model(0);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(restored.line(), model.line());
+        assert_eq!(restored.origin(4, 1), Origin::Hint);
+        for x in 0..9 {
+            for y in 0..9 {
+                assert_eq!(restored.origin(x, y), model.origin(x, y), "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn next_keeps_progress_of_the_puzzle() {
+        let mut pack = Pack::default_pack();
+        let (mut puzzle, mut model) = (0, pack.model(0));
+        model.set(3, 1, 1);
+        pack.next(&mut puzzle, &mut model);
+        assert_eq!(puzzle, 1);
+        assert_eq!(model.line(), pack.model(1).line());
+        assert!(matches!(pack.completion(0), Completion::Started { .. }));
+        assert_eq!(pack.model(0).get(3, 1).value, 1);
+    }
+}
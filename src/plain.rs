@@ -0,0 +1,368 @@
+//! Play without a terminal to draw in, through pipes, in CI scripts or in terminals of editors. The
+//! board is printed as text, and commands are read line by line:
+//!
+//! ```text
+//! r3c5=7      sets the cell in row 3, column 5, `=0` clears it
+//! clear r3c5  clears it too
+//! undo        undoes the last change
+//! hint        fills in a cell with the value of the solution
+//! fill        fills in every cell that has a single candidate
+//! show        prints the board again
+//! help        prints the commands
+//! quit        stops, so does the end of the input
+//! ```
+//!
+//! The board is printed again after every change. Empty lines and ones starting with `#` are
+//! skipped, so scripts can have comments. Messages are in the language of the options, commands
+//! are always the ones above.
+
+use std::fmt::{Display, Formatter, Write as _};
+use std::io::{self, BufRead, Write};
+
+use crate::locale::Message;
+use crate::{Assist, Colour, Locale, Options, Pack, SudokuModel};
+
+/// Plays on stdin and stdout, only the assistance and the locale of the options are used
+pub fn main(sudoku_model: SudokuModel, options: Options) -> io::Result<()> {
+    let model = sudoku_model.with_assist(options.assist);
+    play(
+        model,
+        options.locale,
+        io::stdin().lock(),
+        io::stdout().lock(),
+    )
+    .map(|_| ())
+}
+
+/// Plays the puzzle of the pack, and saves progress of it when the input ends
+pub fn main_pack(mut pack: Pack, puzzle: usize, options: Options) -> io::Result<()> {
+    let model = pack.model(puzzle).with_assist(options.assist);
+    let model = play(
+        model,
+        options.locale,
+        io::stdin().lock(),
+        io::stdout().lock(),
+    )?;
+    pack.record(puzzle, &model);
+    pack.save_progress()
+}
+
+/// Plays until the input ends, or until it says `quit`, and returns the board as it was left
+pub fn play(
+    mut model: SudokuModel,
+    locale: Locale,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<SudokuModel> {
+    write!(output, "{}", board(&model))?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let command = match Command::parse(line) {
+            Ok(Command::Quit) => break,
+            Ok(command) => command,
+            Err(error) => {
+                writeln!(output, "{}", error.message(locale))?;
+                continue;
+            }
+        };
+        let solved = model.is_solved();
+        let result = {
+            let _input =
+                tracing::debug_span!("input", source = "command", command = line).entered();
+            command.run(&mut model, locale)
+        };
+        match result {
+            Ok(Some(message)) => writeln!(output, "{message}")?,
+            Ok(None) => {}
+            Err(error) => {
+                writeln!(output, "{}", error.message(locale))?;
+                continue;
+            }
+        }
+        if command.shows_board() {
+            write!(output, "{}", board(&model))?;
+            if !solved && model.is_solved() {
+                writeln!(output, "{}", locale.text(Message::Solved))?;
+            }
+        }
+        output.flush()?;
+    }
+    output.flush()?;
+    Ok(model)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Cell by column and row, from 0, and the value, `0` clears it
+    Set(usize, usize, u8),
+    Undo,
+    Hint,
+    Fill,
+    Show,
+    Help,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Command, CommandError> {
+        let mut words = line.split_whitespace();
+        let first = words.next().unwrap_or_default().to_ascii_lowercase();
+        let command = match first.as_str() {
+            "undo" => Command::Undo,
+            "hint" => Command::Hint,
+            "fill" => Command::Fill,
+            "show" => Command::Show,
+            "help" | "?" => Command::Help,
+            "quit" | "exit" => Command::Quit,
+            "clear" => {
+                let cell = words.next().ok_or(CommandError::Missing)?;
+                let (x, y) = cell_of(cell)?;
+                Command::Set(x, y, 0)
+            }
+            _ => {
+                let Some((cell, value)) = line.split_once('=') else {
+                    return Err(CommandError::Unknown(first));
+                };
+                let (x, y) = cell_of(cell.trim())?;
+                match value.trim().parse::<u8>() {
+                    Ok(value) if value <= 9 => Command::Set(x, y, value),
+                    _ => return Err(CommandError::Value(value.trim().to_string())),
+                }
+            }
+        };
+        match words.next() {
+            Some(word) if !matches!(command, Command::Set(..)) => {
+                Err(CommandError::Extra(word.to_string()))
+            }
+            _ => Ok(command),
+        }
+    }
+
+    /// Changes, and `show`, print the board afterwards
+    fn shows_board(&self) -> bool {
+        !matches!(self, Command::Help)
+    }
+
+    /// Message to print, if any, before the board
+    fn run(&self, model: &mut SudokuModel, locale: Locale) -> Result<Option<String>, CommandError> {
+        match *self {
+            Command::Set(x, y, value) => {
+                if !model.get(x, y).enabled() {
+                    return Err(CommandError::Given(x, y));
+                }
+                model.set(x, y, value);
+                Ok(None)
+            }
+            Command::Undo if model.undo() => Ok(None),
+            Command::Undo => Err(CommandError::NoUndo),
+            Command::Hint | Command::Fill if model.assist() != Assist::Full => {
+                Err(CommandError::Assist)
+            }
+            Command::Hint => match model.hint() {
+                Some((x, y)) => Ok(Some(locale.format(Message::HintFor, &[&cell_name(x, y)]))),
+                None => Err(CommandError::NoHint),
+            },
+            Command::Fill => Ok(Some(
+                locale.format(Message::Filled, &[&model.fill_singles()]),
+            )),
+            Command::Show | Command::Quit => Ok(None),
+            Command::Help => Ok(Some(locale.text(Message::Help).to_string())),
+        }
+    }
+}
+
+/// `r3c5`, rows and columns are counted from 1
+fn cell_of(cell: &str) -> Result<(usize, usize), CommandError> {
+    let invalid = || CommandError::Cell(cell.to_string());
+    let lower = cell.to_ascii_lowercase();
+    let (row, column) = lower
+        .strip_prefix('r')
+        .and_then(|rest| rest.split_once('c'))
+        .ok_or_else(invalid)?;
+    match (row.parse::<usize>(), column.parse::<usize>()) {
+        (Ok(row @ 1..=9), Ok(column @ 1..=9)) => Ok((column - 1, row - 1)),
+        _ => Err(invalid()),
+    }
+}
+
+fn cell_name(x: usize, y: usize) -> String {
+    format!("r{}c{}", y + 1, x + 1)
+}
+
+/// Grid with `.` for empty cells, and boxes drawn for the standard regions, or the letters of
+/// irregular ones next to it. Conflicts are listed below it, when the assistance shows them.
+pub fn board(model: &SudokuModel) -> String {
+    let regions = model.regions();
+    let standard = regions.is_standard();
+    let value = |x: usize, y: usize| match model.get(x, y).value() {
+        0 => '.',
+        value => char::from(b'0' + value),
+    };
+    let mut text = String::new();
+    let line = "  +-------+-------+-------+\n";
+    if standard {
+        text += "    1 2 3   4 5 6   7 8 9\n";
+        text += line;
+    } else {
+        text += "   1 2 3 4 5 6 7 8 9\n";
+    }
+    for y in 0..9 {
+        let _ = write!(text, "{} ", y + 1);
+        for x in 0..9 {
+            if standard && x % 3 == 0 {
+                text += "| ";
+            } else if !standard {
+                text.push(' ');
+            }
+            text.push(value(x, y));
+            if standard {
+                text.push(' ');
+            }
+        }
+        if standard {
+            text += "|";
+        } else {
+            text += "   ";
+            for x in 0..9 {
+                text.push(' ');
+                text.push(char::from(b'a' + regions.region(x, y) as u8));
+            }
+        }
+        text.push('\n');
+        if standard && y % 3 == 2 {
+            text += line;
+        }
+    }
+    let rules: Vec<_> = model.constraints().map(|rule| rule.name()).collect();
+    if !rules.is_empty() {
+        let _ = writeln!(text, "rules: {}", rules.join(", "));
+    }
+    let conflicts: Vec<_> = (0..9)
+        .flat_map(|y| (0..9).map(move |x| (x, y)))
+        .filter(|&(x, y)| matches!(model.colour(x, y), Colour::Red))
+        .map(|(x, y)| cell_name(x, y))
+        .collect();
+    if !conflicts.is_empty() {
+        let _ = writeln!(text, "conflicts: {}", conflicts.join(" "));
+    }
+    text
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CommandError {
+    Unknown(String),
+    Missing,
+    Extra(String),
+    Cell(String),
+    Value(String),
+    Given(usize, usize),
+    NoUndo,
+    NoHint,
+    Assist,
+}
+
+impl CommandError {
+    fn text(&self, locale: Locale) -> String {
+        match self {
+            CommandError::Unknown(command) => locale.format(Message::UnknownCommand, &[command]),
+            CommandError::Missing => locale.text(Message::MissingCell).to_string(),
+            CommandError::Extra(word) => locale.format(Message::Unexpected, &[word]),
+            CommandError::Cell(cell) => locale.format(Message::NotACell, &[cell]),
+            CommandError::Value(value) => locale.format(Message::NotADigit, &[value]),
+            CommandError::Given(x, y) => locale.format(Message::Given, &[&cell_name(*x, *y)]),
+            CommandError::NoUndo => locale.text(Message::NoUndo).to_string(),
+            CommandError::NoHint => locale.text(Message::NoHint).to_string(),
+            CommandError::Assist => locale.text(Message::AssistanceOff).to_string(),
+        }
+    }
+
+    /// Line to print about it
+    fn message(&self, locale: Locale) -> String {
+        locale.format(Message::Error, &[&self.text(locale)])
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text(Locale::English))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    fn play_lines(model: SudokuModel, input: &str) -> (SudokuModel, String) {
+        let mut output = Vec::new();
+        let model = play(model, Locale::English, input.as_bytes(), &mut output).unwrap();
+        (model, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn commands_change_the_board() {
+        let (model, output) = play_lines(
+            SudokuModel::from_line(PUZZLE).unwrap(),
+            "# comment\nr1c1=4\nR1C2 = 5\nclear r1c2\nr1c3=1\nundo\nundo\nundo\nundo\nr1c1=4\nquit\nr9c9=9\n",
+        );
+        assert_eq!(model.get(0, 0).value(), 4);
+        assert_eq!(model.get(1, 0).value(), 0);
+        assert_eq!(model.get(8, 8).value(), 0);
+        assert!(output.starts_with("    1 2 3   4 5 6   7 8 9\n"));
+        assert!(output.contains("1 | 4 . 3 | . 2 . | 6 . . |\n"));
+        assert!(output.contains("error: r1c3 is given\n"), "{output}");
+        assert!(output.contains("error: nothing to undo\n"), "{output}");
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        assert_eq!(Command::parse("r3c5=7"), Ok(Command::Set(4, 2, 7)));
+        let error = |line| Command::parse(line).unwrap_err().to_string();
+        assert_eq!(error("r10c1=1"), "`r10c1` isn't a cell, like `r3c5`");
+        assert_eq!(error("r1c1=x"), "`x` isn't a digit from 0 to 9");
+        assert_eq!(error("jump"), "unknown command `jump`, `help` lists them");
+        assert_eq!(error("undo twice"), "unexpected `twice`");
+        assert_eq!(error("clear"), "which cell? like `clear r3c5`");
+    }
+
+    #[test]
+    fn solving_is_announced() {
+        let mut model = SudokuModel::from_line(PUZZLE).unwrap();
+        let solution = model.solve().unwrap();
+        // Every empty cell but the first one is filled in before playing
+        let empty: Vec<_> = (0..81)
+            .map(|index| (index % 9, index / 9))
+            .filter(|&(x, y)| model.get(x, y).enabled())
+            .collect();
+        let (x, y) = empty[0];
+        for &(x, y) in &empty[1..] {
+            model.set(x, y, solution.get(x, y).value());
+        }
+        let command = format!("{}={}\n", cell_name(x, y), solution.get(x, y).value());
+        let (model, output) = play_lines(model, &command);
+        assert!(model.is_solved());
+        assert!(output.ends_with("solved\n"), "{output}");
+    }
+
+    #[test]
+    fn messages_are_translated() {
+        let mut output = Vec::new();
+        let input = "r1c3=1\nfill\n".as_bytes();
+        play(
+            SudokuModel::from_line(PUZZLE).unwrap(),
+            Locale::German,
+            input,
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Fehler: r1c3 ist vorgegeben\n"), "{output}");
+        assert!(output.contains("Zellen ausgefüllt\n"), "{output}");
+    }
+}
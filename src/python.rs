@@ -0,0 +1,135 @@
+//! Python module, built with `maturin develop --features python`.
+//!
+//! Coordinates are the column `x`, then the row `y`, the same as in Rust, and a grid passed to the
+//! constructor is a list of columns, like the `[[u8; 9]; 9]` it's converted to. Cell status follows
+//! [`SudokuModel::colour`]: `"red"` for a conflict, `"green"` for a completed group.
+
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::{Colour, SudokuModel};
+
+#[pyclass(name = "SudokuModel", module = "gui_experiment")]
+pub struct PySudokuModel {
+    model: SudokuModel,
+}
+
+impl From<SudokuModel> for PySudokuModel {
+    fn from(model: SudokuModel) -> Self {
+        Self { model }
+    }
+}
+
+fn check(x: usize, y: usize) -> PyResult<()> {
+    if x < 9 && y < 9 {
+        Ok(())
+    } else {
+        Err(PyIndexError::new_err(format!(
+            "cell ({x}, {y}) is outside the grid"
+        )))
+    }
+}
+
+#[pymethods]
+impl PySudokuModel {
+    /// Non-zero values of the grid become givens, an empty board is made without one
+    #[new]
+    #[pyo3(signature = (grid = None))]
+    fn new(grid: Option<[[u8; 9]; 9]>) -> PyResult<Self> {
+        let Some(grid) = grid else {
+            return Ok(SudokuModel::new().into());
+        };
+        if let Some(value) = grid.iter().flatten().find(|value| **value > 9) {
+            return Err(PyValueError::new_err(format!("{value} is not a digit")));
+        }
+        Ok(SudokuModel::from(grid).into())
+    }
+
+    /// 81 cells row by row, `.` or `0` for empty cells
+    #[staticmethod]
+    fn from_line(line: &str) -> PyResult<Self> {
+        SudokuModel::from_line(line)
+            .map(Self::from)
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    #[staticmethod]
+    fn example() -> Self {
+        SudokuModel::example().into()
+    }
+
+    #[staticmethod]
+    fn generate(seed: u64) -> Self {
+        SudokuModel::generate(seed).into()
+    }
+
+    fn get(&self, x: usize, y: usize) -> PyResult<u8> {
+        check(x, y)?;
+        Ok(self.model.get(x, y).value)
+    }
+
+    /// Givens can't be changed, and are left as they are
+    fn set(&mut self, x: usize, y: usize, value: u8) -> PyResult<()> {
+        check(x, y)?;
+        if value > 9 {
+            return Err(PyValueError::new_err(format!("{value} is not a digit")));
+        }
+        self.model.set(x, y, value);
+        Ok(())
+    }
+
+    fn is_given(&self, x: usize, y: usize) -> PyResult<bool> {
+        check(x, y)?;
+        Ok(!self.model.get(x, y).enabled())
+    }
+
+    /// `"black"`, `"red"` or `"green"`
+    fn colour(&self, x: usize, y: usize) -> PyResult<&'static str> {
+        check(x, y)?;
+        Ok(match self.model.colour(x, y) {
+            Colour::Black => "black",
+            Colour::Red => "red",
+            Colour::Green => "green",
+        })
+    }
+
+    fn candidates(&self, x: usize, y: usize) -> PyResult<Vec<u8>> {
+        check(x, y)?;
+        Ok(self.model.candidates(x, y).iter().collect())
+    }
+
+    fn has_conflicts(&self) -> bool {
+        self.model.has_conflicts()
+    }
+
+    fn is_solved(&self) -> bool {
+        self.model.is_solved()
+    }
+
+    /// First solution found, or `None`
+    fn solve(&self) -> Option<Self> {
+        self.model.solve().map(Self::from)
+    }
+
+    #[pyo3(signature = (limit = 2))]
+    fn count_solutions(&self, limit: usize) -> usize {
+        self.model.count_solutions(limit)
+    }
+
+    fn line(&self) -> String {
+        self.model.line()
+    }
+
+    fn __str__(&self) -> String {
+        self.model.line()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SudokuModel.from_line('{}')", self.model.line())
+    }
+}
+
+#[pymodule]
+fn gui_experiment(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySudokuModel>()
+}
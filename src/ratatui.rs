@@ -7,12 +7,22 @@
 //! Value adjustment: +/- keys to increment/decrement
 //! Quit: Press ESC or 'q'
 //!
+//! Letter keys here are the default ones, the config can bind others, see [`crate::config`].
+//!
+//! When stdin or stdout isn't a terminal, e.g. through pipes, a single grid is played line by line
+//! instead, see [`crate::plain`].
+//!
 //! The selected cell is highlighted with a border (or background in minimal mode).
-//! Colors indicate:
-//! - White: Normal state
-//! - Red: Conflict detected
-//! - Green: Row/column/box complete
-//! - Cyan: Fixed cells (initial puzzle)
+//! Colours are the ones of the theme, see [`crate::Palette`], like in the other frontends:
+//! - Text: Givens, values of the player, from a hint ('?'), auto-filled singles ('f') and
+//!   values imported from elsewhere
+//! - Red background: Conflict detected
+//! - Green background: Row/column/box complete
+//! - Borders of fixed cells (initial puzzle) and of cells on a diagonal or in a window of extra
+//!   constraints
+//! - Other backgrounds: Colours the player marked cells with, 'c' cycles through them, 'C' clears
+//!
+//! 'u' undoes the last change of a value or a colour.
 //!
 //! ## Responsive Layout
 //!
@@ -30,6 +40,16 @@
 //! - **17x17**: Overlapping 3x3 cells with collapsed borders and collapsed separators
 //! - **19x19**: Overlapping 3x3 cells with separators, borders and border around, all collapsed
 //!
+//! Irregular (jigsaw) regions don't fit separators every third cell, so they always use collapsed
+//! cells with double borders between regions, or tinted background when cells are too small to have
+//! borders.
+//!
+//! When playing a pack, 'n' moves on to its next unsolved puzzle, and progress is saved on exit.
+//!
+//! Samurai board (`--samurai`) is drawn with lines between boxes only, and scrolls to follow the
+//! cursor when it doesn't fit. 'z' zooms it out to a board without lines, with every other box
+//! tinted instead.
+//!
 //! These modes are not displaying correctly yet, because some maths is off:
 //! - **23x23**: Overlapping 3x3 cells with separators and collapsed borders
 //! - **25x25**: Overlapping 3x3 cells with separators, collapsed borders and border around
@@ -58,7 +78,13 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
-use crate::{Colour, SudokuModel};
+use crate::locale::Message;
+use crate::rpc::{self, Rpc};
+use crate::samurai::SIZE;
+use crate::{
+    Action, Colour, Keys, Locale, Options, Origin, Pack, Palette, Rgb, SamuraiModel, SudokuModel,
+    plain,
+};
 
 pub const DOUBLE_HORIZONTAL_PLAIN_DOWN: &str = "╤";
 pub const DOUBLE_HORIZONTAL_PLAIN_UP: &str = "╧";
@@ -122,13 +148,57 @@ pub const EMPTY_SET: Set = Set {
     cross: " ",
 };
 
-pub fn main(sudoku_model: SudokuModel) -> io::Result<()> {
-    // Check if we're running in a terminal
-    if !crossterm::tty::IsTty::is_tty(&io::stdout()) {
-        eprintln!("Error: This application requires a terminal (TTY) to run.");
-        eprintln!(
-            "Please run it directly in a terminal, not through a pipe or non-TTY environment."
-        );
+/// Only the assistance, keys, theme and locale of the options are used, a terminal has neither a
+/// window, nor a zoom
+pub fn main(sudoku_model: SudokuModel, options: Options, rpc: Option<Rpc>) -> io::Result<()> {
+    let mut app = App::new(sudoku_model.with_assist(options.assist), &options);
+    app.rpc = rpc;
+    run(&mut app)
+}
+
+pub fn main_samurai(samurai_model: SamuraiModel, options: Options) -> io::Result<()> {
+    let mut app = App::new(SudokuModel::new(), &options);
+    app.samurai = Some(samurai_model.with_assist(options.assist));
+    run(&mut app)
+}
+
+/// Plays puzzles of the pack starting from the given one, progress is saved on exit
+pub fn main_pack(pack: Pack, puzzle: usize, options: Options, rpc: Option<Rpc>) -> io::Result<()> {
+    let mut app = App::new(pack.model(puzzle).with_assist(options.assist), &options);
+    app.pack = Some(pack);
+    app.rpc = rpc;
+    app.puzzle = puzzle;
+    run(&mut app)?;
+    app.record();
+    match &app.pack {
+        Some(pack) => pack.save_progress(),
+        None => Ok(()),
+    }
+}
+
+fn run(app: &mut App) -> io::Result<()> {
+    // Without a terminal to draw in, or with commands piped in, a single grid is played line by
+    // line instead
+    let is_tty =
+        crossterm::tty::IsTty::is_tty(&io::stdout()) && crossterm::tty::IsTty::is_tty(&io::stdin());
+    if !is_tty && app.rpc.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "automation needs a terminal to draw in",
+        ));
+    }
+    if !is_tty && app.samurai.is_none() {
+        eprintln!("{}", app.locale.text(Message::NotATerminal));
+        app.model = plain::play(
+            app.model,
+            app.locale,
+            io::stdin().lock(),
+            io::stdout().lock(),
+        )?;
+        return Ok(());
+    }
+    if !is_tty {
+        eprintln!("{}", app.locale.text(Message::NeedsTerminal));
         return Err(io::Error::new(
             io::ErrorKind::Unsupported,
             "Not running in a TTY",
@@ -138,9 +208,8 @@ pub fn main(sudoku_model: SudokuModel) -> io::Result<()> {
     // Setup terminal and restore on exit
     let res = {
         let mut terminal_guard = TerminalGuard::new()?;
-        let mut app = App::new(sudoku_model);
 
-        run_app(terminal_guard.terminal(), &mut app)
+        run_app(terminal_guard.terminal(), app)
     };
 
     if let Err(err) = res {
@@ -210,6 +279,9 @@ struct Cell {
     state: State,
     selected: bool,
     enabled: bool,
+    origin: Origin,
+    tint: Color,
+    palette: &'static Palette,
     separate: bool,
     text: &'static str,
 }
@@ -380,88 +452,201 @@ impl Cell {
 
 struct App {
     model: SudokuModel,
+    /// Replaces `model` when playing samurai, cursor is then on the 21x21 board
+    samurai: Option<SamuraiModel>,
     cursor_x: usize,
     cursor_y: usize,
     should_quit: bool,
     debug: bool,
+    /// Samurai board without lines between boxes, to fit smaller terminals
+    zoomed_out: bool,
+    /// Pack that `model` comes from, with the index of the puzzle in it
+    pack: Option<Pack>,
+    puzzle: usize,
+    keys: Keys,
+    palette: &'static Palette,
+    locale: Locale,
+    /// Requests that drive the board too, on the app's cursor
+    rpc: Option<Rpc>,
 }
 
 impl App {
-    fn new(model: SudokuModel) -> Self {
+    fn new(model: SudokuModel, options: &Options) -> Self {
         Self {
             model,
+            samurai: None,
             cursor_x: 0,
             cursor_y: 0,
             should_quit: false,
             debug: false,
+            zoomed_out: false,
+            pack: None,
+            puzzle: 0,
+            keys: options.keys,
+            palette: options.theme.palette(),
+            locale: options.locale,
+            rpc: None,
+        }
+    }
+
+    fn record(&mut self) {
+        if let Some(pack) = &mut self.pack {
+            pack.record(self.puzzle, &self.model);
+        }
+    }
+
+    /// Switches to the next puzzle of the pack that isn't solved yet, keeping progress of this one
+    fn next_puzzle(&mut self) {
+        if let Some(pack) = &mut self.pack {
+            pack.next(&mut self.puzzle, &mut self.model);
+        }
+    }
+
+    /// Last index of the cursor on either axis
+    fn last(&self) -> usize {
+        if self.samurai.is_some() { SIZE - 1 } else { 8 }
+    }
+
+    /// Moves the cursor to the nearest cell in the direction, skipping gaps between samurai grids
+    fn step(&mut self, dx: isize, dy: isize) {
+        let (mut x, mut y) = (self.cursor_x, self.cursor_y);
+        while let (Some(next_x), Some(next_y)) =
+            (x.checked_add_signed(dx), y.checked_add_signed(dy))
+            && next_x <= self.last()
+            && next_y <= self.last()
+        {
+            (x, y) = (next_x, next_y);
+            if self.samurai.is_none() || SamuraiModel::contains(x, y) {
+                (self.cursor_x, self.cursor_y) = (x, y);
+                return;
+            }
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        match &self.samurai {
+            Some(samurai) => samurai
+                .get(self.cursor_x, self.cursor_y)
+                .is_some_and(|value| value.enabled()),
+            None => self.model.get(self.cursor_x, self.cursor_y).enabled(),
+        }
+    }
+
+    fn set(&mut self, value: u8) {
+        match &mut self.samurai {
+            Some(samurai) => samurai.set(self.cursor_x, self.cursor_y, value),
+            None => self.model.set(self.cursor_x, self.cursor_y, value),
+        }
+    }
+
+    fn add(&mut self, value: i8) {
+        match &mut self.samurai {
+            Some(samurai) => samurai.add(self.cursor_x, self.cursor_y, value),
+            None => self.model.add(self.cursor_x, self.cursor_y, value),
         }
     }
 
     fn handle_key(&mut self, key: KeyEvent) {
+        let _input = tracing::debug_span!("input", source = "key", key = %key.code).entered();
+        let action = match key.code {
+            KeyCode::Char(c) => self.keys.action(c),
+            KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Up => Some(Action::Up),
+            KeyCode::Down => Some(Action::Down),
+            KeyCode::Left => Some(Action::Left),
+            KeyCode::Right => Some(Action::Right),
+            _ => None,
+        };
+        if let Some(action) = action {
+            self.act(action);
+            return;
+        }
         match key.code {
-            // Debug grid info
-            KeyCode::Char('d') => {
-                self.debug = !self.debug;
-            }
-            // Quit
-            KeyCode::Esc | KeyCode::Char('q') => {
-                self.should_quit = true;
-            }
-            // Navigation - Arrow keys
-            KeyCode::Up | KeyCode::Char('k') => {
-                if self.cursor_y > 0 {
-                    self.cursor_y -= 1;
-                }
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                if self.cursor_y < 8 {
-                    self.cursor_y += 1;
-                }
-            }
-            KeyCode::Left | KeyCode::Char('h') => {
-                if self.cursor_x > 0 {
-                    self.cursor_x -= 1;
-                }
-            }
-            KeyCode::Right | KeyCode::Char('l') => {
-                if self.cursor_x < 8 {
-                    self.cursor_x += 1;
-                }
-            }
             // Number input
             KeyCode::Char(c) if c.is_ascii_digit() => {
                 let digit = c.to_digit(10).unwrap() as u8;
-                if self.model.get(self.cursor_x, self.cursor_y).enabled {
-                    self.model.set(self.cursor_x, self.cursor_y, digit);
+                if self.is_enabled() {
+                    self.set(digit);
                 }
             }
             // Clear cell
             KeyCode::Backspace | KeyCode::Delete => {
-                if self.model.get(self.cursor_x, self.cursor_y).enabled {
-                    self.model.set(self.cursor_x, self.cursor_y, 0);
+                if self.is_enabled() {
+                    self.set(0);
                 }
             }
             // Increment/decrement
             KeyCode::Char('+') | KeyCode::Char('=') => {
-                if self.model.get(self.cursor_x, self.cursor_y).enabled {
-                    self.model.add(self.cursor_x, self.cursor_y, 1);
+                if self.is_enabled() {
+                    self.add(1);
                 }
             }
             KeyCode::Char('-') | KeyCode::Char('_') => {
-                if self.model.get(self.cursor_x, self.cursor_y).enabled {
-                    self.model.add(self.cursor_x, self.cursor_y, -1);
+                if self.is_enabled() {
+                    self.add(-1);
                 }
             }
             _ => {}
         }
     }
+
+    fn act(&mut self, action: Action) {
+        match action {
+            // Debug grid info
+            Action::Debug => {
+                self.debug = !self.debug;
+            }
+            Action::Quit => {
+                self.should_quit = true;
+            }
+            Action::Up => self.step(0, -1),
+            Action::Down => self.step(0, 1),
+            Action::Left => self.step(-1, 0),
+            Action::Right => self.step(1, 0),
+            // Assistance, only for a single grid
+            Action::Hint if self.samurai.is_none() => {
+                if let Some((x, y)) = self.model.hint() {
+                    (self.cursor_x, self.cursor_y) = (x, y);
+                }
+            }
+            Action::Fill if self.samurai.is_none() => {
+                self.model.fill_singles();
+            }
+            // Colour annotations and undo, also only for a single grid
+            Action::Colour if self.samurai.is_none() => {
+                self.model.cycle_annotation(self.cursor_x, self.cursor_y);
+            }
+            Action::ClearColour if self.samurai.is_none() => {
+                self.model.annotate(self.cursor_x, self.cursor_y, None);
+            }
+            Action::Undo if self.samurai.is_none() => {
+                self.model.undo();
+            }
+            // Next puzzle of the pack
+            Action::Next => self.next_puzzle(),
+            // Zoom samurai board in and out
+            Action::Zoom => {
+                self.zoomed_out = !self.zoomed_out;
+            }
+            Action::Hint | Action::Fill | Action::Colour | Action::ClearColour | Action::Undo => {}
+        }
+    }
 }
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
     loop {
+        if let Some(rpc) = &mut app.rpc {
+            let mut cursor = (app.cursor_x, app.cursor_y);
+            rpc.poll(&mut app.model, &mut cursor);
+            (app.cursor_x, app.cursor_y) = cursor;
+        }
         terminal.draw(|f| ui(f, app))?;
 
-        if event::poll(std::time::Duration::from_millis(100))?
+        let timeout = match app.rpc {
+            Some(_) => rpc::TICK,
+            None => std::time::Duration::from_millis(100),
+        };
+        if event::poll(timeout)?
             && let Event::Key(key) = event::read()?
             && key.kind == KeyEventKind::Press
         {
@@ -525,6 +710,15 @@ impl LayoutConfig {
         }
     }
 
+    /// Irregular regions can't be split by separators every third cell, so region borders are
+    /// drawn between cells instead, or cells are tinted by region when there are no cell borders.
+    fn for_irregular_regions(mut self) -> Self {
+        self.cell_collapsed = true;
+        self.separators_collapsed = true;
+        self.separators_visible = self.cell_border;
+        self
+    }
+
     fn grid_width(&self) -> u16 {
         self.grid_size(self.cell_w)
     }
@@ -619,6 +813,13 @@ impl LayoutConfig {
 
 fn ui(f: &mut Frame, app: &App) {
     let size = f.area();
+    let palette = app.palette;
+    f.buffer_mut().set_style(
+        size,
+        Style::default()
+            .fg(palette.given.into())
+            .bg(palette.background.into()),
+    );
 
     // Prioritize footer even with smaller cells
     let show_instructions = size.height > 9;
@@ -657,7 +858,17 @@ fn ui(f: &mut Frame, app: &App) {
     // Title (if shown)
     if show_header {
         f.render_widget(
-            render_bordered_text("Sudoku", header_borders, true),
+            render_bordered_text(
+                if let Some(pack) = &app.pack {
+                    &pack.puzzles()[app.puzzle].title
+                } else if app.samurai.is_some() {
+                    app.locale.text(Message::SamuraiTitle)
+                } else {
+                    app.locale.text(Message::Title)
+                },
+                header_borders,
+                true,
+            ),
             chunks[chunk_idx],
         );
         chunk_idx += 1;
@@ -665,45 +876,104 @@ fn ui(f: &mut Frame, app: &App) {
 
     // Sudoku grid - select config based on actual available grid area
     let grid_area = chunks[chunk_idx];
-    let config = LayoutConfig::from_size(grid_area.width, grid_area.height);
-    render_sudoku_grid(f, app, grid_area, &config);
+    if let Some(samurai) = &app.samurai {
+        render_samurai(f, app, samurai, grid_area);
+    } else {
+        let mut config = LayoutConfig::from_size(grid_area.width, grid_area.height);
+        if !app.model.regions().is_standard() {
+            config = config.for_irregular_regions();
+        }
+        render_sudoku_grid(f, app, grid_area, &config);
+    }
     chunk_idx += 1;
 
     // Instructions (if shown)
     if show_instructions {
         let area = chunks[chunk_idx];
 
-        let instructions = if area.width < 9 {
-            "Q"
-        } else if area.width < 12 {
-            "⇆ ⇅ 0-9 Q"
-        } else if area.width < 14 {
-            "⇆ ⇅ 0-9 ⌫ Q"
-        } else if area.width < 16 {
-            "⇆ ⇅ ± 0-9 ⌫ Q"
-        } else if area.width < 17 {
-            "⇆ ⇅ ± 0-9 ⌫ ␛ Q"
-        } else if area.width < 19 {
-            "←↓↑→ ± 0-9 ⌫ ␛ Q"
-        } else if area.width < 24 {
-            "←↓↑→ ± 0-9 ⌫ Esc/Q"
-        } else if area.width < 26 {
-            "←↓↑→/hjkl ± 0-9 ⌫ Esc/Q"
-        } else if area.width < 56 {
-            "←↓↑→/hjkl -/+ 0-9 ⌫ Esc/Q"
-        } else if area.width < 76 {
-            "↑↓←→/hjkl:Move 1-9:Set 0/⌫:Clear +/-:Inc/Dec ESC/q:Quit"
-        } else {
-            "Arrows/hjkl: Move | 1-9: Set value | 0/⌫: Clear | +/-: Inc/Dec | ESC/q: Quit"
-        };
+        // Borders take a column on each side
+        let width = area
+            .width
+            .saturating_sub(if footer_borders { 2 } else { 0 });
+        let instructions = instructions(app, width);
 
         f.render_widget(
-            render_bordered_text(instructions, footer_borders, false),
+            render_bordered_text(&instructions, footer_borders, false),
             area,
         );
     }
 }
 
+/// Keys of the footer, the longest variant that fits in the width. Translations differ in length,
+/// so variants are measured rather than picked by fixed widths, and the shortest ones are symbols.
+fn instructions(app: &App, width: u16) -> String {
+    let text = |message| app.locale.text(message);
+    // Keys can be bound in the config, so the defaults `hjkl` and `q` may be others
+    let keys = &app.keys;
+    let moves: String = [Action::Left, Action::Down, Action::Up, Action::Right]
+        .map(|action| keys.key(action))
+        .iter()
+        .collect();
+    let quit = keys.key(Action::Quit);
+    let extra = if app.samurai.is_some() {
+        Some(format!(
+            "{}: {}",
+            keys.key(Action::Zoom),
+            text(Message::Zoom)
+        ))
+    } else if app.pack.is_some() {
+        Some(format!(
+            "{}: {}",
+            keys.key(Action::Next),
+            text(Message::Next)
+        ))
+    } else {
+        None
+    };
+    let items = |extra: Option<String>| {
+        [
+            Some(format!(
+                "{}/{moves}: {}",
+                text(Message::Arrows),
+                text(Message::Move)
+            )),
+            Some(format!("1-9: {}", text(Message::SetValue))),
+            Some(format!("0/⌫: {}", text(Message::Clear))),
+            Some(format!("+/-: {}", text(Message::IncDec))),
+            extra,
+            Some(format!("ESC/{quit}: {}", text(Message::Quit))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" | ")
+    };
+    let variants = [
+        items(extra),
+        items(None),
+        format!(
+            "↑↓←→/{moves}:{} 1-9:{} 0/⌫:{} +/-:{} ESC/{quit}:{}",
+            text(Message::Move),
+            text(Message::Set),
+            text(Message::Clear),
+            text(Message::IncDec),
+            text(Message::Quit),
+        ),
+        format!("←↓↑→/{moves} -/+ 0-9 ⌫ Esc/{quit}"),
+        format!("←↓↑→/{moves} ± 0-9 ⌫ Esc/{quit}"),
+        format!("←↓↑→ ± 0-9 ⌫ Esc/{quit}"),
+        format!("←↓↑→ ± 0-9 ⌫ ␛ {quit}"),
+        format!("⇆ ⇅ ± 0-9 ⌫ ␛ {quit}"),
+        format!("⇆ ⇅ ± 0-9 ⌫ {quit}"),
+        format!("⇆ ⇅ 0-9 ⌫ {quit}"),
+        format!("⇆ ⇅ 0-9 {quit}"),
+    ];
+    variants
+        .into_iter()
+        .find(|variant| Line::from(variant.as_str()).width() <= usize::from(width))
+        .unwrap_or_else(|| format!("{quit}"))
+}
+
 fn render_bordered_text(text: &str, with_borders: bool, bold: bool) -> Paragraph<'_> {
     let mut text_style = Style::default();
     if bold {
@@ -739,28 +1009,23 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
     };
 
     if grid_height > area.height || grid_width > area.width {
+        // Keys go in place of the `{}` of the message, in any order of words of the language
+        let key_style = Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::Yellow);
+        let mut spans = Vec::new();
+        let quit = app.keys.key(Action::Quit).to_string();
+        let mut keys = ["ESC", quit.as_str()].into_iter();
+        for (index, part) in app.locale.text(Message::TooSmall).split("{}").enumerate() {
+            if index > 0
+                && let Some(key) = keys.next()
+            {
+                spans.push(Span::styled(key, key_style));
+            }
+            spans.push(Span::styled(part, Style::default()));
+        }
         f.render_widget(
-            Paragraph::new(
-                Line::from(vec![
-                    Span::styled("terminal too small to render, press ", Style::default()),
-                    Span::styled(
-                        "ESC",
-                        Style::default()
-                            .add_modifier(Modifier::BOLD)
-                            .fg(Color::Yellow),
-                    ),
-                    Span::styled(" or ", Style::default()),
-                    Span::styled(
-                        "Q",
-                        Style::default()
-                            .add_modifier(Modifier::BOLD)
-                            .fg(Color::Yellow),
-                    ),
-                    Span::styled(" to quit", Style::default()),
-                ])
-                .alignment(Alignment::Left),
-            )
-            .wrap(Wrap { trim: true }),
+            Paragraph::new(Line::from(spans).alignment(Alignment::Left)).wrap(Wrap { trim: true }),
             area,
         );
         return;
@@ -780,6 +1045,8 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
         };
     let separator_stride = if config.separators_collapsed { 0 } else { 1 };
 
+    let regions = app.model.regions();
+    let tints = app.palette.region_tints(regions).map(Color::from);
     let mut cells = Vec::with_capacity(81);
 
     for y in 0..9 {
@@ -800,7 +1067,7 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
             let cell_w = config.cell_w - correction_w;
             let cell_h = config.cell_h - correction_h;
 
-            let enabled = app.model.get(x, y).enabled;
+            let origin = app.model.origin(x, y);
             let selected = app.cursor_x == x && app.cursor_y == y;
             let state = match app.model.colour(x, y) {
                 Colour::Black => State::Neutral,
@@ -811,7 +1078,10 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
             let border_left = if config.cell_border {
                 if x == 0 && !config.outer_border && config.separators_collapsed {
                     BorderStyle::None
-                } else if x % 3 == 0 && config.separators_visible && config.separators_collapsed {
+                } else if !regions.same(x, y, -1, 0)
+                    && config.separators_visible
+                    && config.separators_collapsed
+                {
                     BorderStyle::Double
                 } else {
                     BorderStyle::Plain
@@ -822,7 +1092,10 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
             let border_right = if config.cell_border {
                 if x == 8 && !config.outer_border && config.separators_collapsed {
                     BorderStyle::None
-                } else if x % 3 == 2 && config.separators_visible && config.separators_collapsed {
+                } else if !regions.same(x, y, 1, 0)
+                    && config.separators_visible
+                    && config.separators_collapsed
+                {
                     BorderStyle::Double
                 } else {
                     BorderStyle::Plain
@@ -833,7 +1106,10 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
             let border_top = if config.cell_border {
                 if y == 0 && !config.outer_border && config.separators_collapsed {
                     BorderStyle::None
-                } else if y % 3 == 0 && config.separators_visible && config.separators_collapsed {
+                } else if !regions.same(x, y, 0, -1)
+                    && config.separators_visible
+                    && config.separators_collapsed
+                {
                     BorderStyle::Double
                 } else {
                     BorderStyle::Plain
@@ -844,7 +1120,10 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
             let border_bottom = if config.cell_border {
                 if y == 8 && !config.outer_border && config.separators_collapsed {
                     BorderStyle::None
-                } else if y % 3 == 2 && config.separators_visible && config.separators_collapsed {
+                } else if !regions.same(x, y, 0, 1)
+                    && config.separators_visible
+                    && config.separators_collapsed
+                {
                     BorderStyle::Double
                 } else {
                     BorderStyle::Plain
@@ -877,7 +1156,21 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
                 h: cell_h,
                 state,
                 selected,
-                enabled,
+                enabled: origin != Origin::Given,
+                origin,
+                tint: if let Some(annotation) = app.model.annotation(x, y) {
+                    app.palette
+                        .cell
+                        .mix(app.palette.annotation(annotation))
+                        .into()
+                } else if app.model.is_marked(x, y) {
+                    app.palette.cell.mix(app.palette.marked).into()
+                } else if config.cell_border || regions.is_standard() {
+                    app.palette.cell.into()
+                } else {
+                    tints[regions.region(x, y)]
+                },
+                palette: app.palette,
                 separate: !config.cell_collapsed,
                 text: value,
             });
@@ -895,7 +1188,7 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
         render_cell(f, cell)
     }
 
-    render_separators(f, config, inner, cell_stride_y, cell_stride_x);
+    render_separators(f, config, app.palette, inner, cell_stride_y, cell_stride_x);
 
     if app.debug {
         f.render_widget(
@@ -908,6 +1201,122 @@ fn render_sudoku_grid(f: &mut Frame, app: &App, area: Rect, config: &LayoutConfi
     }
 }
 
+/// Samurai board doesn't fit into the layouts above, so it is drawn character by character. Lines
+/// are only drawn between boxes, and when the board doesn't fit, it is scrolled to keep the cursor
+/// in view. Zoomed out board has no lines at all, and boxes are told apart by background instead.
+fn render_samurai(f: &mut Frame, app: &App, model: &SamuraiModel, area: Rect) {
+    let lines = !app.zoomed_out;
+    let cell_w = if lines { 3 } else { 2 };
+    // Position of a cell from the board edge, with a line before every third cell
+    let column = |i: usize| i as u16 * cell_w + if lines { i as u16 / 3 + 1 } else { 0 };
+    let row = |i: usize| i as u16 + if lines { i as u16 / 3 + 1 } else { 0 };
+    let width = column(SIZE) - u16::from(!lines);
+    let height = row(SIZE) - u16::from(!lines);
+
+    // Centre the board if it fits, otherwise centre the cursor as long as the board fills the area
+    let origin = |start: u16, available: u16, total: u16, cursor: u16| -> i32 {
+        if total <= available {
+            i32::from(start + (available - total) / 2)
+        } else {
+            let scroll = cursor.saturating_sub(available / 2).min(total - available);
+            i32::from(start) - i32::from(scroll)
+        }
+    };
+    let left = origin(area.x, area.width, width, column(app.cursor_x));
+    let top = origin(area.y, area.height, height, row(app.cursor_y));
+
+    let buffer = f.buffer_mut();
+    let mut put = |x: u16, y: u16, symbol: &str, style: Style| {
+        let (x, y) = (left + i32::from(x), top + i32::from(y));
+        if let (Ok(x), Ok(y)) = (u16::try_from(x), u16::try_from(y))
+            && area.contains((x, y).into())
+        {
+            buffer[(x, y)].set_symbol(symbol).set_style(style);
+        }
+    };
+
+    for x in 0..SIZE {
+        for y in 0..SIZE {
+            let Some(value) = model.get(x, y) else {
+                continue;
+            };
+            let palette = app.palette;
+            let mut background = palette.cell(model.colour(x, y));
+            if !lines && matches!(model.colour(x, y), Colour::Black) && (x / 3 + y / 3) % 2 == 1 {
+                background = background.mix(palette.hover);
+            }
+            let mut style = Style::default()
+                .fg(palette.text(value.origin()).into())
+                .bg(background.into());
+            if !value.enabled() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if app.cursor_x == x && app.cursor_y == y {
+                style = style.bg(palette.cursor.into()).add_modifier(Modifier::BOLD);
+            }
+            let text = if lines {
+                format!(" {} ", value.text())
+            } else {
+                format!("{} ", value.text())
+            };
+            for (i, char) in text.chars().enumerate() {
+                put(column(x) + i as u16, row(y), &char.to_string(), style);
+            }
+        }
+    }
+
+    if !lines {
+        return;
+    }
+    // Boxes are aligned to multiples of 3 on every grid, so lines only depend on which boxes exist
+    let boxes = SIZE / 3;
+    let has_box =
+        |x: usize, y: usize| x < boxes && y < boxes && SamuraiModel::contains(x * 3, y * 3);
+    let horizontal = |x: usize, y: usize| has_box(x, y) || y > 0 && has_box(x, y - 1);
+    let vertical = |x: usize, y: usize| has_box(x, y) || x > 0 && has_box(x - 1, y);
+    let style = Style::default().fg(app.palette.border.into());
+    let set = symbols::line::NORMAL;
+    for x in 0..=boxes {
+        for y in 0..=boxes {
+            // Lines go right before the first cell of a box
+            let (line_x, line_y) = (column(x * 3) - 1, row(y * 3) - 1);
+            if horizontal(x, y) {
+                for i in 1..=3 * cell_w {
+                    put(line_x + i, line_y, set.horizontal, style);
+                }
+            }
+            if vertical(x, y) {
+                for i in 1..=3 {
+                    put(line_x, line_y + i, set.vertical, style);
+                }
+            }
+            let up = y > 0 && vertical(x, y - 1);
+            let down = vertical(x, y);
+            let left = x > 0 && horizontal(x - 1, y);
+            let right = horizontal(x, y);
+            let junction = match (up, down, left, right) {
+                (true, true, true, true) => set.cross,
+                (true, true, true, false) => set.vertical_left,
+                (true, true, false, true) => set.vertical_right,
+                (false, true, true, true) => set.horizontal_down,
+                (true, false, true, true) => set.horizontal_up,
+                (false, true, false, true) => set.top_left,
+                (false, true, true, false) => set.top_right,
+                (true, false, false, true) => set.bottom_left,
+                (true, false, true, false) => set.bottom_right,
+                (true, true, false, false)
+                | (true, false, false, false)
+                | (false, true, false, false) => set.vertical,
+                (false, false, true, true)
+                | (false, false, true, false)
+                | (false, false, false, true) => set.horizontal,
+                (false, false, false, false) => continue,
+            };
+            put(line_x, line_y, junction, style);
+        }
+    }
+}
+
 fn get_correction(config: &LayoutConfig, x: usize) -> (u16, u16) {
     if !config.outer_border && config.separators_visible {
         if config.separators_collapsed {
@@ -927,6 +1336,7 @@ fn get_correction(config: &LayoutConfig, x: usize) -> (u16, u16) {
 fn render_separators(
     f: &mut Frame,
     config: &LayoutConfig,
+    palette: &Palette,
     inner: Rect,
     cell_stride_y: u16,
     cell_stride_x: u16,
@@ -1040,7 +1450,7 @@ fn render_separators(
                 f.render_widget(
                     Block::default()
                         .borders(borders)
-                        .border_style(Style::default().fg(Color::DarkGray))
+                        .border_style(Style::default().fg(palette.border.into()))
                         .border_set(border_set),
                     Rect {
                         x,
@@ -1054,6 +1464,12 @@ fn render_separators(
     }
 }
 
+impl From<Rgb> for Color {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        Color::Rgb(r, g, b)
+    }
+}
+
 fn render_cell(f: &mut Frame, cell: Cell) {
     let area = Rect {
         x: cell.x,
@@ -1071,20 +1487,23 @@ fn render_cell(f: &mut Frame, cell: Cell) {
     let is_selected = cell.selected;
 
     // Determine colors
-    let fg_color = match cell.state {
-        State::Neutral => Color::White,
-        State::Bad => Color::Red,
-        State::Good => Color::Green,
+    let palette = cell.palette;
+    let bg_color = match cell.state {
+        State::Neutral => cell.tint,
+        State::Bad => palette.conflict.into(),
+        State::Good => palette.complete.into(),
     };
 
-    let mut style = Style::default().fg(fg_color);
+    let mut style = Style::default()
+        .fg(palette.text(cell.origin).into())
+        .bg(bg_color);
     if !cell.enabled {
         style = style.add_modifier(Modifier::BOLD);
     }
 
     // Highlight selected cell
     if is_selected {
-        style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+        style = style.bg(palette.cursor.into()).add_modifier(Modifier::BOLD);
     }
 
     if cell.has_borders() {
@@ -1094,14 +1513,14 @@ fn render_cell(f: &mut Frame, cell: Cell) {
         let border_set = cell.get_border_set();
         let border_style = Style::default().fg(if cell.separate {
             if is_selected {
-                Color::Yellow
+                palette.player.into()
             } else if !cell.enabled {
-                Color::Cyan
+                palette.given_border.into()
             } else {
-                Color::DarkGray
+                palette.border.into()
             }
         } else {
-            Color::DarkGray
+            palette.border.into()
         });
 
         let block = Block::default()
@@ -1159,6 +1578,50 @@ mod tests {
         assert_eq!(config.grid_height(), 17, "{config:?}");
     }
 
+    #[test]
+    fn samurai_cursor_skips_gaps() {
+        let mut app = App::new(SudokuModel::new(), &Options::default());
+        app.samurai = Some(SamuraiModel::example());
+        app.cursor_x = 8;
+        app.cursor_y = 2;
+        app.step(1, 0);
+        assert_eq!((app.cursor_x, app.cursor_y), (12, 2));
+        app.cursor_x = 3;
+        app.cursor_y = 8;
+        app.step(0, 1);
+        assert_eq!((app.cursor_x, app.cursor_y), (3, 12));
+        app.cursor_x = 20;
+        app.step(1, 0);
+        assert_eq!((app.cursor_x, app.cursor_y), (20, 12));
+    }
+
+    #[test]
+    fn instructions_fit_in_any_language() {
+        for locale in Locale::ALL {
+            let options = Options {
+                locale,
+                ..Options::default()
+            };
+            let app = App::new(SudokuModel::new(), &options);
+            for width in 1..120 {
+                let instructions = instructions(&app, width);
+                assert!(
+                    Line::from(instructions.as_str()).width() <= usize::from(width),
+                    "{locale:?} {width}: {instructions}"
+                );
+            }
+            assert!(instructions(&app, 120).contains(locale.text(Message::Quit)));
+        }
+
+        let mut options = Options::default();
+        options.keys.bind(Action::Quit, 'x').unwrap();
+        let app = App::new(SudokuModel::new(), &options);
+        for width in 1..120 {
+            let instructions = instructions(&app, width);
+            assert!(!instructions.contains('X'), "{width}: {instructions}");
+        }
+    }
+
     #[test]
     fn from_size_21() {
         let config = LayoutConfig::from_size(129, 21);
@@ -0,0 +1,185 @@
+//! Regions ("boxes") of the grid.
+//!
+//! Classic sudoku uses nine 3x3 boxes, jigsaw sudoku uses nine irregular shapes instead. Either way
+//! each region must be a connected group of exactly nine cells, so that every digit appears in it
+//! once.
+
+use core::fmt::{Display, Formatter};
+
+/// Assignment of every cell to one of nine regions, indexed the same way as the model: `[x][y]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Regions {
+    map: [[u8; 9]; 9],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionError {
+    /// Region number is not in `0..9`
+    OutOfRange { x: usize, y: usize, region: u8 },
+    /// Region doesn't have exactly nine cells
+    WrongSize { region: u8, size: usize },
+    /// Region is split into several parts that don't touch each other
+    Disconnected { region: u8 },
+}
+
+impl Display for RegionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RegionError::OutOfRange { x, y, region } => {
+                write!(f, "cell ({x}, {y}) has region {region}, expected 0-8")
+            }
+            RegionError::WrongSize { region, size } => {
+                write!(f, "region {region} has {size} cells, expected 9")
+            }
+            RegionError::Disconnected { region } => write!(f, "region {region} is not connected"),
+        }
+    }
+}
+
+impl core::error::Error for RegionError {}
+
+impl Default for Regions {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl Regions {
+    /// Nine 3x3 boxes of the classic sudoku
+    pub const STANDARD: Regions = {
+        let mut map = [[0; 9]; 9];
+        let mut x = 0;
+        while x < 9 {
+            let mut y = 0;
+            while y < 9 {
+                map[x][y] = (x / 3 + y / 3 * 3) as u8;
+                y += 1;
+            }
+            x += 1;
+        }
+        Regions { map }
+    };
+
+    /// Validates that every region is in `0..9`, connected and has nine cells.
+    pub fn new(map: [[u8; 9]; 9]) -> Result<Self, RegionError> {
+        let mut sizes = [0; 9];
+        let mut starts = [(0, 0); 9];
+        for (x, col) in map.iter().enumerate() {
+            for (y, &region) in col.iter().enumerate() {
+                if region > 8 {
+                    return Err(RegionError::OutOfRange { x, y, region });
+                }
+                if sizes[region as usize] == 0 {
+                    starts[region as usize] = (x, y);
+                }
+                sizes[region as usize] += 1;
+            }
+        }
+        for (region, &size) in sizes.iter().enumerate() {
+            if size != 9 {
+                return Err(RegionError::WrongSize {
+                    region: region as u8,
+                    size,
+                });
+            }
+        }
+
+        // Flood fill from the first cell of each region, a fixed stack is enough for nine cells
+        for (region, &(start_x, start_y)) in starts.iter().enumerate() {
+            let region = region as u8;
+            let mut visited = [[false; 9]; 9];
+            let mut stack = [(0, 0); 9];
+            let mut depth = 1;
+            let mut reached = 1;
+            stack[0] = (start_x, start_y);
+            visited[start_x][start_y] = true;
+            while depth > 0 {
+                depth -= 1;
+                let (x, y) = stack[depth];
+                for (next_x, next_y) in orthogonal(x, y) {
+                    if map[next_x][next_y] == region && !visited[next_x][next_y] {
+                        visited[next_x][next_y] = true;
+                        stack[depth] = (next_x, next_y);
+                        depth += 1;
+                        reached += 1;
+                    }
+                }
+            }
+            if reached != 9 {
+                return Err(RegionError::Disconnected { region });
+            }
+        }
+
+        Ok(Self { map })
+    }
+
+    pub fn region(&self, x: usize, y: usize) -> usize {
+        self.map[x][y] as usize
+    }
+
+    /// Cells of the given region, in column-major order
+    pub fn cells(&self, region: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..9)
+            .flat_map(|x| (0..9).map(move |y| (x, y)))
+            .filter(move |&(x, y)| self.region(x, y) == region)
+    }
+
+    /// Whether the cell is in the same region as its neighbour, neighbours outside the grid are
+    /// never in the same region.
+    pub fn same(&self, x: usize, y: usize, dx: isize, dy: isize) -> bool {
+        match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+            (Some(other_x), Some(other_y)) if other_x < 9 && other_y < 9 => {
+                self.map[x][y] == self.map[other_x][other_y]
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == Self::STANDARD
+    }
+}
+
+/// Orthogonal neighbours of a cell that are still inside the grid
+pub(crate) fn orthogonal(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let x = x.checked_add_signed(dx)?;
+            let y = y.checked_add_signed(dy)?;
+            (x < 9 && y < 9).then_some((x, y))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_is_valid() {
+        assert_eq!(Regions::new(Regions::STANDARD.map), Ok(Regions::STANDARD));
+        assert_eq!(Regions::STANDARD.region(4, 7), 7);
+    }
+
+    #[test]
+    fn wrong_size() {
+        let mut map = Regions::STANDARD.map;
+        map[2][0] = 1;
+        assert_eq!(
+            Regions::new(map),
+            Err(RegionError::WrongSize { region: 0, size: 8 })
+        );
+    }
+
+    #[test]
+    fn disconnected() {
+        let mut map = Regions::STANDARD.map;
+        // swap two corner cells between boxes 0 and 8
+        map[0][0] = 8;
+        map[8][8] = 0;
+        assert_eq!(
+            Regions::new(map),
+            Err(RegionError::Disconnected { region: 0 })
+        );
+    }
+}
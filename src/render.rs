@@ -0,0 +1,504 @@
+//! Headless pictures of a board, as SVG or PNG, without any frontend running.
+//!
+//! The board is first turned into a list of [`Shape`]s, which every output format then draws the
+//! same way. Digits are drawn with strokes instead of a font, so the pictures look the same
+//! everywhere and PNG doesn't need a font rasterizer. Colours are the ones of a [`Palette`], like
+//! in the frontends.
+
+use std::fmt::Write;
+
+use crate::{Colour, Palette, Rgb, SudokuModel, Theme};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    /// Width and height of a cell, in pixels for PNG and in user units for SVG
+    pub cell: f32,
+    /// Candidates of empty cells, as small digits
+    pub notes: bool,
+    /// Red and green backgrounds for conflicts and completed groups
+    pub status: bool,
+    /// Colours of the picture, the light theme's by default, as pictures are mostly printed
+    pub palette: &'static Palette,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            cell: 48.0,
+            notes: false,
+            status: true,
+            palette: Theme::Light.palette(),
+        }
+    }
+}
+
+impl RenderOptions {
+    fn thin(&self) -> f32 {
+        (self.cell / 32.0).max(1.0)
+    }
+
+    fn thick(&self) -> f32 {
+        (self.cell / 12.0).max(2.0)
+    }
+
+    /// Candidates of empty cells, lighter than values
+    fn note(&self) -> Rgb {
+        self.palette.given.mix(self.palette.cell)
+    }
+
+    /// Width and height of the whole picture, the outer border included
+    pub fn size(&self) -> f32 {
+        9.0 * self.cell + self.thick()
+    }
+}
+
+/// Something to draw, coordinates are from the top left corner of the picture
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        fill: Rgb,
+    },
+    /// Round caps and joins, so that lines meet without gaps
+    Polyline {
+        points: Vec<(f32, f32)>,
+        width: f32,
+        stroke: Rgb,
+    },
+}
+
+impl Shape {
+    /// Same shape moved and scaled, so that a board can be placed anywhere on a page
+    pub fn transform(&self, dx: f32, dy: f32, scale: f32) -> Shape {
+        match self {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+            } => Shape::Rect {
+                x: dx + x * scale,
+                y: dy + y * scale,
+                width: width * scale,
+                height: height * scale,
+                fill: *fill,
+            },
+            Shape::Polyline {
+                points,
+                width,
+                stroke,
+            } => Shape::Polyline {
+                points: points
+                    .iter()
+                    .map(|(x, y)| (dx + x * scale, dy + y * scale))
+                    .collect(),
+                width: width * scale,
+                stroke: *stroke,
+            },
+        }
+    }
+}
+
+/// Strokes of every digit in a unit square, `y` goes down
+const GLYPHS: [&[&[(f32, f32)]]; 9] = [
+    &[
+        &[(0.32, 0.26), (0.55, 0.1), (0.55, 0.9)],
+        &[(0.32, 0.9), (0.78, 0.9)],
+    ],
+    &[&[
+        (0.22, 0.26),
+        (0.36, 0.1),
+        (0.64, 0.1),
+        (0.78, 0.25),
+        (0.78, 0.4),
+        (0.22, 0.9),
+        (0.8, 0.9),
+    ]],
+    &[&[
+        (0.22, 0.1),
+        (0.78, 0.1),
+        (0.46, 0.45),
+        (0.64, 0.45),
+        (0.8, 0.6),
+        (0.8, 0.76),
+        (0.64, 0.9),
+        (0.36, 0.9),
+        (0.2, 0.8),
+    ]],
+    &[&[(0.66, 0.9), (0.66, 0.1), (0.16, 0.65), (0.84, 0.65)]],
+    &[&[
+        (0.78, 0.1),
+        (0.27, 0.1),
+        (0.22, 0.46),
+        (0.6, 0.42),
+        (0.8, 0.58),
+        (0.8, 0.76),
+        (0.64, 0.9),
+        (0.32, 0.9),
+        (0.2, 0.8),
+    ]],
+    &[&[
+        (0.74, 0.12),
+        (0.46, 0.1),
+        (0.26, 0.3),
+        (0.2, 0.6),
+        (0.2, 0.76),
+        (0.36, 0.9),
+        (0.64, 0.9),
+        (0.8, 0.76),
+        (0.8, 0.61),
+        (0.64, 0.47),
+        (0.36, 0.47),
+        (0.2, 0.6),
+    ]],
+    &[&[(0.2, 0.1), (0.8, 0.1), (0.4, 0.9)]],
+    &[
+        &[
+            (0.5, 0.47),
+            (0.3, 0.4),
+            (0.25, 0.25),
+            (0.36, 0.1),
+            (0.64, 0.1),
+            (0.75, 0.25),
+            (0.7, 0.4),
+            (0.5, 0.47),
+        ],
+        &[
+            (0.5, 0.47),
+            (0.25, 0.58),
+            (0.2, 0.75),
+            (0.36, 0.9),
+            (0.64, 0.9),
+            (0.8, 0.75),
+            (0.75, 0.58),
+            (0.5, 0.47),
+        ],
+    ],
+    &[&[
+        (0.26, 0.88),
+        (0.54, 0.9),
+        (0.74, 0.7),
+        (0.8, 0.4),
+        (0.8, 0.24),
+        (0.64, 0.1),
+        (0.36, 0.1),
+        (0.2, 0.24),
+        (0.2, 0.39),
+        (0.36, 0.53),
+        (0.64, 0.53),
+        (0.8, 0.4),
+    ]],
+];
+
+/// Strokes of a digit `1..=9` in a box at `(x, y)` with the given size
+pub fn digit(value: u8, x: f32, y: f32, size: f32, width: f32, stroke: Rgb) -> Vec<Shape> {
+    let Some(strokes) = GLYPHS.get((value as usize).wrapping_sub(1)) else {
+        return Vec::new();
+    };
+    strokes
+        .iter()
+        .map(|points| Shape::Polyline {
+            points: points
+                .iter()
+                .map(|(px, py)| (x + px * size, y + py * size))
+                .collect(),
+            width,
+            stroke,
+        })
+        .collect()
+}
+
+/// Everything there is to draw for the board, background first
+pub fn shapes(model: &SudokuModel, options: &RenderOptions) -> Vec<Shape> {
+    let cell = options.cell;
+    let origin = options.thick() / 2.0;
+    let corner = |x: usize, y: usize| (origin + x as f32 * cell, origin + y as f32 * cell);
+    let mut shapes = vec![Shape::Rect {
+        x: 0.0,
+        y: 0.0,
+        width: options.size(),
+        height: options.size(),
+        fill: options.palette.cell,
+    }];
+
+    for y in 0..9 {
+        for x in 0..9 {
+            let fill = match model.colour(x, y) {
+                Colour::Red if options.status => options.palette.conflict,
+                Colour::Green if options.status => options.palette.complete,
+                _ => continue,
+            };
+            let (left, top) = corner(x, y);
+            shapes.push(Shape::Rect {
+                x: left,
+                y: top,
+                width: cell,
+                height: cell,
+                fill,
+            });
+        }
+    }
+
+    // Thin lines between cells of a region first, so that region borders are drawn over them
+    let regions = model.regions();
+    let mut borders = Vec::new();
+    for y in 0..9 {
+        for x in 0..9 {
+            let (left, top) = corner(x, y);
+            if x < 8 {
+                let line = vec![(left + cell, top), (left + cell, top + cell)];
+                if regions.same(x, y, 1, 0) {
+                    shapes.push(Shape::Polyline {
+                        points: line,
+                        width: options.thin(),
+                        stroke: options.palette.border,
+                    });
+                } else {
+                    borders.push(line);
+                }
+            }
+            if y < 8 {
+                let line = vec![(left, top + cell), (left + cell, top + cell)];
+                if regions.same(x, y, 0, 1) {
+                    shapes.push(Shape::Polyline {
+                        points: line,
+                        width: options.thin(),
+                        stroke: options.palette.border,
+                    });
+                } else {
+                    borders.push(line);
+                }
+            }
+        }
+    }
+    let (start, end) = (corner(0, 0), corner(9, 9));
+    borders.push(vec![start, (end.0, start.1), end, (start.0, end.1), start]);
+    shapes.extend(borders.into_iter().map(|points| Shape::Polyline {
+        points,
+        width: options.thick(),
+        stroke: options.palette.given_border,
+    }));
+
+    for y in 0..9 {
+        for x in 0..9 {
+            let (left, top) = corner(x, y);
+            let value = model.get(x, y);
+            if value.value != 0 {
+                let width = if value.enabled() {
+                    cell / 18.0
+                } else {
+                    cell / 12.0
+                };
+                let stroke = options.palette.text(value.origin());
+                let inset = cell * 0.2;
+                shapes.extend(digit(
+                    value.value,
+                    left + inset,
+                    top + inset,
+                    cell - 2.0 * inset,
+                    width,
+                    stroke,
+                ));
+            } else if options.notes {
+                let third = cell / 3.0;
+                for note in model.candidates(x, y).iter() {
+                    let index = note as usize - 1;
+                    let inset = third * 0.2;
+                    shapes.extend(digit(
+                        note,
+                        left + (index % 3) as f32 * third + inset,
+                        top + (index / 3) as f32 * third + inset,
+                        third - 2.0 * inset,
+                        cell / 40.0,
+                        options.note(),
+                    ));
+                }
+            }
+        }
+    }
+
+    shapes
+}
+
+/// SVG elements of the shapes, without the surrounding `<svg>`
+pub fn svg_elements(shapes: &[Shape]) -> String {
+    let mut svg = String::new();
+    for shape in shapes {
+        // Writing to a string can't fail
+        let _ = match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill: Rgb(r, g, b),
+            } => writeln!(
+                svg,
+                r#"<rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{height:.2}" fill="rgb({r},{g},{b})"/>"#
+            ),
+            Shape::Polyline {
+                points,
+                width,
+                stroke: Rgb(r, g, b),
+            } => {
+                let points = points
+                    .iter()
+                    .map(|(x, y)| format!("{x:.2},{y:.2}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(
+                    svg,
+                    r#"<polyline points="{points}" fill="none" stroke="rgb({r},{g},{b})" stroke-width="{width:.2}" stroke-linecap="round" stroke-linejoin="round"/>"#
+                )
+            }
+        };
+    }
+    svg
+}
+
+/// Complete SVG document
+pub fn svg(model: &SudokuModel, options: &RenderOptions) -> String {
+    let size = options.size();
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size:.0}\" height=\"{size:.0}\" viewBox=\"0 0 {size:.2} {size:.2}\">\n{}</svg>\n",
+        svg_elements(&shapes(model, options))
+    )
+}
+
+/// Picture with anti-aliased lines, one pixel per unit
+#[cfg(feature = "image")]
+pub fn png(model: &SudokuModel, options: &RenderOptions) -> image::RgbImage {
+    let size = options.size().ceil() as u32;
+    let mut image = image::RgbImage::new(size, size);
+    for shape in shapes(model, options) {
+        rasterize(&mut image, &shape);
+    }
+    image
+}
+
+/// Writes [`png`] to a file
+#[cfg(feature = "image")]
+pub fn save_png(
+    model: &SudokuModel,
+    options: &RenderOptions,
+    path: impl AsRef<std::path::Path>,
+) -> image::ImageResult<()> {
+    png(model, options).save_with_format(path, image::ImageFormat::Png)
+}
+
+#[cfg(feature = "image")]
+fn rasterize(image: &mut image::RgbImage, shape: &Shape) {
+    let (width, height) = image.dimensions();
+    match shape {
+        Shape::Rect {
+            x,
+            y,
+            width: w,
+            height: h,
+            fill,
+        } => {
+            let x_range = x.round().max(0.0) as u32..(x + w).round().min(width as f32) as u32;
+            for py in y.round().max(0.0) as u32..(y + h).round().min(height as f32) as u32 {
+                for px in x_range.clone() {
+                    image.put_pixel(px, py, image::Rgb([fill.0, fill.1, fill.2]));
+                }
+            }
+        }
+        Shape::Polyline {
+            points,
+            width: stroke_width,
+            stroke,
+        } => {
+            let radius = stroke_width / 2.0;
+            for segment in points.windows(2) {
+                let (a, b) = (segment[0], segment[1]);
+                let left = (a.0.min(b.0) - radius - 1.0).max(0.0) as u32;
+                let top = (a.1.min(b.1) - radius - 1.0).max(0.0) as u32;
+                let right = ((a.0.max(b.0) + radius + 1.0) as u32).min(width);
+                let bottom = ((a.1.max(b.1) + radius + 1.0) as u32).min(height);
+                for py in top..bottom {
+                    for px in left..right {
+                        let distance = distance((px as f32 + 0.5, py as f32 + 0.5), a, b);
+                        let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                        if coverage > 0.0 {
+                            let pixel = image.get_pixel_mut(px, py);
+                            for (channel, target) in
+                                pixel.0.iter_mut().zip([stroke.0, stroke.1, stroke.2])
+                            {
+                                let mixed =
+                                    *channel as f32 * (1.0 - coverage) + target as f32 * coverage;
+                                *channel = mixed.round() as u8;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// From the point to the closest point of the segment
+#[cfg(feature = "image")]
+fn distance(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+    let (x, y) = (a.0 + t * dx, a.1 + t * dy);
+    ((point.0 - x).powi(2) + (point.1 - y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_shows_givens_entries_and_status() {
+        let mut model = SudokuModel::example();
+        model.set(4, 3, 4);
+        let options = RenderOptions::default();
+        let colour =
+            |attribute: &str, Rgb(r, g, b): Rgb| format!(r#"{attribute}="rgb({r},{g},{b})""#);
+        let palette = options.palette;
+        let svg = svg(&model, &options);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(&colour("stroke", palette.player)));
+        assert!(svg.contains(&colour("fill", palette.conflict)));
+        assert!(svg.contains(&colour("fill", palette.complete)));
+        assert!(!svg.contains(&colour("stroke", options.note())));
+
+        let options = RenderOptions {
+            notes: true,
+            status: false,
+            palette: Theme::HighContrast.palette(),
+            ..options
+        };
+        let svg = super::svg(&model, &options);
+        assert!(svg.contains(&colour("stroke", options.note())));
+        assert!(svg.contains(&colour("fill", options.palette.cell)));
+        assert!(!svg.contains(&colour("fill", options.palette.conflict)));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn png_has_the_requested_size() {
+        let options = RenderOptions {
+            cell: 20.0,
+            ..RenderOptions::default()
+        };
+        let image = png(&SudokuModel::jigsaw_example(), &options);
+        assert_eq!(image.width(), options.size().ceil() as u32);
+        // Outer border is drawn like borders of givens, the middle of the first cell is empty
+        let Rgb(r, g, b) = options.palette.given_border;
+        assert_eq!(image.get_pixel(1, 1).0, [r, g, b]);
+        let Rgb(r, g, b) = options.palette.cell;
+        assert_eq!(image.get_pixel(12, 4).0, [r, g, b]);
+    }
+}
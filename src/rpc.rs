@@ -0,0 +1,488 @@
+//! JSON-RPC 2.0 for scripts, tests and bots, over stdio or a local Unix socket. Every request,
+//! response and notification is a single line of JSON, rows and columns are counted from 1:
+//!
+//! ```text
+//! {"jsonrpc": "2.0", "id": 1, "method": "set", "params": {"row": 3, "column": 5, "value": 7}}
+//! {"jsonrpc": "2.0", "id": 1, "result": {"cells": "..3.27...", "cursor": {"row": 3, ...}, ...}}
+//! ```
+//!
+//! Methods are:
+//! - `state`, the board as a line of 81 cells, its givens, the cursor, whether it's solved, and
+//!   the conflicting cells when the assistance shows them
+//! - `set` with a `row`, `column` and `value`, `0` clears the cell
+//! - `clear` with a `row` and `column`
+//! - `move` of the cursor to a `row` and `column`
+//! - `undo`
+//! - `subscribe` and `unsubscribe` to `changed` notifications with the state, which are sent
+//!   after changes made by anyone, the player too
+//!
+//! Cells default to the one under the cursor, and every method returns the new state. Frontends
+//! [`poll`](Rpc::poll) for requests in their own event loops, every [`TICK`], so that the changes
+//! are shown like ones of the player. Frontends without a cursor of their own keep one for this.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{Map, Value, json};
+
+use crate::{Colour, SudokuModel};
+
+/// How often frontends poll for requests
+pub const TICK: Duration = Duration::from_millis(50);
+
+/// Connection that requests came on, pipes and stdio have a single one
+type Client = usize;
+
+type Writers = Arc<Mutex<HashMap<Client, Box<dyn Write + Send>>>>;
+
+/// Line that the client sent, or `None` once it's gone
+type Request = (Client, Option<String>);
+
+pub struct Rpc {
+    requests: Receiver<Request>,
+    writers: Writers,
+    subscribers: Vec<Client>,
+    /// State that subscribers were told about last
+    notified: Option<Value>,
+}
+
+impl Rpc {
+    /// Requests from stdin, responses to stdout
+    pub fn stdio() -> Rpc {
+        Rpc::connect(BufReader::new(io::stdin()), io::stdout())
+    }
+
+    /// Listens on the socket, a stale one of an earlier run is replaced. Every connection is a
+    /// client of its own.
+    #[cfg(unix)]
+    pub fn listen(path: &Path) -> io::Result<Rpc> {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::UnixListener;
+
+        if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        let (sender, requests) = mpsc::channel();
+        let writers = Writers::default();
+        let clients = writers.clone();
+        std::thread::spawn(move || {
+            for (client, stream) in listener.incoming().flatten().enumerate() {
+                let Ok(writer) = stream.try_clone() else {
+                    continue;
+                };
+                clients
+                    .lock()
+                    .expect("writers don't panic")
+                    .insert(client, Box::new(writer));
+                read(client, BufReader::new(stream), sender.clone());
+            }
+        });
+        Ok(Rpc::new(requests, writers))
+    }
+
+    #[cfg(not(unix))]
+    pub fn listen(_path: &Path) -> io::Result<Rpc> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "only Unix sockets are supported, use stdio",
+        ))
+    }
+
+    /// Single client on any reader and writer, e.g. pipes
+    pub fn connect(
+        reader: impl BufRead + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Rpc {
+        let (sender, requests) = mpsc::channel();
+        let writers = Writers::default();
+        writers
+            .lock()
+            .expect("nothing runs yet")
+            .insert(0, Box::new(writer));
+        read(0, reader, sender);
+        Rpc::new(requests, writers)
+    }
+
+    fn new(requests: Receiver<Request>, writers: Writers) -> Rpc {
+        Rpc {
+            requests,
+            writers,
+            subscribers: Vec::new(),
+            notified: None,
+        }
+    }
+
+    /// Handles requests that came since the last poll, and notifies subscribers of changes.
+    /// Returns whether requests changed the board or the cursor, so that it's drawn again.
+    pub fn poll(&mut self, model: &mut SudokuModel, cursor: &mut (usize, usize)) -> bool {
+        let mut changed = false;
+        while let Ok((client, request)) = self.requests.try_recv() {
+            let before = (model.line(), *cursor);
+            self.receive(client, request, model, cursor);
+            changed |= before != (model.line(), *cursor);
+        }
+        if !self.subscribers.is_empty() {
+            let state = state(model, *cursor);
+            if self.notified.as_ref() != Some(&state) {
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "changed",
+                    "params": state,
+                });
+                for client in self.subscribers.clone() {
+                    self.send(client, &notification);
+                }
+                self.notified = Some(state);
+            }
+        }
+        changed
+    }
+
+    /// Responds to the request, or forgets the client once it's gone. Writers are removed here
+    /// rather than by readers, so that responses to the last requests are still sent.
+    fn receive(
+        &mut self,
+        client: Client,
+        request: Option<String>,
+        model: &mut SudokuModel,
+        cursor: &mut (usize, usize),
+    ) {
+        match request {
+            Some(request) => {
+                if let Some(response) = self.handle(client, &request, model, cursor) {
+                    self.send(client, &response);
+                }
+            }
+            None => {
+                self.writers
+                    .lock()
+                    .expect("writers don't panic")
+                    .remove(&client);
+                self.subscribers.retain(|subscriber| *subscriber != client);
+            }
+        }
+    }
+
+    /// Response to the request, notifications, valid requests without an `id`, get none
+    fn handle(
+        &mut self,
+        client: Client,
+        request: &str,
+        model: &mut SudokuModel,
+        cursor: &mut (usize, usize),
+    ) -> Option<Value> {
+        let request = match serde_json::from_str::<Value>(request) {
+            Ok(Value::Object(request)) => request,
+            Ok(_) => return Some(response(Value::Null, Err(RpcError::Request))),
+            Err(_) => return Some(response(Value::Null, Err(RpcError::Parse))),
+        };
+        let id = request.get("id").cloned();
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) => {
+                let _input = tracing::debug_span!("input", source = "rpc", method).entered();
+                let params = match request.get("params") {
+                    Some(Value::Object(params)) => params.clone(),
+                    None => Map::new(),
+                    Some(_) => return Some(response(id?, Err(RpcError::Params("params")))),
+                };
+                self.call(client, method, &params, model, cursor)
+            }
+            // Without a method it isn't a notification, so it's answered even without an `id`
+            None => return Some(response(id.unwrap_or_default(), Err(RpcError::Request))),
+        };
+        Some(response(id?, result))
+    }
+
+    fn call(
+        &mut self,
+        client: Client,
+        method: &str,
+        params: &Map<String, Value>,
+        model: &mut SudokuModel,
+        cursor: &mut (usize, usize),
+    ) -> Result<Value, RpcError> {
+        match method {
+            "state" => {}
+            "set" | "clear" => {
+                let (x, y) = cell(params, *cursor)?;
+                let value = match method {
+                    "set" => params
+                        .get("value")
+                        .and_then(Value::as_u64)
+                        .filter(|value| *value <= 9)
+                        .ok_or(RpcError::Params("value"))? as u8,
+                    _ => 0,
+                };
+                if !model.get(x, y).enabled() {
+                    return Err(RpcError::Given(x, y));
+                }
+                model.set(x, y, value);
+            }
+            "move" => *cursor = cell(params, *cursor)?,
+            "undo" => {
+                if !model.undo() {
+                    return Err(RpcError::Undo);
+                }
+            }
+            "subscribe" => {
+                if !self.subscribers.contains(&client) {
+                    self.subscribers.push(client);
+                }
+                self.notified = Some(state(model, *cursor));
+            }
+            "unsubscribe" => self.subscribers.retain(|subscriber| *subscriber != client),
+            _ => return Err(RpcError::Method(method.to_string())),
+        }
+        Ok(state(model, *cursor))
+    }
+
+    /// Clients that are gone stop being subscribers
+    fn send(&mut self, client: Client, message: &Value) {
+        let mut writers = self.writers.lock().expect("writers don't panic");
+        let sent = writers.get_mut(&client).is_some_and(|writer| {
+            writeln!(writer, "{message}")
+                .and_then(|_| writer.flush())
+                .is_ok()
+        });
+        if !sent {
+            writers.remove(&client);
+            self.subscribers.retain(|subscriber| *subscriber != client);
+        }
+    }
+}
+
+/// Reads requests of the client, line by line, until it's gone
+fn read(client: Client, reader: impl BufRead + Send + 'static, sender: Sender<Request>) {
+    std::thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !line.trim().is_empty() && sender.send((client, Some(line))).is_err() {
+                return;
+            }
+        }
+        let _ = sender.send((client, None));
+    });
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": error.code(), "message": error.to_string()},
+        }),
+    }
+}
+
+/// Cell of the `row` and `column`, or the cursor without them
+fn cell(params: &Map<String, Value>, cursor: (usize, usize)) -> Result<(usize, usize), RpcError> {
+    let coordinate = |name| {
+        params
+            .get(name)
+            .map(|value| {
+                value
+                    .as_u64()
+                    .filter(|value| (1..=9).contains(value))
+                    .map(|value| value as usize - 1)
+                    .ok_or(RpcError::Params(name))
+            })
+            .transpose()
+    };
+    match (coordinate("column")?, coordinate("row")?) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        (None, None) => Ok(cursor),
+        (None, Some(_)) => Err(RpcError::Params("column")),
+        (Some(_), None) => Err(RpcError::Params("row")),
+    }
+}
+
+fn position(x: usize, y: usize) -> Value {
+    json!({"row": y + 1, "column": x + 1})
+}
+
+pub fn state(model: &SudokuModel, (x, y): (usize, usize)) -> Value {
+    let conflicts: Vec<_> = (0..9)
+        .flat_map(|y| (0..9).map(move |x| (x, y)))
+        .filter(|&(x, y)| matches!(model.colour(x, y), Colour::Red))
+        .map(|(x, y)| position(x, y))
+        .collect();
+    json!({
+        "cells": model.line(),
+        "givens": model.givens_line(),
+        "cursor": position(x, y),
+        "solved": model.is_solved(),
+        "conflicts": conflicts,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RpcError {
+    Parse,
+    Request,
+    Method(String),
+    /// Parameter that is missing or invalid
+    Params(&'static str),
+    Given(usize, usize),
+    Undo,
+}
+
+impl RpcError {
+    /// Codes of JSON-RPC itself are negative, ones of the game positive
+    fn code(&self) -> i64 {
+        match self {
+            RpcError::Parse => -32700,
+            RpcError::Request => -32600,
+            RpcError::Method(_) => -32601,
+            RpcError::Params(_) => -32602,
+            RpcError::Given(..) => 1,
+            RpcError::Undo => 2,
+        }
+    }
+}
+
+impl Display for RpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Parse => write!(f, "not JSON"),
+            RpcError::Request => write!(f, "not a request"),
+            RpcError::Method(method) => write!(f, "unknown method `{method}`"),
+            RpcError::Params(name) => write!(f, "missing or invalid `{name}`"),
+            RpcError::Given(x, y) => write!(f, "row {}, column {} is given", y + 1, x + 1),
+            RpcError::Undo => write!(f, "nothing to undo"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    /// Writer of a test that it can read back
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Responses to the requests, once the reader thread has read all of them
+    fn run(requests: &str, model: &mut SudokuModel, cursor: &mut (usize, usize)) -> Vec<Value> {
+        let output = Output::default();
+        let mut rpc = Rpc::connect(io::Cursor::new(requests.to_string()), output.clone());
+        loop {
+            let (client, request) = rpc.requests.recv_timeout(Duration::from_secs(5)).unwrap();
+            let gone = request.is_none();
+            rpc.receive(client, request, model, cursor);
+            if gone {
+                break;
+            }
+        }
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn requests_change_the_board() {
+        let mut model = SudokuModel::from_line(PUZZLE).unwrap();
+        let mut cursor = (0, 0);
+        let responses = run(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "set", "params": {"row": 1, "column": 1, "value": 4}}
+{"jsonrpc": "2.0", "id": 2, "method": "move", "params": {"row": 1, "column": 2}}
+{"jsonrpc": "2.0", "id": 3, "method": "set", "params": {"value": 4}}
+{"jsonrpc": "2.0", "method": "undo"}
+{"jsonrpc": "2.0", "id": "state", "method": "state"}
+"#,
+            &mut model,
+            &mut cursor,
+        );
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(
+            responses[2]["result"]["conflicts"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        let state = &responses[3]["result"];
+        assert_eq!(responses[3]["id"], "state");
+        assert_eq!(state["cells"].as_str().unwrap()[..3].to_string(), "4.3");
+        assert_eq!(state["cursor"], json!({"row": 1, "column": 2}));
+        assert_eq!(cursor, (1, 0));
+        assert_eq!(model.get(0, 0).value(), 4);
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        let mut model = SudokuModel::from_line(PUZZLE).unwrap();
+        let responses = run(
+            "not json\n[1]\n\
+             {\"id\": 1, \"method\": \"jump\"}\n\
+             {\"id\": 2, \"method\": \"set\", \"params\": {\"row\": 10, \"column\": 1, \"value\": 1}}\n\
+             {\"id\": 3, \"method\": \"clear\", \"params\": {\"row\": 1, \"column\": 3}}\n\
+             {\"id\": 4, \"method\": \"undo\"}\n\
+             {\"params\": {}}\n\
+             {\"method\": \"jump\"}\n",
+            &mut model,
+            &mut (0, 0),
+        );
+        let codes: Vec<_> = responses
+            .iter()
+            .map(|response| response["error"]["code"].as_i64().unwrap())
+            .collect();
+        assert_eq!(codes, [-32700, -32600, -32601, -32602, 1, 2, -32600]);
+        assert_eq!(responses[4]["error"]["message"], "row 1, column 3 is given");
+        assert_eq!(responses[6]["id"], Value::Null);
+    }
+
+    #[test]
+    fn subscribers_are_notified_of_changes() {
+        let mut model = SudokuModel::from_line(PUZZLE).unwrap();
+        let output = Output::default();
+        let (sender, requests) = mpsc::channel();
+        let writers = Writers::default();
+        writers.lock().unwrap().insert(0, Box::new(output.clone()));
+        let mut rpc = Rpc::new(requests, writers);
+        sender
+            .send((0, Some(r#"{"id": 1, "method": "subscribe"}"#.to_string())))
+            .unwrap();
+        rpc.poll(&mut model, &mut (0, 0));
+        rpc.poll(&mut model, &mut (0, 0));
+        // Changes of the player are noticed on the next poll
+        model.set(0, 0, 4);
+        rpc.poll(&mut model, &mut (0, 0));
+        rpc.poll(&mut model, &mut (0, 0));
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let messages: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(messages.len(), 2, "{output}");
+        assert_eq!(messages[1]["method"], "changed");
+        assert_eq!(
+            messages[1]["params"]["cells"].as_str().unwrap()[..1].to_string(),
+            "4"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Samurai sudoku: five overlapping 9x9 grids on a 21x21 board, where the middle grid shares its
+//! corner boxes with the four outer grids.
+//!
+//! Every grid is a regular [`SudokuModel`], and shared cells are stored in each grid that contains
+//! them, so conflicts within a grid are found the usual way, and shared boxes are checked by both
+//! grids.
+
+use crate::{Assist, Candidates, Colour, SudokuModel, SudokuValue};
+
+/// Width and height of the board in cells
+pub const SIZE: usize = 21;
+
+/// Top left corners of the grids on the board
+pub const GRIDS: [(usize, usize); 5] = [(0, 0), (12, 0), (6, 6), (0, 12), (12, 12)];
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SamuraiModel {
+    grids: [SudokuModel; 5],
+}
+
+/// Values are indexed `[x][y]` on the 21x21 board, like [`SudokuModel`] does, values outside of
+/// the grids are ignored.
+impl From<[[u8; SIZE]; SIZE]> for SamuraiModel {
+    fn from(value: [[u8; SIZE]; SIZE]) -> Self {
+        let mut result = SamuraiModel::default();
+        for (x, col) in value.iter().enumerate() {
+            for (y, num) in col.iter().enumerate() {
+                if *num != 0 && Self::contains(x, y) {
+                    result.set(x, y, *num);
+                    result.set_enabled(x, y, false);
+                }
+            }
+        }
+        for grid in &mut result.grids {
+            grid.clear_history();
+        }
+        result
+    }
+}
+
+impl SamuraiModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn example() -> Self {
+        let mut board = [[0; SIZE]; SIZE];
+        for (y, line) in EXAMPLE.iter().enumerate() {
+            for (x, char) in line.chars().enumerate() {
+                board[x][y] = char.to_digit(10).unwrap_or(0) as u8;
+            }
+        }
+        Self::from(board)
+    }
+
+    /// Same assistance for every grid
+    pub fn with_assist(mut self, assist: Assist) -> Self {
+        for grid in &mut self.grids {
+            grid.set_assist(assist);
+        }
+        self
+    }
+
+    pub fn grid(&self, index: usize) -> &SudokuModel {
+        &self.grids[index]
+    }
+
+    /// Whether the cell of the board belongs to any grid
+    pub fn contains(x: usize, y: usize) -> bool {
+        Self::locate(x, y).next().is_some()
+    }
+
+    /// Grids that contain the cell of the board, with coordinates of the cell within each grid
+    pub fn locate(x: usize, y: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+        GRIDS
+            .into_iter()
+            .enumerate()
+            .filter_map(move |(index, (left, top))| {
+                let local_x = x.checked_sub(left).filter(|&x| x < 9)?;
+                let local_y = y.checked_sub(top).filter(|&y| y < 9)?;
+                Some((index, local_x, local_y))
+            })
+    }
+
+    /// Value of the cell, if it is on any grid
+    pub fn get(&self, x: usize, y: usize) -> Option<&SudokuValue> {
+        Self::locate(x, y)
+            .next()
+            .map(|(index, x, y)| self.grids[index].get(x, y))
+    }
+
+    pub fn text(&self, x: usize, y: usize) -> &str {
+        self.get(x, y).map_or(" ", SudokuValue::text)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: u8) {
+        for (index, x, y) in Self::locate(x, y) {
+            self.grids[index].set(x, y, value);
+        }
+    }
+
+    pub fn set_enabled(&mut self, x: usize, y: usize, enabled: bool) {
+        for (index, x, y) in Self::locate(x, y) {
+            self.grids[index].set_enabled(x, y, enabled);
+        }
+    }
+
+    pub fn add(&mut self, x: usize, y: usize, value: i8) {
+        if let Some(current) = self.get(x, y) {
+            self.set(x, y, current.value.wrapping_add_signed(value));
+        }
+    }
+
+    /// Conflict in any of the grids wins over a completed row, column or box in another one
+    pub fn colour(&self, x: usize, y: usize) -> Colour {
+        let mut result = Colour::Black;
+        for (index, x, y) in Self::locate(x, y) {
+            match self.grids[index].colour(x, y) {
+                Colour::Red => return Colour::Red,
+                Colour::Green => result = Colour::Green,
+                Colour::Black => {}
+            }
+        }
+        result
+    }
+
+    /// Whether two neighbouring cells of the board are in the same box
+    pub fn same_box(&self, x: usize, y: usize, dx: isize, dy: isize) -> bool {
+        let (Some(other_x), Some(other_y)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+        else {
+            return false;
+        };
+        Self::locate(x, y).any(|(index, local_x, local_y)| {
+            Self::locate(other_x, other_y).any(|(other_index, other_local_x, other_local_y)| {
+                let regions = self.grids[index].regions();
+                index == other_index
+                    && regions.region(local_x, local_y)
+                        == regions.region(other_local_x, other_local_y)
+            })
+        })
+    }
+
+    /// Digits allowed by every grid that contains the cell
+    pub fn candidates(&self, x: usize, y: usize) -> Candidates {
+        let mut result = Candidates::ALL;
+        for (index, x, y) in Self::locate(x, y) {
+            let allowed = self.grids[index].candidates(x, y);
+            for value in result.iter() {
+                if !allowed.contains(value) {
+                    result.remove(value);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.grids.iter().all(SudokuModel::is_solved)
+    }
+
+    pub fn solve(&self) -> Option<SamuraiModel> {
+        let mut result = None;
+        let mut work = *self;
+        if !work.grids.iter().any(SudokuModel::has_conflicts) {
+            work.backtrack(&mut |solved| {
+                result = Some(*solved);
+                false
+            });
+        }
+        result
+    }
+
+    /// Counts solutions, but stops as soon as `limit` is reached. Use `2` to check uniqueness.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut count = 0;
+        let mut work = *self;
+        if limit > 0 && !work.grids.iter().any(SudokuModel::has_conflicts) {
+            work.backtrack(&mut |_| {
+                count += 1;
+                count < limit
+            });
+        }
+        count
+    }
+
+    /// Same approach as the solver for a single grid, but going over all cells of the board
+    fn backtrack(&mut self, found: &mut impl FnMut(&SamuraiModel) -> bool) -> bool {
+        let mut best = None;
+        for x in 0..SIZE {
+            for y in 0..SIZE {
+                if self.get(x, y).is_none_or(|value| value.value != 0) {
+                    continue;
+                }
+                let candidates = self.candidates(x, y);
+                if candidates.is_empty() {
+                    return true;
+                }
+                if best.is_none_or(|(_, _, best): (_, _, Candidates)| candidates.len() < best.len())
+                {
+                    best = Some((x, y, candidates));
+                }
+            }
+        }
+
+        let Some((x, y, candidates)) = best else {
+            return found(self);
+        };
+        for value in candidates.iter() {
+            self.put(x, y, value);
+            if !self.backtrack(found) {
+                return false;
+            }
+        }
+        self.put(x, y, 0);
+        true
+    }
+
+    /// Writes the value into every grid, ignoring whether the cell is enabled
+    fn put(&mut self, x: usize, y: usize, value: u8) {
+        for (index, x, y) in Self::locate(x, y) {
+            self.grids[index].get_mut(x, y).value = value;
+        }
+    }
+}
+
+/// Rows of the example board, anything but digits is an empty cell
+const EXAMPLE: [&str; SIZE] = [
+    ".84..2.5.   61.......",
+    "6........   ..738...2",
+    ".5.3....2   ......9.7",
+    "846....7.   ...5.....",
+    ".2.5.....   ..384.1..",
+    "........9   .....9.73",
+    "....95.3......5......",
+    ".....84.1.9.7...612..",
+    "...4.129..........738",
+    "      ...61.9.7      ",
+    "      ......3..      ",
+    "      .57.....2      ",
+    "..95..8..12...3..6.2.",
+    ".73...1.....8.612....",
+    "....2.573...1..57....",
+    "..5...4..   ....6...5",
+    "....61...   ...2...3.",
+    "...29.73.   ....384..",
+    "957......   .8.......",
+    "...6.....   .1295....",
+    ".......84   9....46..",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_boxes_stay_in_sync() {
+        let mut model = SamuraiModel::new();
+        model.set(7, 7, 3);
+        assert_eq!(model.grid(0).get(7, 7).value, 3);
+        assert_eq!(model.grid(2).get(1, 1).value, 3);
+        assert_eq!(model.get(9, 3).map(|value| value.value), None);
+    }
+
+    #[test]
+    fn conflict_across_grids() {
+        let mut model = SamuraiModel::new();
+        // middle grid's top left box is the bottom right box of the first grid
+        model.set(6, 6, 5);
+        model.set(14, 6, 5);
+        assert!(matches!(model.colour(6, 6), Colour::Red));
+        assert!(matches!(model.colour(14, 6), Colour::Red));
+        assert!(matches!(model.colour(14, 7), Colour::Black));
+    }
+
+    #[test]
+    fn example_is_unique() {
+        let model = SamuraiModel::example();
+        assert_eq!(model.count_solutions(2), 1);
+        assert!(model.solve().unwrap().is_solved());
+    }
+}
@@ -0,0 +1,190 @@
+//! Log of a game session as JSON lines, to analyse later or to attach to bug reports. Every event
+//! of the game is a line with the seconds since the session started, fields of the event, and
+//! fields of the spans it happened in, in the order of their names, e.g. a click that changed a
+//! cell:
+//!
+//! ```text
+//! {"button":"primary","column":5,"level":"DEBUG","message":"edit","new":1,"old":0,"origin":"Player","row":3,"source":"click","target":"gui_experiment","time":12.345}
+//! ```
+//!
+//! Frontends put handling of the player's input in `input` spans with its `source`, a `click`,
+//! a `key`, a `command` of the plain text or a request of [`rpc`](crate::rpc), and the model
+//! emits an event for every edit, undo and annotation, with the [`Origin`](crate::Origin) of the
+//! value, e.g. a hint. Edits outside of input, like building puzzles or restoring progress of a
+//! pack, are left out.
+
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, EnteredSpan, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span of a click on a cell, until it's dropped
+pub fn click(button: &str) -> EnteredSpan {
+    tracing::debug_span!("input", source = "click", button).entered()
+}
+
+/// Layer that writes events as JSON lines, each one as soon as it happens
+pub struct SessionLog {
+    start: Instant,
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl SessionLog {
+    /// Log in a new file, or in the one that is there, replacing it
+    pub fn create(path: &Path) -> io::Result<SessionLog> {
+        Ok(SessionLog::new(LineWriter::new(File::create(path)?)))
+    }
+
+    pub fn new(output: impl Write + Send + 'static) -> SessionLog {
+        SessionLog {
+            start: Instant::now(),
+            output: Mutex::new(Box::new(output)),
+        }
+    }
+}
+
+impl<S> Layer<S> for SessionLog
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, context: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attributes.record(&mut fields);
+        if let Some(span) = context.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, context: Context<'_, S>) {
+        if let Some(span) = context.span(id)
+            && let Some(fields) = span.extensions_mut().get_mut::<Fields>()
+        {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, context: Context<'_, S>) {
+        let scope = context.event_scope(event);
+        if scope.is_none() && *event.metadata().level() > Level::INFO {
+            return;
+        }
+        let mut line = Map::new();
+        let millis = self.start.elapsed().as_millis() as f64;
+        line.insert("time".into(), (millis / 1000.0).into());
+        line.insert("level".into(), event.metadata().level().as_str().into());
+        line.insert("target".into(), event.metadata().target().into());
+        // Fields of inner spans, and of the event itself, win over the ones of outer spans
+        let mut fields = Fields::default();
+        for span in scope.into_iter().flat_map(|scope| scope.from_root()) {
+            if let Some(span_fields) = span.extensions().get::<Fields>() {
+                fields.0.extend(span_fields.0.clone());
+            }
+        }
+        event.record(&mut fields);
+        line.extend(fields.0);
+        let mut output = self.output.lock().expect("writing doesn't panic");
+        // A log that can't be written shouldn't stop the game
+        let _ = writeln!(output, "{}", Value::Object(line));
+    }
+}
+
+/// Fields of a span or an event, numbers and flags stay JSON ones, the rest is text
+#[derive(Default)]
+struct Fields(Map<String, Value>);
+
+impl Visit for Fields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::{Origin, SudokuModel};
+
+    /// Writer of a test that it can read back
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn edits_are_logged_with_their_input() {
+        let output = Output::default();
+        let subscriber = tracing_subscriber::registry().with(SessionLog::new(output.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let mut model = SudokuModel::example();
+            {
+                let _input = click("primary");
+                model.set(2, 5, 5);
+            }
+            let _input = tracing::debug_span!("input", source = "key", key = "?").entered();
+            model.place(3, 5, 7, Origin::Hint);
+            model.undo();
+        });
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3, "{output}");
+        assert_eq!(lines[0]["message"], "edit");
+        assert_eq!(lines[0]["source"], "click");
+        assert_eq!(lines[0]["button"], "primary");
+        assert_eq!(lines[0]["row"], 6);
+        assert_eq!(lines[0]["column"], 3);
+        assert_eq!(lines[0]["old"], 0);
+        assert_eq!(lines[0]["new"], 5);
+        assert_eq!(lines[0]["origin"], "Player");
+        assert!(lines[0]["time"].is_f64());
+        assert_eq!(lines[1]["origin"], "Hint");
+        assert_eq!(lines[1]["source"], "key");
+        assert_eq!(lines[1].get("button"), None);
+        assert_eq!(lines[2]["message"], "undo");
+        assert_eq!(lines[2]["old"], 7);
+    }
+}
@@ -15,67 +15,184 @@
 //! You should have received a copy of the GNU General Public License
 //! along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use slint::platform::PointerEventButton;
 use slint::private_unstable_api::re_exports::PointerEventKind;
-use slint::{Color, Model, PlatformError};
+use slint::{Color, Model, PlatformError, Timer, TimerMode, VecModel};
 
-use crate::{Colour, SudokuModel};
+use crate::assets;
+use crate::locale::Message;
+use crate::rpc::{self, Rpc};
+use crate::session;
+use crate::{Action, Options, Pack, Palette, Rgb, SudokuModel};
 
 slint::include_modules!();
 
-impl From<Colour> for Color {
-    fn from(value: Colour) -> Self {
-        match value {
-            Colour::Black => Color::from_rgb_u8(64, 64, 64),
-            Colour::Red => Color::from_rgb_u8(128, 32, 32),
-            Colour::Green => Color::from_rgb_u8(32, 128, 32),
-        }
+impl From<Rgb> for Color {
+    fn from(Rgb(r, g, b): Rgb) -> Self {
+        Color::from_rgb_u8(r, g, b)
     }
 }
 
-pub fn main(mut sudoku_model: SudokuModel) -> Result<(), PlatformError> {
+/// Size of cells in `ui/main.slint`, without zoom
+const CELL_SIZE: f32 = 50.0;
+
+/// The grid is laid out at a fixed size, so the scale and the cell size only zoom it, through
+/// `SLINT_SCALE_FACTOR` that Slint reads when the window is created. The next puzzle of the pack
+/// that isn't solved is played on [`Action::Next`], progress is saved when the window is closed.
+pub fn main(
+    pack: Pack,
+    puzzle: usize,
+    options: Options,
+    rpc: Option<Rpc>,
+) -> Result<(), PlatformError> {
+    let zoom = options.zoom(CELL_SIZE);
+    if zoom != 1.0 {
+        // SAFETY: nothing else runs yet to read the environment at the same time
+        unsafe { std::env::set_var("SLINT_SCALE_FACTOR", zoom.to_string()) };
+    }
+    // Slint loads fonts only by path, an override of the assets replaces the default font, the
+    // compiled in one is imported by `ui/main.slint`
+    let font = assets::path(assets::FONT);
+    if let Some(path) = &font {
+        // SAFETY: as above
+        unsafe { std::env::set_var("SLINT_DEFAULT_FONT", path) };
+    }
     let ui = MainWindow::new()?;
+    if font.is_none() {
+        ui.set_font_family(assets::font_family().into());
+    }
+    let palette = options.theme.palette();
+    let sudoku_model = pack.model(puzzle).with_assist(options.assist);
+    ui.set_board_background(palette.background.into());
+    ui.set_window_title(options.locale.text(Message::Title).into());
     let tiles = (0..9)
-        .flat_map(|y| {
-            (0..9).map(move |x| TileData {
-                color: sudoku_model.colour(x, y).into(),
-                enabled: sudoku_model.get(x, y).enabled,
-                text: sudoku_model.text(x, y).into(),
-            })
-        })
+        .flat_map(|y| (0..9).map(move |x| tile(palette, &sudoku_model, x, y)))
         .collect::<Vec<_>>();
-    let tiles_model = std::rc::Rc::new(slint::VecModel::from(tiles));
+    let tiles_model = Rc::new(VecModel::from(tiles));
+    let sudoku_model = Rc::new(RefCell::new(sudoku_model));
+    // Puzzle of the pack that is played, givens change with it
+    let playing = Rc::new(RefCell::new((pack, puzzle)));
 
     ui.set_tiles(tiles_model.clone().into());
 
-    ui.on_click(move |event, x, y| {
-        // info!(?event);
-        let value = match event.kind {
-            PointerEventKind::Up => match event.button {
-                PointerEventButton::Left => 1,
-                PointerEventButton::Right => -1,
+    // Requests are handled every tick, the board has no cursor, so one is kept for them
+    let timer = Timer::default();
+    if let Some(mut rpc) = rpc {
+        let sudoku_model = sudoku_model.clone();
+        let tiles_model = tiles_model.clone();
+        let mut cursor = (0, 0);
+        timer.start(TimerMode::Repeated, rpc::TICK, move || {
+            if rpc.poll(&mut sudoku_model.borrow_mut(), &mut cursor) {
+                refresh(palette, &tiles_model, &sudoku_model.borrow());
+            }
+        });
+    }
+
+    ui.on_key({
+        let sudoku_model = sudoku_model.clone();
+        let tiles_model = tiles_model.clone();
+        let playing = playing.clone();
+        move |key| {
+            if !key.starts_with(options.keys.key(Action::Next)) {
+                return false;
+            }
+            let _input = tracing::debug_span!("input", source = "key", key = "next").entered();
+            let (pack, puzzle) = &mut *playing.borrow_mut();
+            let mut model = sudoku_model.borrow_mut();
+            pack.next(puzzle, &mut model);
+            refresh(palette, &tiles_model, &model);
+            true
+        }
+    });
+
+    ui.on_click({
+        let sudoku_model = sudoku_model.clone();
+        move |event, x, y| {
+            if !matches!(event.kind, PointerEventKind::Up) {
+                return;
+            }
+            let x = x as usize;
+            let y = y as usize;
+            let mut model = sudoku_model.borrow_mut();
+            match event.button {
+                PointerEventButton::Left => {
+                    let _input = session::click("primary");
+                    model.add(x, y, 1)
+                }
+                PointerEventButton::Right => {
+                    let _input = session::click("secondary");
+                    model.add(x, y, -1)
+                }
+                PointerEventButton::Middle => {
+                    let _input = session::click("middle");
+                    model.cycle_annotation(x, y)
+                }
                 _ => return,
-            },
-            _ => return,
-        };
-        let x = x as usize;
-        let y = y as usize;
-        sudoku_model.add(x, y, value);
-        for x in 0..9 {
-            for y in 0..9 {
-                tiles_model.set_row_data(
-                    x + 9 * y,
-                    TileData {
-                        color: sudoku_model.colour(x, y).into(),
-                        enabled: sudoku_model.get(x, y).enabled,
-                        text: sudoku_model.text(x, y).into(),
-                    },
-                );
             }
+            refresh(palette, &tiles_model, &model);
         }
     });
 
     ui.run()?;
 
+    let (pack, puzzle) = &mut *playing.borrow_mut();
+    pack.record(*puzzle, &sudoku_model.borrow());
+    if let Err(err) = pack.save_progress() {
+        tracing::warn!("progress of the pack can't be saved: {err}");
+    }
     Ok(())
 }
+
+/// Status colour of the tile, mixed half and half with the colour the player marked it with.
+/// Tiles have gaps between 3x3 regions, or are tinted by their region for irregular ones, whose
+/// borders aren't straight lines.
+fn tile(palette: &Palette, model: &SudokuModel, x: usize, y: usize) -> TileData {
+    let regions = model.regions();
+    let standard = regions.is_standard();
+    let mut colour = palette.cell(model.colour(x, y));
+    if !standard && colour == palette.cell {
+        colour = palette.region_tints(regions)[regions.region(x, y)];
+    }
+    let gaps = |boundaries: usize| match standard {
+        true => boundaries as i32,
+        false => 0,
+    };
+    if let Some(annotation) = model.annotation(x, y) {
+        colour = colour.mix(palette.annotation(annotation));
+    }
+    let enabled = model.get(x, y).enabled();
+    let marked = model.is_marked(x, y);
+    let border = match (marked, enabled) {
+        (true, _) => palette.marked,
+        (false, true) => palette.border,
+        (false, false) => palette.given_border,
+    };
+    TileData {
+        column: x as i32,
+        row: y as i32,
+        column_gaps: gaps((0..x).filter(|&left| !regions.same(left, y, 1, 0)).count()),
+        row_gaps: gaps(
+            (0..y)
+                .filter(|&above| !regions.same(x, above, 0, 1))
+                .count(),
+        ),
+        color: colour.into(),
+        hover_color: colour.mix(palette.hover).into(),
+        border_color: border.into(),
+        enabled,
+        text_color: palette.text(model.origin(x, y)).into(),
+        marked,
+        text: model.text(x, y).into(),
+    }
+}
+
+fn refresh(palette: &Palette, tiles_model: &VecModel<TileData>, model: &SudokuModel) {
+    for x in 0..9 {
+        for y in 0..9 {
+            tiles_model.set_row_data(x + 9 * y, tile(palette, model, x, y));
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Simple backtracking solver, that always continues from the cell with the fewest candidates.
+//!
+//! It only relies on candidates of the cell, so it works the same for classic and jigsaw regions,
+//! and respects any extra constraints.
+//!
+//! Other backends, like [`Dlx`](crate::Dlx), implement the same [`Solver`] trait.
+
+use crate::{Assist, Origin, SudokuModel};
+
+/// Set of digits that can still be placed into a cell
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Candidates(u16);
+
+impl Candidates {
+    pub const ALL: Candidates = Candidates(0b11_1111_1110);
+
+    pub fn contains(&self, value: u8) -> bool {
+        value != 0 && value < 10 && self.0 & (1 << value) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn remove(&mut self, value: u8) {
+        if value < 10 {
+            self.0 &= !(1 << value);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u8> + use<> {
+        let bits = self.0;
+        (1..=9).filter(move |value| bits & (1 << value) != 0)
+    }
+}
+
+/// Search backend, so that different algorithms can be swapped and compared
+pub trait Solver {
+    /// Calls `found` for every solution until it returns `false`. Values entered by the player are
+    /// kept as if they were given, and nothing is found if they already conflict.
+    fn search(&mut self, model: &SudokuModel, found: &mut dyn FnMut(&SudokuModel) -> bool);
+
+    fn solve(&mut self, model: &SudokuModel) -> Option<SudokuModel> {
+        let mut result = None;
+        self.search(model, &mut |solved| {
+            result = Some(*solved);
+            false
+        });
+        result
+    }
+
+    /// Stops as soon as `limit` is reached, use `2` to check uniqueness
+    fn count_solutions(&mut self, model: &SudokuModel, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            self.search(model, &mut |_| {
+                count += 1;
+                count < limit
+            });
+        }
+        count
+    }
+}
+
+/// The default backend, needs no memory besides a copy of the model
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Backtracking;
+
+impl Solver for Backtracking {
+    fn search(&mut self, model: &SudokuModel, found: &mut dyn FnMut(&SudokuModel) -> bool) {
+        let mut work = *model;
+        if !work.has_conflicts() {
+            backtrack(&mut work, found);
+        }
+    }
+}
+
+impl SudokuModel {
+    /// Digits that don't clash with any peer of an empty cell, nothing for a filled cell.
+    pub fn candidates(&self, x: usize, y: usize) -> Candidates {
+        if self.get(x, y).value != 0 {
+            return Candidates::default();
+        }
+        let mut result = Candidates::ALL;
+        for (peer_x, peer_y) in self.peers(x, y) {
+            result.remove(self.get(peer_x, peer_y).value);
+        }
+        for constraint in self.constraints() {
+            for value in result.iter() {
+                if !constraint.allows(self, x, y, value) {
+                    result.remove(value);
+                }
+            }
+        }
+        result
+    }
+
+    /// Every cell is filled, and there are no conflicts
+    pub fn is_solved(&self) -> bool {
+        (0..9).all(|x| (0..9).all(|y| self.get(x, y).value != 0)) && !self.has_conflicts()
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        (0..9).any(|x| {
+            (0..9).any(|y| {
+                let value = self.get(x, y).value;
+                value != 0
+                    && (self
+                        .peers(x, y)
+                        .any(|(peer_x, peer_y)| self.get(peer_x, peer_y).value == value)
+                        || self
+                            .constraints()
+                            .any(|constraint| !constraint.allows(self, x, y, value)))
+            })
+        })
+    }
+
+    /// First solution found, values entered by the player are kept as if they were given.
+    pub fn solve(&self) -> Option<SudokuModel> {
+        Backtracking.solve(self)
+    }
+
+    /// Counts solutions, but stops as soon as `limit` is reached. Use `2` to check uniqueness.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        Backtracking.count_solutions(self, limit)
+    }
+
+    /// Reveals the value of the empty cell with the fewest candidates, if the grid can still be
+    /// solved and hints are allowed by [`Assist::Full`]. Returns the cell that was filled.
+    pub fn hint(&mut self) -> Option<(usize, usize)> {
+        if self.assist() != Assist::Full {
+            return None;
+        }
+        let solution = self.solve()?;
+        let (x, y) = (0..9)
+            .flat_map(|y| (0..9).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(x, y).value == 0)
+            .min_by_key(|&(x, y)| self.candidates(x, y).len())?;
+        self.place(x, y, solution.get(x, y).value, Origin::Hint);
+        Some((x, y))
+    }
+
+    /// Fills every empty cell that has a single candidate, returns how many cells were filled.
+    /// Candidates are taken before filling anything, so cells don't depend on each other. Nothing
+    /// is filled without [`Assist::Full`].
+    pub fn fill_singles(&mut self) -> usize {
+        if self.assist() != Assist::Full {
+            return 0;
+        }
+        let mut singles = [[0; 9]; 9];
+        for (x, column) in singles.iter_mut().enumerate() {
+            for (y, single) in column.iter_mut().enumerate() {
+                let candidates = self.candidates(x, y);
+                if candidates.len() == 1 {
+                    *single = candidates.iter().next().unwrap_or_default();
+                }
+            }
+        }
+        let mut count = 0;
+        for (x, column) in singles.iter().enumerate() {
+            for (y, &value) in column.iter().enumerate() {
+                if value != 0 {
+                    self.place(x, y, value, Origin::AutoFill);
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+/// Fills the grid recursively, `found` is called for every solution and decides whether to go on.
+fn backtrack(model: &mut SudokuModel, found: &mut dyn FnMut(&SudokuModel) -> bool) -> bool {
+    let mut best = None;
+    for x in 0..9 {
+        for y in 0..9 {
+            if model.get(x, y).value != 0 {
+                continue;
+            }
+            let candidates = model.candidates(x, y);
+            if candidates.is_empty() {
+                return true;
+            }
+            if best.is_none_or(|(_, _, best): (_, _, Candidates)| candidates.len() < best.len()) {
+                best = Some((x, y, candidates));
+            }
+        }
+    }
+
+    let Some((x, y, candidates)) = best else {
+        return found(model);
+    };
+    for value in candidates.iter() {
+        model.get_mut(x, y).value = value;
+        if !backtrack(model, found) {
+            return false;
+        }
+    }
+    model.get_mut(x, y).value = 0;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_is_solvable() {
+        let model = SudokuModel::example();
+        let solution = model.solve().unwrap();
+        assert!(solution.is_solved());
+        assert_eq!(solution.get(0, 0).value, 1);
+    }
+
+    #[test]
+    fn jigsaw_is_unique() {
+        let model = SudokuModel::jigsaw_example();
+        assert_eq!(model.count_solutions(2), 1);
+        let solution = model.solve().unwrap();
+        assert!(solution.is_solved());
+        for region in 0..9 {
+            let mut seen = Candidates::ALL;
+            for (x, y) in solution.regions().cells(region) {
+                assert!(seen.contains(solution.get(x, y).value));
+                seen.remove(solution.get(x, y).value);
+            }
+        }
+    }
+
+    #[test]
+    fn assisted_cells_are_counted() {
+        let mut model = SudokuModel::from_line(
+            "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+        )
+        .unwrap();
+        let givens = model.stats().given;
+        let filled = model.fill_singles();
+        assert!(filled > 0);
+        let (x, y) = model.hint().unwrap();
+        assert_eq!(model.origin(x, y), Origin::Hint);
+        model.set(x, y, 0);
+        let stats = model.stats();
+        assert_eq!(stats.given, givens);
+        assert_eq!(stats.auto_fill, filled);
+        assert_eq!(stats.assisted(), filled);
+        assert_eq!(model.solve().unwrap().stats().player, 81 - givens - filled);
+    }
+
+    #[test]
+    fn assistance_can_be_turned_off() {
+        let mut model = SudokuModel::example().with_assist(Assist::Conflicts);
+        model.set(3, 1, 6);
+        assert_eq!(model.fill_singles(), 0);
+        assert_eq!(model.hint(), None);
+        assert!(matches!(model.colour(3, 1), crate::Colour::Red));
+        model.set_assist(Assist::Off);
+        assert!(matches!(model.colour(3, 1), crate::Colour::Black));
+        assert_eq!(Assist::by_name("OFF"), Some(Assist::Off));
+    }
+
+    #[test]
+    fn conflicting_has_no_solution() {
+        let mut model = SudokuModel::new();
+        model.set(0, 0, 5);
+        model.set(0, 8, 5);
+        assert_eq!(model.solve().map(|_| ()), None);
+    }
+}
@@ -0,0 +1,336 @@
+//! Colours of the board, the same in every frontend. Frontends convert [`Rgb`] to their own colour
+//! type, and take everything they draw from the [`Palette`] of the [`Theme`], so that switching
+//! themes looks the same everywhere.
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::{Colour, Origin, PALETTE, Regions};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Theme {
+    Light,
+    #[default]
+    Dark,
+    /// White on black, with saturated colours, and no shades that only differ a little
+    HighContrast,
+    /// Light, with the colours of Okabe and Ito that can be told apart with any colour vision
+    /// deficiency, so conflicts are orange rather than red, and completed groups blue
+    ColourBlind,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [
+        Theme::Light,
+        Theme::Dark,
+        Theme::HighContrast,
+        Theme::ColourBlind,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+            Theme::ColourBlind => "colour-blind",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Theme> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Whether widgets that aren't drawn from the palette, like scroll bars, should be dark
+    pub fn is_dark(&self) -> bool {
+        matches!(self, Theme::Dark | Theme::HighContrast)
+    }
+
+    pub fn palette(&self) -> &'static Palette {
+        match self {
+            Theme::Light => &LIGHT,
+            Theme::Dark => &DARK,
+            Theme::HighContrast => &HIGH_CONTRAST,
+            Theme::ColourBlind => &COLOUR_BLIND,
+        }
+    }
+}
+
+/// Colour as red, green and blue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// `0xRRGGBB`
+    pub const fn hex(hex: u32) -> Rgb {
+        Rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    /// Halfway between the two colours
+    pub fn mix(self, other: Rgb) -> Rgb {
+        let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+        Rgb(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+
+    pub fn to_f32(self) -> [f32; 3] {
+        [self.0, self.1, self.2].map(|channel| channel as f32 / 255.0)
+    }
+}
+
+/// `#rrggbb`, as in CSS and SVG
+impl Display for Rgb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl From<[u8; 3]> for Rgb {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Rgb(r, g, b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Behind the board
+    pub background: Rgb,
+    /// Cells without a status, and the colour that cells under the pointer are mixed with
+    pub cell: Rgb,
+    pub hover: Rgb,
+    /// Cells of groups with a conflict, see [`Colour::Red`]
+    pub conflict: Rgb,
+    /// Cells of groups that are complete, see [`Colour::Green`]
+    pub complete: Rgb,
+    /// Text of values by their [`Origin`]
+    pub given: Rgb,
+    pub player: Rgb,
+    pub hint: Rgb,
+    pub auto_fill: Rgb,
+    pub imported: Rgb,
+    /// Borders of cells, of givens, and of cells on a diagonal or in a window of extra rules
+    pub border: Rgb,
+    pub given_border: Rgb,
+    pub marked: Rgb,
+    /// The cell under the cursor, in frontends that have one
+    pub cursor: Rgb,
+    /// Colours players mark cells with, by annotations `1..=9`
+    pub annotations: [Rgb; 9],
+}
+
+impl Palette {
+    /// Background of a cell of the status
+    pub fn cell(&self, colour: Colour) -> Rgb {
+        match colour {
+            Colour::Black => self.cell,
+            Colour::Red => self.conflict,
+            Colour::Green => self.complete,
+        }
+    }
+
+    /// Text of a value of the origin
+    pub fn text(&self, origin: Origin) -> Rgb {
+        match origin {
+            Origin::Given => self.given,
+            Origin::Player => self.player,
+            Origin::Hint => self.hint,
+            Origin::AutoFill => self.auto_fill,
+            Origin::Imported => self.imported,
+        }
+    }
+
+    /// Colour of an annotation `1..=9`, see [`SudokuModel::annotation`](crate::SudokuModel)
+    pub fn annotation(&self, annotation: u8) -> Rgb {
+        self.annotations[annotation as usize - 1]
+    }
+
+    /// Background colours for regions, so that neighbouring regions never share one
+    pub fn region_tints(&self, regions: &Regions) -> [Rgb; 9] {
+        let shades = [
+            self.cell,
+            self.cell.mix(self.cell.mix(self.hover)),
+            self.cell.mix(self.hover),
+            self.hover,
+        ];
+        let mut tints = [None; 9];
+        for region in 0..9 {
+            let mut used = [false; 4];
+            for (x, y) in regions.cells(region) {
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    if let (Some(other_x), Some(other_y)) =
+                        (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                        && other_x < 9
+                        && other_y < 9
+                        && let Some(tint) = tints[regions.region(other_x, other_y)]
+                    {
+                        used[tint] = true;
+                    }
+                }
+            }
+            // four colours are always enough for a planar map, but greedy choice may need more
+            tints[region] = Some(
+                used.iter()
+                    .position(|used| !used)
+                    .unwrap_or(region % shades.len()),
+            );
+        }
+        tints.map(|tint| shades[tint.unwrap_or_default()])
+    }
+}
+
+const fn annotations(palette: [[u8; 3]; 9]) -> [Rgb; 9] {
+    let mut annotations = [Rgb(0, 0, 0); 9];
+    let mut index = 0;
+    while index < 9 {
+        let [r, g, b] = palette[index];
+        annotations[index] = Rgb(r, g, b);
+        index += 1;
+    }
+    annotations
+}
+
+const LIGHT: Palette = Palette {
+    background: Rgb::hex(0xe4e4e4),
+    cell: Rgb::hex(0xf8f8f8),
+    hover: Rgb::hex(0xb0b0b0),
+    conflict: Rgb::hex(0xf2b8b8),
+    complete: Rgb::hex(0xbfe3bf),
+    given: Rgb::hex(0x000000),
+    player: Rgb::hex(0x2458b8),
+    hint: Rgb::hex(0xb07d00),
+    auto_fill: Rgb::hex(0x8048c0),
+    imported: Rgb::hex(0x1f8aa8),
+    border: Rgb::hex(0x999999),
+    given_border: Rgb::hex(0x000000),
+    marked: Rgb::hex(0x4d80e6),
+    cursor: Rgb::hex(0xfff0a0),
+    annotations: annotations(PALETTE),
+};
+
+const DARK: Palette = Palette {
+    background: Rgb::hex(0x1b1b1b),
+    cell: Rgb::hex(0x404040),
+    hover: Rgb::hex(0x808080),
+    conflict: Rgb::hex(0x802020),
+    complete: Rgb::hex(0x206420),
+    given: Rgb::hex(0xaaaaaa),
+    player: Rgb::hex(0xffffff),
+    hint: Rgb::hex(0xe6b428),
+    auto_fill: Rgb::hex(0xaa78e6),
+    imported: Rgb::hex(0x50aac8),
+    border: Rgb::hex(0x707070),
+    given_border: Rgb::hex(0xcccccc),
+    marked: Rgb::hex(0x4d80e6),
+    cursor: Rgb::hex(0x2a3a5a),
+    annotations: annotations(PALETTE),
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    background: Rgb::hex(0x000000),
+    cell: Rgb::hex(0x000000),
+    hover: Rgb::hex(0x808080),
+    conflict: Rgb::hex(0x900000),
+    complete: Rgb::hex(0x006000),
+    given: Rgb::hex(0xffffff),
+    player: Rgb::hex(0xffff00),
+    hint: Rgb::hex(0x00ffff),
+    auto_fill: Rgb::hex(0xff80ff),
+    imported: Rgb::hex(0x80ff80),
+    border: Rgb::hex(0xffffff),
+    given_border: Rgb::hex(0xffffff),
+    marked: Rgb::hex(0x00a0ff),
+    cursor: Rgb::hex(0x0000c0),
+    annotations: annotations([
+        [255, 0, 0],
+        [255, 128, 0],
+        [255, 255, 0],
+        [0, 255, 0],
+        [0, 255, 255],
+        [0, 96, 255],
+        [160, 0, 255],
+        [255, 0, 255],
+        [160, 160, 160],
+    ]),
+};
+
+const COLOUR_BLIND: Palette = Palette {
+    background: Rgb::hex(0xe4e4e4),
+    cell: Rgb::hex(0xf8f8f8),
+    hover: Rgb::hex(0xb0b0b0),
+    conflict: Rgb::hex(0xf8cca8),
+    complete: Rgb::hex(0xc4e2f6),
+    given: Rgb::hex(0x000000),
+    player: Rgb::hex(0x0072b2),
+    hint: Rgb::hex(0xa06000),
+    auto_fill: Rgb::hex(0xa04080),
+    imported: Rgb::hex(0x009e73),
+    border: Rgb::hex(0x999999),
+    given_border: Rgb::hex(0x000000),
+    marked: Rgb::hex(0x0072b2),
+    cursor: Rgb::hex(0xf0e442),
+    annotations: annotations([
+        [230, 159, 0],
+        [86, 180, 233],
+        [0, 158, 115],
+        [240, 228, 66],
+        [0, 114, 178],
+        [213, 94, 0],
+        [204, 121, 167],
+        [100, 100, 100],
+        [190, 190, 190],
+    ]),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Contrast ratio of WCAG
+    fn contrast(a: Rgb, b: Rgb) -> f32 {
+        let luminance = |colour: Rgb| {
+            let [r, g, b] = colour.to_f32().map(|channel| {
+                if channel <= 0.03928 {
+                    channel / 12.92
+                } else {
+                    ((channel + 0.055) / 1.055).powf(2.4)
+                }
+            });
+            0.2126 * r + 0.7152 * g + 0.0722 * b
+        };
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn values_can_be_read_on_every_cell() {
+        for theme in Theme::ALL {
+            let palette = theme.palette();
+            let minimum = match theme {
+                Theme::HighContrast => 7.0,
+                _ => 3.0,
+            };
+            for cell in [palette.cell, palette.conflict, palette.complete] {
+                for text in [palette.given, palette.player] {
+                    let contrast = contrast(text, cell);
+                    assert!(
+                        contrast >= minimum,
+                        "{theme:?}: {text:?} on {cell:?} {contrast}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn themes_are_named() {
+        for theme in Theme::ALL {
+            assert_eq!(Theme::by_name(theme.name()), Some(theme));
+        }
+        assert_eq!(Theme::by_name("High-Contrast"), Some(Theme::HighContrast));
+        assert_eq!(Rgb::hex(0x4d80e6), Rgb(77, 128, 230));
+        assert_eq!(Rgb(77, 128, 230).to_string(), "#4d80e6");
+    }
+}
@@ -6,11 +6,13 @@ use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{Element, Event, HtmlButtonElement};
 
-use crate::{Colour, SudokuModel};
+use crate::locale::Message;
+use crate::{Colour, Locale, Origin, Pack, Palette, SudokuModel, Theme};
 
 #[wasm_bindgen(start)]
 pub fn main() -> Result<(), JsValue> {
-    let sudoku_model = Rc::new(RefCell::new(SudokuModel::example()));
+    // There is no file system to read packs from or save progress to, so the built-in pack is used
+    let sudoku_model = Rc::new(RefCell::new(Pack::default_pack().model(0)));
 
     // Use `web_sys`'s global `window` function to get a handle on the global
     // window object.
@@ -18,23 +20,57 @@ pub fn main() -> Result<(), JsValue> {
     let document = window.document().expect("should have a document on window");
     let body = document.body().expect("document should have a body");
 
+    // Colours of the theme, `?theme=light` picks another one than the default
+    let search = window.location().search()?;
+    let theme = search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("theme="))
+        .and_then(Theme::by_name)
+        .unwrap_or_default();
+    // Language of the browser, there's no environment to take the locale from
+    let locale = window
+        .navigator()
+        .language()
+        .and_then(|language| Locale::by_name(&language))
+        .unwrap_or_default();
+    document.set_title(locale.text(Message::Title));
+
+    let style = document.create_element("style")?;
+    style.set_text_content(Some(&variables(theme.palette())));
+    body.append_child(&style)?;
+
     // Manufacture the element we're gonna append
     let table = document.create_element("table")?;
     table.set_class_name("sudoku-table");
     let table_body = document.create_element("tbody")?;
     let buttons = Rc::new(RefCell::new(Vec::new()));
+    // Gaps between 3x3 regions, or regions tinted for irregular ones, whose borders aren't
+    // straight lines
+    let regions = *sudoku_model.borrow().regions();
+    let standard = regions.is_standard();
+    let tints = theme.palette().region_tints(&regions);
     for y in 0..9 {
         let tr = document.create_element("tr")?;
-        tr.set_class_name("sudoku-row");
         for x in 0..9 {
             let td = document.create_element("td")?;
-            td.set_class_name("sudoku-col");
+            let right = standard && x < 8 && !regions.same(x, y, 1, 0);
+            let bottom = standard && y < 8 && !regions.same(x, y, 0, 1);
+            td.set_class_name(&format!(
+                "sudoku-col{}{}",
+                if right { " gap-right" } else { "" },
+                if bottom { " gap-bottom" } else { "" },
+            ));
 
             let button = document.create_element("button")?;
             set_button_values(sudoku_model.borrow(), &button, x, y);
+            if !standard {
+                button
+                    .set_attribute("style", &format!("--tint: {}", tints[regions.region(x, y)]))?;
+            }
             button.set_attribute("x", &x.to_string())?;
             button.set_attribute("y", &y.to_string())?;
-            if !sudoku_model.borrow().get(x, y).enabled {
+            if !sudoku_model.borrow().get(x, y).enabled() {
                 button.set_attribute("disabled", "")?;
             } else {
                 let model = sudoku_model.clone();
@@ -76,17 +112,41 @@ pub fn main() -> Result<(), JsValue> {
     Ok(())
 }
 
+/// Colours of the palette as CSS variables, that `www/styles.css` uses
+fn variables(palette: &Palette) -> String {
+    format!(
+        ":root {{ --background: {}; --cell: {}; --hover: {}; --conflict: {}; --complete: {}; \
+         --given: {}; --player: {}; --hint: {}; --auto-fill: {}; --imported: {}; --border: {}; \
+         --given-border: {}; --marked: {}; }}",
+        palette.background,
+        palette.cell,
+        palette.cell.mix(palette.hover),
+        palette.conflict,
+        palette.complete,
+        palette.given,
+        palette.player,
+        palette.hint,
+        palette.auto_fill,
+        palette.imported,
+        palette.border,
+        palette.given_border,
+        palette.marked,
+    )
+}
+
 fn set_button_values(model: Ref<SudokuModel>, button: &Element, x: usize, y: usize) {
     button.set_inner_html(model.text(x, y));
-    match model.colour(x, y) {
-        Colour::Black => {
-            button.set_class_name("sudoku-cell");
-        }
-        Colour::Red => {
-            button.set_class_name("sudoku-cell red");
-        }
-        Colour::Green => {
-            button.set_class_name("sudoku-cell green");
-        }
-    }
+    let colour = match model.colour(x, y) {
+        Colour::Black => "",
+        Colour::Red => " red",
+        Colour::Green => " green",
+    };
+    let marked = if model.is_marked(x, y) { " marked" } else { "" };
+    let origin = match model.origin(x, y) {
+        Origin::Given | Origin::Player => "",
+        Origin::Hint => " hint",
+        Origin::AutoFill => " auto-fill",
+        Origin::Imported => " imported",
+    };
+    button.set_class_name(&format!("sudoku-cell{colour}{marked}{origin}"));
 }
@@ -0,0 +1,78 @@
+/* Exercises the C ABI the way an embedding program would, returns the number of failed checks. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "sudoku.h"
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: failed: %s\n", __FILE__, __LINE__, #condition); \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+static const char *GRID_01 =
+    "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+int c_api_test(void) {
+    int failures = 0;
+    char line[SUDOKU_LINE_SIZE];
+
+    SudokuModel *board = sudoku_load(GRID_01);
+    CHECK(board != NULL);
+    CHECK(sudoku_get(board, 2, 0) == 3);
+    CHECK(sudoku_is_given(board, 2, 0));
+    CHECK(!sudoku_set(board, 2, 0, 4));
+    CHECK(sudoku_count_solutions(board, 2) == 1);
+
+    /* A second 3 in the first row */
+    CHECK(sudoku_set(board, 0, 0, 3));
+    CHECK(sudoku_status(board, 0, 0) == SUDOKU_STATUS_CONFLICT);
+    CHECK(!sudoku_solve(board));
+    CHECK(sudoku_set(board, 0, 0, 0));
+
+    CHECK(sudoku_save(board, line, sizeof line) == SUDOKU_LINE_SIZE);
+    CHECK(strcmp(line, GRID_01) == 0);
+    CHECK(sudoku_solve(board));
+    CHECK(sudoku_get(board, 0, 0) == 4);
+    CHECK(sudoku_status(board, 0, 0) == SUDOKU_STATUS_COMPLETE);
+    CHECK(sudoku_save(board, line, 10) == SUDOKU_LINE_SIZE);
+    sudoku_free(board);
+
+    /* Givens and entries saved apart come back as they were */
+    board = sudoku_load(GRID_01);
+    CHECK(sudoku_set(board, 0, 0, 4));
+    char givens[SUDOKU_LINE_SIZE];
+    char entries[SUDOKU_LINE_SIZE];
+    CHECK(sudoku_save_givens(board, givens, sizeof givens) == SUDOKU_LINE_SIZE);
+    CHECK(sudoku_save_entries(board, entries, sizeof entries) == SUDOKU_LINE_SIZE);
+    CHECK(strcmp(givens, GRID_01) == 0);
+    CHECK(entries[0] == '4' && entries[1] == '.' && entries[2] == '.');
+    sudoku_free(board);
+    board = sudoku_load(givens);
+    CHECK(sudoku_load_entries(board, entries));
+    CHECK(sudoku_get(board, 0, 0) == 4);
+    CHECK(!sudoku_is_given(board, 0, 0));
+    CHECK(sudoku_is_given(board, 2, 0));
+    CHECK(!sudoku_load_entries(board, "not a line"));
+    sudoku_free(board);
+
+    CHECK(sudoku_load("not a puzzle") == NULL);
+    CHECK(sudoku_get(NULL, 0, 0) == 0);
+    sudoku_free(NULL);
+
+    board = sudoku_new();
+    CHECK(sudoku_set(board, 8, 8, 9));
+    CHECK(!sudoku_set(board, 9, 0, 1));
+    CHECK(!sudoku_set(board, 0, 0, 10));
+    CHECK(sudoku_status(board, 8, 8) == SUDOKU_STATUS_NORMAL);
+    sudoku_free(board);
+
+    board = sudoku_generate(7);
+    CHECK(sudoku_count_solutions(board, 2) == 1);
+    sudoku_free(board);
+
+    return failures;
+}
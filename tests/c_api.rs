@@ -0,0 +1,29 @@
+//! Checks the C header, and runs the C program from `tests/c`, that the build script compiles
+//! against the generated header and links into tests only.
+
+use std::ffi::c_int;
+use std::path::Path;
+
+// The C program calls back into the library
+extern crate gui_experiment;
+
+unsafe extern "C" {
+    fn c_api_test() -> c_int;
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let generated = std::fs::read_to_string(Path::new(env!("OUT_DIR")).join("sudoku.h")).unwrap();
+    let checked_in =
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("include/sudoku.h"));
+    assert!(
+        checked_in.is_ok_and(|header| header == generated),
+        "include/sudoku.h is out of date, regenerate it with\n\
+         cbindgen --config cbindgen.toml --output include/sudoku.h src/capi.rs"
+    );
+}
+
+#[test]
+fn c_program_passes() {
+    assert_eq!(unsafe { c_api_test() }, 0, "see failed checks above");
+}